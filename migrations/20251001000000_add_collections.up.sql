@@ -0,0 +1,20 @@
+-- Table for user collections. A user may keep several collections.
+-- Each is tied to the API key that created it, which alone may change it; collections
+-- without one are only changed by admins.
+CREATE TABLE IF NOT EXISTS collections (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    owner TEXT, -- e.g., a user ID; NULL for shared collections
+    api_key_id INTEGER REFERENCES api_keys(id)
+);
+
+-- Owned quantities of each printing within a collection.
+-- Rows are removed when their quantity drops to zero.
+CREATE TABLE IF NOT EXISTS collection_items (
+    collection_id INTEGER NOT NULL,
+    printing_id INTEGER NOT NULL,
+    quantity INTEGER NOT NULL CHECK(quantity > 0),
+    PRIMARY KEY (collection_id, printing_id),
+    FOREIGN KEY(collection_id) REFERENCES collections(id) ON DELETE CASCADE,
+    FOREIGN KEY(printing_id) REFERENCES printings(id)
+);
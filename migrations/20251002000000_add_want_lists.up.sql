@@ -0,0 +1,15 @@
+-- Cards a collection wants to acquire. A want either targets a specific
+-- printing, or a base card where any of its printings is acceptable.
+CREATE TABLE IF NOT EXISTS collection_wants (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    collection_id INTEGER NOT NULL,
+    printing_id INTEGER,
+    card_id INTEGER,
+    quantity INTEGER NOT NULL CHECK(quantity > 0),
+    CHECK((printing_id IS NULL) <> (card_id IS NULL)),
+    UNIQUE(collection_id, printing_id),
+    UNIQUE(collection_id, card_id),
+    FOREIGN KEY(collection_id) REFERENCES collections(id) ON DELETE CASCADE,
+    FOREIGN KEY(printing_id) REFERENCES printings(id),
+    FOREIGN KEY(card_id) REFERENCES cards(id)
+);
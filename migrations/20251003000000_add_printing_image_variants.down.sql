@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE printings DROP COLUMN medium_url;
+ALTER TABLE printings DROP COLUMN thumbnail_url;
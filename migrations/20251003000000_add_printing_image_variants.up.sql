@@ -0,0 +1,3 @@
+-- Resized copies of locally stored printing images, for card grids and detail pages.
+ALTER TABLE printings ADD COLUMN thumbnail_url TEXT;
+ALTER TABLE printings ADD COLUMN medium_url TEXT;
@@ -0,0 +1,3 @@
+-- The perceptual hash of a locally stored printing image, used to identify cards
+-- from photos. Stored as the signed reinterpretation of the 64-bit hash.
+ALTER TABLE printings ADD COLUMN perceptual_hash INTEGER;
@@ -0,0 +1,11 @@
+-- API keys authorizing writes. Only a hash of each secret is stored; the full key
+-- is shown once, when it is issued.
+CREATE TABLE IF NOT EXISTS api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL, -- who or what the key was issued to
+    role TEXT NOT NULL CHECK(role IN ('reader', 'editor', 'admin')),
+    key_prefix TEXT NOT NULL UNIQUE, -- the public part of the key, used to look it up
+    secret_hash TEXT NOT NULL, -- hex-encoded SHA-256 of the secret part
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TEXT
+);
@@ -0,0 +1,16 @@
+-- Every change to cards and reference data, with the state before and after it as JSON.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    actor TEXT NOT NULL, -- the name of the API key, or of the local tool, that made the change
+    api_key_id INTEGER,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    action TEXT NOT NULL CHECK(action IN ('create', 'update', 'delete')),
+    entity_kind TEXT NOT NULL CHECK(entity_kind IN ('card', 'set', 'group', 'unit', 'rarity', 'name_variant', 'group_variant')),
+    entity_key TEXT NOT NULL,
+    before TEXT, -- NULL for creations
+    after TEXT, -- NULL for deletions
+    FOREIGN KEY(api_key_id) REFERENCES api_keys(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_entity ON audit_log (entity_kind, entity_key);
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log (created_at);
@@ -0,0 +1,10 @@
+-- Snapshots of each card after every change, as `FullCard` JSON, numbered from 1 per card.
+CREATE TABLE IF NOT EXISTS card_revisions (
+    card_id INTEGER NOT NULL,
+    revision INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    actor TEXT NOT NULL,
+    snapshot TEXT NOT NULL,
+    PRIMARY KEY (card_id, revision),
+    FOREIGN KEY(card_id) REFERENCES cards(id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Anything left in the trash becomes live again.
+ALTER TABLE units DROP COLUMN deleted_at;
+ALTER TABLE groups DROP COLUMN deleted_at;
+ALTER TABLE sets DROP COLUMN deleted_at;
@@ -0,0 +1,4 @@
+-- Deleted sets, groups and units are kept in the trash until purged.
+ALTER TABLE sets ADD COLUMN deleted_at TEXT;
+ALTER TABLE groups ADD COLUMN deleted_at TEXT;
+ALTER TABLE units ADD COLUMN deleted_at TEXT;
@@ -0,0 +1,5 @@
+ALTER TABLE sets DROP COLUMN sort_order;
+ALTER TABLE sets DROP COLUMN language;
+ALTER TABLE sets DROP COLUMN total_cards;
+ALTER TABLE sets DROP COLUMN product_type;
+ALTER TABLE sets DROP COLUMN release_date;
@@ -0,0 +1,17 @@
+-- Describes the products sets were released as, and orders them.
+ALTER TABLE sets ADD COLUMN release_date TEXT; -- e.g., '2025-04-26'
+ALTER TABLE sets ADD COLUMN product_type TEXT
+    CHECK(product_type IN ('Booster', 'PremiumBooster', 'StartDeck', 'Promo'));
+ALTER TABLE sets ADD COLUMN total_cards INTEGER -- The official number of cards in the set
+    CHECK(total_cards BETWEEN 0 AND 999);
+ALTER TABLE sets ADD COLUMN language TEXT NOT NULL DEFAULT 'ja'; -- e.g., 'ja', 'en'
+ALTER TABLE sets ADD COLUMN sort_order INTEGER;
+
+-- The initial sets were added in release order.
+UPDATE sets SET sort_order = id;
+UPDATE sets SET product_type = CASE
+    WHEN set_code = 'PR' THEN 'Promo'
+    WHEN set_code LIKE '%SD%' THEN 'StartDeck'
+    WHEN set_code LIKE 'PB%' THEN 'PremiumBooster'
+    WHEN set_code LIKE 'BP%' THEN 'Booster'
+END;
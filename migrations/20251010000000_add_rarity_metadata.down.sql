@@ -0,0 +1,12 @@
+DROP TABLE set_rarities;
+
+-- Unlisted rarities are read as 'Regular' again, so drop the regular ones listed
+-- by the up migration unless a printing still uses them.
+DELETE FROM rarities
+WHERE rarity_code IN ('N', 'R', 'R+', 'L', 'PE', 'SEC', 'PR')
+    AND rarity_code NOT IN (SELECT rarity_code FROM printings);
+
+ALTER TABLE rarities DROP COLUMN description;
+ALTER TABLE rarities DROP COLUMN is_secret;
+ALTER TABLE rarities DROP COLUMN sort_rank;
+ALTER TABLE rarities DROP COLUMN display_name;
@@ -0,0 +1,36 @@
+-- Describes rarities beyond their type, and lets sets declare the rarities they contain.
+ALTER TABLE rarities ADD COLUMN display_name TEXT NOT NULL DEFAULT ''; -- e.g., 'Secret Rare'
+ALTER TABLE rarities ADD COLUMN sort_rank INTEGER NOT NULL DEFAULT 0; -- Lower ranks sort first
+ALTER TABLE rarities ADD COLUMN is_secret BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE rarities ADD COLUMN description TEXT;
+
+-- Unknown rarity codes are no longer read as 'Regular', so list every known one,
+-- including any already in use.
+INSERT INTO rarities (rarity_code, rarity_type, display_name, sort_rank, is_secret) VALUES
+    ('N',   'Regular',  'Normal',               10, 0),
+    ('R',   'Regular',  'Rare',                 20, 0),
+    ('R+',  'Regular',  'Rare+',                30, 0),
+    ('L',   'Regular',  'Live',                 40, 0),
+    ('PE',  'Regular',  'Energy',               50, 0),
+    ('P',   'Parallel', 'Parallel',             60, 0),
+    ('P+',  'Parallel', 'Parallel+',            70, 0),
+    ('PE+', 'Parallel', 'Parallel Energy',      80, 0),
+    ('SEC', 'Regular',  'Secret Rare',          90, 1),
+    ('LLE', 'Parallel', 'Love Live! Edition',  100, 1),
+    ('PR',  'Regular',  'Promo',               110, 0)
+ON CONFLICT (rarity_code) DO UPDATE SET
+    display_name = excluded.display_name,
+    sort_rank = excluded.sort_rank,
+    is_secret = excluded.is_secret;
+INSERT INTO rarities (rarity_code, rarity_type)
+    SELECT DISTINCT rarity_code, rarity_type FROM printings WHERE true
+ON CONFLICT (rarity_code) DO NOTHING;
+UPDATE rarities SET display_name = rarity_code WHERE display_name = '';
+UPDATE rarities SET sort_rank = 1000 WHERE sort_rank = 0;
+
+-- The rarities a set contains. Sets that declare none accept any known rarity.
+CREATE TABLE set_rarities (
+    set_code TEXT NOT NULL REFERENCES sets(set_code) ON UPDATE CASCADE ON DELETE CASCADE,
+    rarity_code TEXT NOT NULL REFERENCES rarities(rarity_code) ON UPDATE CASCADE ON DELETE CASCADE,
+    PRIMARY KEY (set_code, rarity_code)
+);
@@ -0,0 +1,23 @@
+CREATE TABLE audit_log_old (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    actor TEXT NOT NULL,
+    api_key_id INTEGER,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    action TEXT NOT NULL CHECK(action IN ('create', 'update', 'delete')),
+    entity_kind TEXT NOT NULL CHECK(entity_kind IN ('card', 'set', 'group', 'unit', 'rarity', 'name_variant', 'group_variant')),
+    entity_key TEXT NOT NULL,
+    before TEXT,
+    after TEXT,
+    FOREIGN KEY(api_key_id) REFERENCES api_keys(id)
+);
+INSERT INTO audit_log_old SELECT * FROM audit_log WHERE entity_kind != 'translation';
+DROP TABLE audit_log;
+ALTER TABLE audit_log_old RENAME TO audit_log;
+CREATE INDEX idx_audit_log_entity ON audit_log (entity_kind, entity_key);
+CREATE INDEX idx_audit_log_created_at ON audit_log (created_at);
+
+DROP TABLE skill_translations;
+DROP TABLE set_translations;
+DROP TABLE unit_translations;
+DROP TABLE group_translations;
+DROP TABLE name_translations;
@@ -0,0 +1,56 @@
+-- Translations of card names, groups, units, sets and skills, one per language.
+-- Languages are lowercase codes, e.g. 'en' or 'zh-tw'.
+CREATE TABLE name_translations (
+    name_id INTEGER NOT NULL REFERENCES names(id) ON DELETE CASCADE,
+    language TEXT NOT NULL,
+    text TEXT NOT NULL,
+    PRIMARY KEY (name_id, language)
+);
+
+CREATE TABLE group_translations (
+    group_id INTEGER NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    language TEXT NOT NULL,
+    text TEXT NOT NULL,
+    PRIMARY KEY (group_id, language)
+);
+
+CREATE TABLE unit_translations (
+    unit_id INTEGER NOT NULL REFERENCES units(id) ON DELETE CASCADE,
+    language TEXT NOT NULL,
+    text TEXT NOT NULL,
+    PRIMARY KEY (unit_id, language)
+);
+
+CREATE TABLE set_translations (
+    set_id INTEGER NOT NULL REFERENCES sets(id) ON DELETE CASCADE,
+    language TEXT NOT NULL,
+    text TEXT NOT NULL,
+    PRIMARY KEY (set_id, language)
+);
+
+CREATE TABLE skill_translations (
+    skill_id INTEGER NOT NULL REFERENCES skills(id) ON DELETE CASCADE,
+    language TEXT NOT NULL,
+    text TEXT NOT NULL,
+    PRIMARY KEY (skill_id, language)
+);
+
+-- Record changes to translations in the audit log, which needs the table rebuilt to
+-- widen its CHECK constraint.
+CREATE TABLE audit_log_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    actor TEXT NOT NULL, -- the name of the API key, or of the local tool, that made the change
+    api_key_id INTEGER,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    action TEXT NOT NULL CHECK(action IN ('create', 'update', 'delete')),
+    entity_kind TEXT NOT NULL CHECK(entity_kind IN ('card', 'set', 'group', 'unit', 'rarity', 'name_variant', 'group_variant', 'translation')),
+    entity_key TEXT NOT NULL,
+    before TEXT, -- NULL for creations
+    after TEXT, -- NULL for deletions
+    FOREIGN KEY(api_key_id) REFERENCES api_keys(id)
+);
+INSERT INTO audit_log_new SELECT * FROM audit_log;
+DROP TABLE audit_log;
+ALTER TABLE audit_log_new RENAME TO audit_log;
+CREATE INDEX idx_audit_log_entity ON audit_log (entity_kind, entity_key);
+CREATE INDEX idx_audit_log_created_at ON audit_log (created_at);
@@ -0,0 +1,186 @@
+use crate::{
+    ApiState, Pool, db,
+    models::{Actor, ApiKey, Role},
+};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{StatusCode, header, request::Parts},
+    response::{IntoResponse, Response},
+};
+use rand::{RngCore, rngs::OsRng};
+use sha2::{Digest, Sha256};
+
+/// The start of every token, so that leaked tokens are easy to recognize.
+const TOKEN_PREFIX: &str = "llocg";
+
+/// The number of random bytes in the public prefix and in the secret of a key.
+const KEY_PREFIX_BYTES: usize = 6;
+const SECRET_BYTES: usize = 32;
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0; len];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Hashes the secret part of a token for storage.
+///
+/// Secrets are long random strings rather than passwords, so a plain SHA-256 is enough.
+pub fn hash_secret(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/// Splits a `llocg_<prefix>_<secret>` token into its prefix and secret.
+fn parse_token(token: &str) -> Option<(&str, &str)> {
+    let rest = token.strip_prefix(TOKEN_PREFIX)?.strip_prefix('_')?;
+    let (key_prefix, secret) = rest.split_once('_')?;
+    (!key_prefix.is_empty() && !secret.is_empty()).then_some((key_prefix, secret))
+}
+
+/// Compares two byte strings in time independent of where they differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Issues a new API key, returning it along with its token.
+///
+/// The token is not stored anywhere, so it cannot be shown again.
+pub async fn issue_key(
+    pool: &Pool,
+    name: &str,
+    role: Role,
+) -> Result<(ApiKey, String), sqlx::Error> {
+    let key_prefix = random_hex(KEY_PREFIX_BYTES);
+    let secret = random_hex(SECRET_BYTES);
+    let key = db::insert_api_key(pool, name, role, &key_prefix, &hash_secret(&secret)).await?;
+    Ok((key, format!("{}_{}_{}", TOKEN_PREFIX, key_prefix, secret)))
+}
+
+/// Looks up the unrevoked key a token belongs to.
+pub async fn authenticate(pool: &Pool, token: &str) -> Result<Option<ApiKey>, sqlx::Error> {
+    let Some((key_prefix, secret)) = parse_token(token) else {
+        return Ok(None);
+    };
+    Ok(db::fetch_active_api_key(pool, key_prefix)
+        .await?
+        .filter(|(_, secret_hash)| {
+            constant_time_eq(hash_secret(secret).as_bytes(), secret_hash.as_bytes())
+        })
+        .map(|(key, _)| key))
+}
+
+/// Authenticates a request by its `Authorization: Bearer` token and checks its role.
+///
+/// # Returns
+/// - The API key the request was made with.
+/// - `401 Unauthorized` if the token is missing, malformed, unknown or revoked.
+/// - `403 Forbidden` if the key's role is below `required`.
+async fn authorize(parts: &Parts, state: &ApiState, required: Role) -> Result<ApiKey, Response> {
+    let unauthorized = |message: &str| {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            message.to_string(),
+        )
+            .into_response()
+    };
+
+    let Some(token) = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return Err(unauthorized("An API key is required."));
+    };
+
+    // Looked up on every request, so that keys revoked or changed from another process
+    // lose their rights at once.
+    let key = authenticate(&state.pool, token.trim())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?
+        .ok_or_else(|| unauthorized("Invalid or revoked API key."))?;
+
+    if key.role < required {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!(
+                "This requires the {} role.",
+                format!("{:?}", required).to_lowercase()
+            ),
+        )
+            .into_response());
+    }
+    Ok(key)
+}
+
+/// Extracts the API key of a request made with any role.
+pub struct Reader(pub ApiKey);
+
+#[async_trait]
+impl FromRequestParts<ApiState> for Reader {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &ApiState) -> Result<Self, Response> {
+        authorize(parts, state, Role::Reader).await.map(Reader)
+    }
+}
+
+/// Extracts the API key of a request made with at least the `editor` role.
+pub struct Editor(pub ApiKey);
+
+impl Editor {
+    /// The key, as recorded in the audit log.
+    pub fn actor(&self) -> Actor {
+        Actor::from(&self.0)
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<ApiState> for Editor {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &ApiState) -> Result<Self, Response> {
+        authorize(parts, state, Role::Editor).await.map(Editor)
+    }
+}
+
+/// Extracts the API key of a request made with the `admin` role.
+pub struct Admin(pub ApiKey);
+
+impl Admin {
+    /// The key, as recorded in the audit log.
+    pub fn actor(&self) -> Actor {
+        Actor::from(&self.0)
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<ApiState> for Admin {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &ApiState) -> Result<Self, Response> {
+        authorize(parts, state, Role::Admin).await.map(Admin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token() {
+        assert_eq!(parse_token("llocg_ab12_secret"), Some(("ab12", "secret")));
+        assert_eq!(parse_token("llocg_ab12_"), None);
+        assert_eq!(parse_token("other_ab12_secret"), None);
+        assert_eq!(parse_token("llocgab12_secret"), None);
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+}
@@ -0,0 +1,970 @@
+//! Data management for the LLOCG database, working directly against `DATABASE_URL`
+//! without the HTTP server running.
+//!
+//! Run `llocg-admin --help` for the list of commands.
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use futures::StreamExt;
+use llocg_backend_api::{
+    ApiState, Pool, auth, create_app_state_with_pool, csv_import, db, dump, html_import,
+    image_store, integrity, localization,
+    models::{
+        Actor, CreateCard, CreateRarity, CreateSet, CsvColumnMapping, DependentAction,
+        ImportDiffStatus, ProductType, RarityType, Role, Translation, TranslationKind, TrashKind,
+        UpdateSet,
+    },
+};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+};
+
+type CliResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(name = "llocg-admin", about = "Manage the LLOCG card database")]
+struct Cli {
+    /// The database to manage.
+    #[arg(long, env = "DATABASE_URL")]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the database if needed and apply all pending migrations.
+    Migrate,
+    /// Import cards or a database dump, migrating a new database first.
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Export the card catalog or a database dump.
+    Export {
+        format: ExportFormat,
+        /// The file to write to. Defaults to standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Manage card sets.
+    Sets {
+        #[command(subcommand)]
+        action: SetAction,
+    },
+    /// Manage groups.
+    Groups {
+        #[command(subcommand)]
+        action: NameAction,
+    },
+    /// Manage units.
+    Units {
+        #[command(subcommand)]
+        action: NameAction,
+    },
+    /// Manage rarity mappings.
+    Rarities {
+        #[command(subcommand)]
+        action: RarityAction,
+    },
+    /// Manage card name variants.
+    NameVariants {
+        #[command(subcommand)]
+        action: VariantAction,
+    },
+    /// Manage group name variants.
+    GroupVariants {
+        #[command(subcommand)]
+        action: VariantAction,
+    },
+    /// List or submit translations of card names, groups, units, sets and skills.
+    Translations {
+        #[command(subcommand)]
+        action: TranslationAction,
+    },
+    /// List, restore or purge deleted sets, groups and units.
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+    /// Manage API keys.
+    Keys {
+        #[command(subcommand)]
+        action: KeyAction,
+    },
+    /// Check the database for corruption and inconsistent data.
+    Validate,
+    /// Re-render the variants and perceptual hashes of every stored image.
+    RegenerateImages {
+        /// The directory of the local image store.
+        #[arg(long, env = "IMAGE_DIR", default_value = "images")]
+        image_dir: PathBuf,
+    },
+    /// Check that the lookup caches the server builds at startup load from the database.
+    ///
+    /// A running server keeps its own caches, and picks up changes made through this
+    /// tool once restarted.
+    CheckCaches,
+}
+
+#[derive(Subcommand)]
+enum ImportSource {
+    /// A CSV card sheet using the default column names.
+    Csv { file: PathBuf },
+    /// A JSON array of cards, as accepted by `POST /cards/bulk`.
+    Json { file: PathBuf },
+    /// Saved card detail pages of the official card list.
+    Html {
+        files: Vec<PathBuf>,
+        /// Only report the differences against the database.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// A database dump, restored into an empty database.
+    Dump { file: PathBuf },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Ndjson,
+    Dump,
+}
+
+#[derive(Subcommand)]
+enum SetAction {
+    List,
+    Add {
+        set_code: String,
+        name: String,
+        #[command(flatten)]
+        fields: SetFieldArgs,
+    },
+    /// Change the code, name or other fields of a set, moving its cards along to a new
+    /// code.
+    Update {
+        set_code: String,
+        #[arg(long = "code")]
+        new_code: Option<String>,
+        #[arg(long = "name")]
+        new_name: Option<String>,
+        #[command(flatten)]
+        fields: SetFieldArgs,
+        /// Optional fields to clear, taking precedence over values given for them.
+        #[arg(long, value_enum)]
+        clear: Vec<ClearableSetField>,
+    },
+    /// Declare the rarities cards of a set can have, or list them if none are given.
+    Rarities {
+        set_code: String,
+        rarity_codes: Vec<String>,
+        /// Accept any known rarity for the set again.
+        #[arg(long, conflicts_with = "rarity_codes")]
+        clear: bool,
+    },
+    Delete {
+        set_code: String,
+        #[command(flatten)]
+        dependents: DependentArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum NameAction {
+    List,
+    Add {
+        name: String,
+    },
+    Rename {
+        name: String,
+        new_name: String,
+        /// Keep the old name as a group variant of the new one. Only applies to groups.
+        #[arg(long)]
+        keep_old_as_variant: bool,
+    },
+    Delete {
+        name: String,
+        #[command(flatten)]
+        dependents: DependentArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum RarityAction {
+    List,
+    Add {
+        rarity_code: String,
+        rarity_type: RarityTypeArg,
+        /// The name shown for the rarity. Defaults to its code.
+        #[arg(long)]
+        display_name: Option<String>,
+        /// Where the rarity sorts in lists, lowest first.
+        #[arg(long)]
+        sort_rank: Option<i64>,
+        /// Mark the rarity as a secret one.
+        #[arg(long)]
+        secret: bool,
+        #[arg(long)]
+        description: Option<String>,
+    },
+    Delete {
+        rarity_code: String,
+        #[command(flatten)]
+        dependents: DependentArgs,
+    },
+}
+
+/// What to do with the cards that depend on deleted reference data. Without either
+/// option, nothing that cards depend on is deleted.
+#[derive(Args)]
+struct DependentArgs {
+    /// Remove the printings of a rarity. Sets, groups and units go to the trash instead,
+    /// so they do not accept it.
+    #[arg(long, conflicts_with = "reassign_to")]
+    cascade: bool,
+    /// Move the dependent cards to another set, group, unit or rarity.
+    #[arg(long)]
+    reassign_to: Option<String>,
+}
+
+impl From<DependentArgs> for DependentAction {
+    fn from(value: DependentArgs) -> Self {
+        match value.reassign_to {
+            Some(target) => DependentAction::ReassignTo(target),
+            None if value.cascade => DependentAction::Cascade,
+            None => DependentAction::Refuse,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RarityTypeArg {
+    Regular,
+    Parallel,
+}
+
+impl From<RarityTypeArg> for RarityType {
+    fn from(value: RarityTypeArg) -> Self {
+        match value {
+            RarityTypeArg::Regular => RarityType::Regular,
+            RarityTypeArg::Parallel => RarityType::Parallel,
+        }
+    }
+}
+
+/// The optional fields of a set.
+#[derive(Args)]
+struct SetFieldArgs {
+    /// The release date, as YYYY-MM-DD.
+    #[arg(long)]
+    release_date: Option<String>,
+    #[arg(long)]
+    product_type: Option<ProductTypeArg>,
+    /// The official number of cards in the set.
+    #[arg(long)]
+    total_cards: Option<i64>,
+    /// The language or region the set was released for. New sets default to `ja`.
+    #[arg(long)]
+    language: Option<String>,
+    /// The position of the set in listings.
+    #[arg(long)]
+    sort_order: Option<i64>,
+}
+
+/// The optional fields of a set that can be cleared.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ClearableSetField {
+    ReleaseDate,
+    ProductType,
+    TotalCards,
+    SortOrder,
+}
+
+/// The change to an optional field of a set: clearing it, or setting it if a value is given.
+fn clear_or<T>(
+    clear: &[ClearableSetField],
+    field: ClearableSetField,
+    value: Option<T>,
+) -> Option<Option<T>> {
+    if clear.contains(&field) {
+        Some(None)
+    } else {
+        value.map(Some)
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ProductTypeArg {
+    Booster,
+    PremiumBooster,
+    StartDeck,
+    Promo,
+}
+
+impl From<ProductTypeArg> for ProductType {
+    fn from(value: ProductTypeArg) -> Self {
+        match value {
+            ProductTypeArg::Booster => ProductType::Booster,
+            ProductTypeArg::PremiumBooster => ProductType::PremiumBooster,
+            ProductTypeArg::StartDeck => ProductType::StartDeck,
+            ProductTypeArg::Promo => ProductType::Promo,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum VariantAction {
+    List,
+    Add {
+        variant_name: String,
+        canonical_name: String,
+    },
+    Delete {
+        variant_name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TranslationAction {
+    List {
+        #[arg(long)]
+        kind: Option<TranslationKindArg>,
+        #[arg(long)]
+        language: Option<String>,
+    },
+    /// Translate a card name, group, unit, set code or skill text, replacing any earlier
+    /// translation into the language.
+    Set {
+        kind: TranslationKindArg,
+        key: String,
+        language: String,
+        text: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TranslationKindArg {
+    Name,
+    Group,
+    Unit,
+    Set,
+    Skill,
+}
+
+impl From<TranslationKindArg> for TranslationKind {
+    fn from(value: TranslationKindArg) -> Self {
+        match value {
+            TranslationKindArg::Name => TranslationKind::Name,
+            TranslationKindArg::Group => TranslationKind::Group,
+            TranslationKindArg::Unit => TranslationKind::Unit,
+            TranslationKindArg::Set => TranslationKind::Set,
+            TranslationKindArg::Skill => TranslationKind::Skill,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    List,
+    Restore {
+        kind: TrashKindArg,
+        key: String,
+    },
+    /// Permanently delete an item.
+    Purge {
+        kind: TrashKindArg,
+        key: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TrashKindArg {
+    Set,
+    Group,
+    Unit,
+}
+
+impl From<TrashKindArg> for TrashKind {
+    fn from(value: TrashKindArg) -> Self {
+        match value {
+            TrashKindArg::Set => TrashKind::Set,
+            TrashKindArg::Group => TrashKind::Group,
+            TrashKindArg::Unit => TrashKind::Unit,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum KeyAction {
+    List,
+    /// Issue a key and print its token, which cannot be shown again.
+    Issue {
+        /// Who or what the key is for.
+        name: String,
+        #[arg(long, default_value = "editor")]
+        role: RoleArg,
+    },
+    Revoke {
+        id: i64,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RoleArg {
+    Reader,
+    Editor,
+    Admin,
+}
+
+impl From<RoleArg> for Role {
+    fn from(value: RoleArg) -> Self {
+        match value {
+            RoleArg::Reader => Role::Reader,
+            RoleArg::Editor => Role::Editor,
+            RoleArg::Admin => Role::Admin,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Opens the database, creating it first only if `create_if_missing` is set.
+async fn connect(database_url: &str, create_if_missing: bool) -> CliResult<Pool> {
+    let options =
+        SqliteConnectOptions::from_str(database_url)?.create_if_missing(create_if_missing);
+    Ok(SqlitePoolOptions::new().connect_with(options).await?)
+}
+
+/// Whether the database has no tables yet, as when it was just created.
+async fn is_empty(pool: &Pool) -> CliResult<bool> {
+    let tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master")
+        .fetch_one(pool)
+        .await?;
+    Ok(tables == 0)
+}
+
+/// Loads the lookup caches used to normalize imported cards.
+async fn load_state(pool: &Pool) -> CliResult<ApiState> {
+    create_app_state_with_pool(pool.clone()).await
+}
+
+/// Changes made through the CLI are recorded in the audit log under the local user.
+fn actor() -> Actor {
+    let user = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+    Actor {
+        name: format!("llocg-admin ({})", user),
+        api_key_id: None,
+    }
+}
+
+async fn run(cli: Cli) -> CliResult<ExitCode> {
+    // Only commands that fill a new database create it; the others fail on a mistyped path.
+    let create_if_missing = matches!(cli.command, Command::Migrate | Command::Import { .. });
+    let pool = connect(&cli.database_url, create_if_missing).await?;
+    let actor = actor();
+
+    // Imports into a new database migrate it first, as they need its tables.
+    if matches!(cli.command, Command::Import { .. }) && is_empty(&pool).await? {
+        sqlx::migrate!("./migrations").run(&pool).await?;
+    }
+
+    match cli.command {
+        Command::Migrate => {
+            sqlx::migrate!("./migrations").run(&pool).await?;
+            println!(
+                "Database is at schema version {}.",
+                dump::schema_version(&pool).await?
+            );
+        }
+        Command::Import { source } => return import(&pool, source).await,
+        Command::Export { format, output } => export(&pool, format, output.as_deref()).await?,
+        Command::Sets { action } => match action {
+            SetAction::List => {
+                for set in db::fetch_all_sets(&pool).await? {
+                    println!(
+                        "{}\t{}\t{}",
+                        set.set_code,
+                        set.name,
+                        set.release_date.unwrap_or_default()
+                    );
+                }
+            }
+            SetAction::Add {
+                set_code,
+                name,
+                fields,
+            } => {
+                let set = CreateSet {
+                    set_code,
+                    name,
+                    release_date: fields.release_date,
+                    product_type: fields.product_type.map(Into::into),
+                    total_cards: fields.total_cards,
+                    language: fields.language,
+                    sort_order: fields.sort_order,
+                };
+                db::add_set(&pool, &actor, &set).await?
+            }
+            SetAction::Update {
+                set_code,
+                new_code,
+                new_name,
+                fields,
+                clear,
+            } => {
+                let update = UpdateSet {
+                    set_code: new_code,
+                    name: new_name,
+                    release_date: clear_or(
+                        &clear,
+                        ClearableSetField::ReleaseDate,
+                        fields.release_date,
+                    ),
+                    product_type: clear_or(
+                        &clear,
+                        ClearableSetField::ProductType,
+                        fields.product_type.map(Into::into),
+                    ),
+                    total_cards: clear_or(
+                        &clear,
+                        ClearableSetField::TotalCards,
+                        fields.total_cards,
+                    ),
+                    language: fields.language,
+                    sort_order: clear_or(&clear, ClearableSetField::SortOrder, fields.sort_order),
+                };
+                if update.is_empty() {
+                    return Err("Give at least one field to change.".into());
+                }
+                report_updated(
+                    db::update_set(&pool, &actor, &set_code, &update).await?,
+                    &set_code,
+                )
+            }
+            SetAction::Rarities {
+                set_code,
+                rarity_codes,
+                clear,
+            } => {
+                if rarity_codes.is_empty() && !clear {
+                    let Some(rarities) = db::fetch_set_rarities(&pool, &set_code).await? else {
+                        return Err(format!("Set not found: {}", set_code).into());
+                    };
+                    print_lines(
+                        rarities
+                            .into_iter()
+                            .map(|rarity| rarity.rarity_code)
+                            .collect(),
+                    );
+                } else if !db::set_set_rarities(&pool, &actor, &set_code, &rarity_codes).await? {
+                    eprintln!("Nothing to update: '{}' does not exist.", set_code);
+                }
+            }
+            SetAction::Delete {
+                set_code,
+                dependents,
+            } => report_deleted(
+                db::delete_set(&pool, &actor, &set_code, &dependents.into()).await?,
+                &set_code,
+            ),
+        },
+        Command::Groups { action } => match action {
+            NameAction::List => print_lines(db::fetch_all_groups(&pool).await?),
+            NameAction::Add { name } => db::add_group(&pool, &actor, &name).await?,
+            NameAction::Rename {
+                name,
+                new_name,
+                keep_old_as_variant,
+            } => report_updated(
+                db::rename_group(&pool, &actor, &name, &new_name, keep_old_as_variant).await?,
+                &name,
+            ),
+            NameAction::Delete { name, dependents } => report_deleted(
+                db::delete_group(&pool, &actor, &name, &dependents.into()).await?,
+                &name,
+            ),
+        },
+        Command::Units { action } => match action {
+            NameAction::List => print_lines(db::fetch_all_units(&pool).await?),
+            NameAction::Add { name } => db::add_unit(&pool, &actor, &name).await?,
+            NameAction::Rename {
+                name,
+                new_name,
+                keep_old_as_variant,
+            } => {
+                if keep_old_as_variant {
+                    return Err("Units have no variants to keep the old name as.".into());
+                }
+                report_updated(
+                    db::rename_unit(&pool, &actor, &name, &new_name).await?,
+                    &name,
+                )
+            }
+            NameAction::Delete { name, dependents } => report_deleted(
+                db::delete_unit(&pool, &actor, &name, &dependents.into()).await?,
+                &name,
+            ),
+        },
+        Command::Rarities { action } => match action {
+            RarityAction::List => {
+                for rarity in db::fetch_all_rarities(&pool).await? {
+                    println!(
+                        "{}\t{:?}\t{}",
+                        rarity.rarity_code, rarity.rarity_type, rarity.display_name
+                    );
+                }
+            }
+            RarityAction::Add {
+                rarity_code,
+                rarity_type,
+                display_name,
+                sort_rank,
+                secret,
+                description,
+            } => {
+                let rarity = CreateRarity {
+                    rarity_code,
+                    rarity_type: rarity_type.into(),
+                    display_name,
+                    sort_rank,
+                    is_secret: secret,
+                    description,
+                };
+                db::add_rarity(&pool, &actor, &rarity.into()).await?
+            }
+            RarityAction::Delete {
+                rarity_code,
+                dependents,
+            } => report_deleted(
+                db::delete_rarity(&pool, &actor, &rarity_code, &dependents.into()).await?,
+                &rarity_code,
+            ),
+        },
+        Command::NameVariants { action } => match action {
+            VariantAction::List => {
+                for variant in db::fetch_all_name_variants(&pool).await? {
+                    println!("{}\t{}", variant.variant_name, variant.canonical_name);
+                }
+            }
+            VariantAction::Add {
+                variant_name,
+                canonical_name,
+            } => db::add_name_variant(&pool, &actor, &variant_name, &canonical_name).await?,
+            VariantAction::Delete { variant_name } => report_deleted(
+                db::delete_name_variant(&pool, &actor, &variant_name).await?,
+                &variant_name,
+            ),
+        },
+        Command::GroupVariants { action } => match action {
+            VariantAction::List => {
+                for variant in db::fetch_all_group_variants(&pool).await? {
+                    println!("{}\t{}", variant.variant_name, variant.canonical_name);
+                }
+            }
+            VariantAction::Add {
+                variant_name,
+                canonical_name,
+            } => db::add_group_variant(&pool, &actor, &variant_name, &canonical_name).await?,
+            VariantAction::Delete { variant_name } => report_deleted(
+                db::delete_group_variant(&pool, &actor, &variant_name).await?,
+                &variant_name,
+            ),
+        },
+        Command::Translations { action } => match action {
+            TranslationAction::List { kind, language } => {
+                let translations =
+                    db::fetch_translations(&pool, kind.map(Into::into), language.as_deref())
+                        .await?;
+                for translation in translations {
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        translation.kind.as_str(),
+                        translation.key,
+                        translation.language,
+                        translation.text
+                    );
+                }
+            }
+            TranslationAction::Set {
+                kind,
+                key,
+                language,
+                text,
+            } => {
+                let language = localization::normalize_language(&language)
+                    .ok_or_else(|| format!("Invalid language code: {}", language))?;
+                let translation = Translation {
+                    kind: kind.into(),
+                    key,
+                    language,
+                    text,
+                };
+                if !db::upsert_translation(&pool, &actor, &translation).await? {
+                    eprintln!(
+                        "Nothing to translate: {} '{}' does not exist.",
+                        translation.kind.as_str(),
+                        translation.key
+                    );
+                }
+            }
+        },
+        Command::Trash { action } => match action {
+            TrashAction::List => {
+                for item in db::fetch_trash(&pool).await? {
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        format!("{:?}", item.kind).to_lowercase(),
+                        item.key,
+                        item.name,
+                        item.deleted_at
+                    );
+                }
+            }
+            TrashAction::Restore { kind, key } => {
+                if !db::restore_from_trash(&pool, &actor, kind.into(), &key).await? {
+                    eprintln!("Nothing to restore: '{}' is not in the trash.", key);
+                }
+            }
+            TrashAction::Purge { kind, key } => {
+                if !db::purge_from_trash(&pool, &actor, kind.into(), &key).await? {
+                    eprintln!("Nothing to purge: '{}' is not in the trash.", key);
+                }
+            }
+        },
+        Command::Keys { action } => match action {
+            KeyAction::List => {
+                for key in db::fetch_all_api_keys(&pool).await? {
+                    println!(
+                        "{}\t{}\t{}\t{}\t{}",
+                        key.id,
+                        key.key_prefix,
+                        format!("{:?}", key.role).to_lowercase(),
+                        key.name,
+                        key.revoked_at
+                            .map_or_else(|| "active".to_string(), |at| format!("revoked {}", at))
+                    );
+                }
+            }
+            KeyAction::Issue { name, role } => {
+                let (key, token) = auth::issue_key(&pool, &name, role.into()).await?;
+                eprintln!(
+                    "Issued key {} to {}. Store its token now:",
+                    key.id, key.name
+                );
+                println!("{}", token);
+            }
+            KeyAction::Revoke { id } => {
+                if db::revoke_api_key(&pool, id).await?.rows_affected() == 0 {
+                    eprintln!(
+                        "Nothing to revoke: key {} does not exist or is revoked.",
+                        id
+                    );
+                }
+            }
+        },
+        Command::Validate => {
+            let problems = integrity::check_database(&pool).await?;
+            for problem in &problems {
+                println!("{}: {}", problem.check, problem.detail);
+            }
+            if !problems.is_empty() {
+                eprintln!("{} problems found.", problems.len());
+                return Ok(ExitCode::FAILURE);
+            }
+            println!("No problems found.");
+        }
+        Command::RegenerateImages { image_dir } => {
+            let report = image_store::regenerate_variants(&pool, &image_dir).await?;
+            println!(
+                "Regenerated variants and hashes of {} images for {} printings.",
+                report.images, report.printings
+            );
+            for hash in &report.failed {
+                eprintln!(
+                    "Could not regenerate {}: original missing or unreadable.",
+                    hash
+                );
+            }
+            if !report.failed.is_empty() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::CheckCaches => {
+            load_state(&pool).await?;
+            println!("All caches loaded.");
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_lines(lines: Vec<String>) {
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+fn report_deleted(result: sqlx::sqlite::SqliteQueryResult, key: &str) {
+    if result.rows_affected() == 0 {
+        eprintln!("Nothing to delete: '{}' does not exist.", key);
+    }
+}
+
+fn report_updated(result: sqlx::sqlite::SqliteQueryResult, key: &str) {
+    if result.rows_affected() == 0 {
+        eprintln!("Nothing to update: '{}' does not exist.", key);
+    }
+}
+
+/// Creates cards through the bulk path, reporting a failure against its row.
+async fn create_cards(
+    pool: &Pool,
+    state: &ApiState,
+    cards: Vec<CreateCard>,
+    rows: &[usize],
+) -> CliResult<usize> {
+    let rarity_cache = state.rarity_cache.read().await;
+    let name_variant_cache = state.name_variant_cache.read().await;
+    let group_variant_cache = state.group_variant_cache.read().await;
+
+    match db::create_bulk_cards_indexed(
+        pool,
+        &actor(),
+        &rarity_cache,
+        &name_variant_cache,
+        &group_variant_cache,
+        cards,
+    )
+    .await
+    {
+        Ok(created) => Ok(created.len()),
+        Err((Some(index), e)) => Err(format!("row {}: {}", rows[index], e).into()),
+        Err((None, e)) => Err(e.into()),
+    }
+}
+
+async fn import(pool: &Pool, source: ImportSource) -> CliResult<ExitCode> {
+    let state = load_state(pool).await?;
+    let (cards, rows) = match source {
+        ImportSource::Csv { file } => {
+            let data = std::fs::read_to_string(file)?;
+            match csv_import::parse_cards(&data, &CsvColumnMapping::default()) {
+                Ok(cards) => {
+                    let rows = (1..=cards.len()).collect();
+                    (cards, rows)
+                }
+                Err(errors) => {
+                    for error in errors {
+                        eprintln!("row {}: {}", error.row, error.message);
+                    }
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+        }
+        ImportSource::Json { file } => {
+            let cards: Vec<CreateCard> = serde_json::from_slice(&std::fs::read(file)?)?;
+            let rows = (1..=cards.len()).collect();
+            (cards, rows)
+        }
+        ImportSource::Html { files, dry_run } => {
+            let pages = files
+                .iter()
+                .map(std::fs::read_to_string)
+                .collect::<Result<Vec<_>, _>>()?;
+            let cards = match html_import::parse_pages(&pages) {
+                Ok(cards) => cards,
+                Err(errors) => {
+                    for error in errors {
+                        eprintln!("{}: {}", files[error.row - 1].display(), error.message);
+                    }
+                    return Ok(ExitCode::FAILURE);
+                }
+            };
+
+            let plan = html_import::plan_import(
+                pool,
+                &*state.name_variant_cache.read().await,
+                &*state.group_variant_cache.read().await,
+                cards,
+            )
+            .await?;
+            for entry in &plan.diff {
+                let status = match entry.status {
+                    ImportDiffStatus::New => "new",
+                    ImportDiffStatus::Unchanged => "unchanged",
+                    ImportDiffStatus::Changed => "changed",
+                };
+                println!("{}\t{}", entry.card_identifier, status);
+                for change in &entry.changes {
+                    println!(
+                        "\t{}: {} -> {}",
+                        change.field, change.existing, change.incoming
+                    );
+                }
+            }
+            if dry_run {
+                return Ok(ExitCode::SUCCESS);
+            }
+            (plan.new_cards, plan.new_rows)
+        }
+        ImportSource::Dump { file } => {
+            let dump = dump::parse_dump(&std::fs::read(file)?)?;
+            dump::import_database(pool, &dump).await?;
+            println!("Restored {} cards.", dump.cards.len());
+            return Ok(ExitCode::SUCCESS);
+        }
+    };
+
+    let created = if cards.is_empty() {
+        0
+    } else {
+        create_cards(pool, &state, cards, &rows).await?
+    };
+    println!("Created {} cards.", created);
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn export(pool: &Pool, format: ExportFormat, output: Option<&Path>) -> CliResult<()> {
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    match format {
+        ExportFormat::Dump => {
+            serde_json::to_writer_pretty(&mut out, &dump::export_database(pool).await?)?;
+        }
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            let mut rows = Box::pin(db::stream_card_export_rows(pool.clone()));
+            while let Some(row) = rows.next().await {
+                writer.serialize(row?)?;
+            }
+            writer.flush()?;
+            return Ok(());
+        }
+        ExportFormat::Ndjson => {
+            let mut rows = Box::pin(db::stream_card_export_rows(pool.clone()));
+            while let Some(row) = rows.next().await {
+                serde_json::to_writer(&mut out, &row?)?;
+                out.write_all(b"\n")?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
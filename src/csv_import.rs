@@ -0,0 +1,248 @@
+use crate::models::{CreateCard, CsvColumnMapping, ImportRowError};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Looks up a cell by its column header, treating blank cells as missing.
+fn cell<'a>(
+    record: &'a csv::StringRecord,
+    columns: &HashMap<&str, usize>,
+    header: &str,
+) -> Option<&'a str> {
+    columns
+        .get(header)
+        .and_then(|&index| record.get(index))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Parses an integer cell, naming the column in the error message.
+fn integer_cell(
+    record: &csv::StringRecord,
+    columns: &HashMap<&str, usize>,
+    header: &str,
+) -> Result<Option<i64>, String> {
+    cell(record, columns, header)
+        .map(|value| {
+            value
+                .parse::<i64>()
+                .map_err(|_| format!("Column '{}' must be an integer, got '{}'.", header, value))
+        })
+        .transpose()
+}
+
+/// Splits a delimited list cell into its trimmed, non-empty items.
+fn list_cell(
+    record: &csv::StringRecord,
+    columns: &HashMap<&str, usize>,
+    header: &str,
+    delimiter: char,
+) -> Vec<Value> {
+    cell(record, columns, header)
+        .map(|value| {
+            value
+                .split(delimiter)
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Converts a single CSV record into a [`CreateCard`].
+///
+/// The record is first shaped into the same JSON document accepted by `POST /cards`,
+/// so that it goes through exactly the same validation.
+fn parse_record(
+    record: &csv::StringRecord,
+    columns: &HashMap<&str, usize>,
+    mapping: &CsvColumnMapping,
+) -> Result<CreateCard, String> {
+    let mut card = Map::new();
+
+    for (field, header) in [
+        ("card_identifier", &mapping.card_identifier),
+        ("name", &mapping.name),
+        ("card_type", &mapping.card_type),
+        ("blade_heart", &mapping.blade_heart),
+        ("special_heart", &mapping.special_heart),
+        ("image_url", &mapping.image_url),
+    ] {
+        if let Some(value) = cell(record, columns, header) {
+            card.insert(field.to_string(), Value::String(value.to_string()));
+        }
+    }
+
+    for (field, header) in [
+        ("cost", &mapping.cost),
+        ("blades", &mapping.blades),
+        ("score", &mapping.score),
+    ] {
+        if let Some(value) = integer_cell(record, columns, header)? {
+            card.insert(field.to_string(), Value::from(value));
+        }
+    }
+
+    let mut hearts = Map::new();
+    for (color, header) in &mapping.hearts {
+        let count = integer_cell(record, columns, header)?.unwrap_or(0);
+        if count <= 0 {
+            continue;
+        }
+        // Use the serialized color name so the JSON matches `POST /cards` exactly.
+        if let Value::String(color) = serde_json::to_value(color).unwrap_or_default() {
+            hearts.insert(color, Value::from(count));
+        }
+    }
+    if !hearts.is_empty() {
+        card.insert("hearts".to_string(), Value::Object(hearts));
+    }
+
+    for (field, header) in [
+        ("groups", &mapping.groups),
+        ("units", &mapping.units),
+        ("skills", &mapping.skills),
+    ] {
+        let items = list_cell(record, columns, header, mapping.list_delimiter);
+        card.insert(field.to_string(), Value::Array(items));
+    }
+
+    serde_json::from_value(Value::Object(card)).map_err(|e| e.to_string())
+}
+
+/// Parses a CSV card sheet into [`CreateCard`] payloads using the given column mapping.
+///
+/// Every row is parsed even if earlier rows fail, so that all problems can be reported
+/// at once. Returns the parsed cards if every row is valid, or the errors of every
+/// invalid row otherwise.
+pub fn parse_cards(
+    data: &str,
+    mapping: &CsvColumnMapping,
+) -> Result<Vec<CreateCard>, Vec<ImportRowError>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::Headers)
+        .from_reader(data.as_bytes());
+
+    let headers = reader.headers().cloned().map_err(|e| {
+        vec![ImportRowError {
+            row: 0,
+            message: format!("Failed to read CSV header: {}", e),
+        }]
+    })?;
+    let columns: HashMap<&str, usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| (header, index))
+        .collect();
+
+    for required in [&mapping.card_identifier, &mapping.name, &mapping.card_type] {
+        if !columns.contains_key(required.as_str()) {
+            return Err(vec![ImportRowError {
+                row: 0,
+                message: format!("Missing required column '{}'.", required),
+            }]);
+        }
+    }
+
+    let mut cards = Vec::new();
+    let mut errors = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 1;
+        let result = record
+            .map_err(|e| e.to_string())
+            .and_then(|record| parse_record(&record, &columns, mapping));
+        match result {
+            Ok(card) => cards.push(card),
+            Err(message) => errors.push(ImportRowError { row, message }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(cards)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CardType, CreateCardTypeSpecifics, HeartColor};
+
+    #[test]
+    fn test_parse_cards_default_mapping() {
+        let data = "\
+card_identifier,name,card_type,cost,blades,heart_pink,heart_red,blade_heart,score,groups,units,skills
+PL!SP-bp1-001-R,Shibuya Kanon,Character,9,3,,2,Red,,Love Live! Superstar!!,CatChu!,Skill one|Skill two
+PL!SP-bp1-023-L,START!! True dreams,Live,,,1,,,1,Love Live! Superstar!!,,
+";
+        let cards = parse_cards(data, &CsvColumnMapping::default()).unwrap();
+        assert_eq!(cards.len(), 2);
+
+        let member = &cards[0];
+        assert_eq!(member.set_code, "bp1");
+        assert_eq!(member.card_type, CardType::Character);
+        assert_eq!(member.units, vec!["CatChu!"]);
+        assert_eq!(member.skills, vec!["Skill one", "Skill two"]);
+        match &member.type_specifics {
+            Some(CreateCardTypeSpecifics::Character(c)) => {
+                assert_eq!(c.cost, 9);
+                assert_eq!(c.hearts.get(&HeartColor::Red), Some(&2));
+                assert_eq!(c.hearts.get(&HeartColor::Pink), None);
+            }
+            _ => panic!("Expected Character type specifics"),
+        }
+
+        let live = &cards[1];
+        assert_eq!(live.card_type, CardType::Live);
+        assert!(live.units.is_empty());
+        assert!(live.skills.is_empty());
+    }
+
+    #[test]
+    fn test_parse_cards_custom_mapping() {
+        let data = "\
+id,Name,Type,Series
+PL!HS-bp1-031-PE,Anyoji Hime,Energy,Hasu no Sora Jogakuin School Idol Club/Edel Note
+";
+        let mapping = CsvColumnMapping {
+            card_identifier: "id".to_string(),
+            name: "Name".to_string(),
+            card_type: "Type".to_string(),
+            groups: "Series".to_string(),
+            list_delimiter: '/',
+            ..Default::default()
+        };
+        let cards = parse_cards(data, &mapping).unwrap();
+        assert_eq!(cards[0].card_type, CardType::Energy);
+        assert_eq!(cards[0].rarity_code, "PE");
+        assert_eq!(
+            cards[0].groups,
+            vec!["Hasu no Sora Jogakuin School Idol Club", "Edel Note"]
+        );
+
+        // The default mapping does not find the renamed columns.
+        let errors = parse_cards(data, &CsvColumnMapping::default()).unwrap_err();
+        assert_eq!(errors[0].row, 0);
+    }
+
+    #[test]
+    fn test_parse_cards_reports_every_bad_row() {
+        let data = "\
+card_identifier,name,card_type,cost,blades,heart_pink
+PL!-BP01-001-R,Kousaka Honoka,Character,four,2,1
+PL!-BP01-002-R,Ayase Eli,Character,4,2,1
+PL!-BP01-003-R,Minami Kotori,Character,4,2,
+";
+        let errors = parse_cards(data, &CsvColumnMapping::default()).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].row, 1);
+        assert!(
+            errors[0]
+                .message
+                .contains("Column 'cost' must be an integer")
+        );
+        assert_eq!(errors[1].row, 3);
+        assert!(errors[1].message.contains("`hearts` field is required"));
+    }
+}
@@ -1,9 +1,14 @@
 use crate::Pool;
 use crate::models::{
-    BaseCard, Card, CardType, CardTypeSpecifics, CharacterCard, CreateCard, CreateCardTypeSpecifics,
-    FullCard, HeartColor, LiveCard, Printing, RarityType,
+    Actor, ApiKey, AuditAction, AuditEntry, AuditQuery, BaseCard, Card, CardExportRow,
+    CardRevision, CardType, CardTypeSpecifics, CharacterCard, Collection, CollectionEntry,
+    CollectionQuantity, CreateCard, CreateCardTypeSpecifics, CreateWant, DeckEntry,
+    DependentAction, EntityKind, FullCard, GroupVariant, HeartColor, LiveCard, NameVariant,
+    OwnedPrinting, Printing, Rarity, RarityType, Role, SetCompletion, Translation, TranslationKind,
+    TrashItem, TrashKind, Want,
 };
-use futures::try_join;
+use crate::models::{ChecklistCard, CreateSet, SetChecklist, SetResponse, UpdateSet};
+use futures::{SinkExt, Stream, StreamExt, channel::mpsc, try_join};
 use std::collections::HashMap;
 
 /// Custom error type for database operations to provide more specific feedback.
@@ -15,12 +20,98 @@ pub enum DbError {
     #[error("Unit not found: {0}")]
     UnitNotFound(String),
 
+    #[error("Card not found: {0}")]
+    CardNotFound(i64),
+
+    #[error("Too many copies of card {0}")]
+    TooManyCopies(i64),
+
+    #[error("Collection not found: {0}")]
+    CollectionNotFound(i64),
+
+    #[error("Printing not found: {0}")]
+    PrintingNotFound(i64),
+
+    #[error("Quantity of printing {0} is too large")]
+    QuantityOverflow(i64),
+
+    #[error("Revision {revision} of card {card_id} not found")]
+    RevisionNotFound { card_id: i64, revision: i64 },
+
+    #[error(
+        "Unsupported dump format version: {0}; only version {} can be imported, so export \
+         the dump again with a matching release",
+        crate::dump::DUMP_FORMAT_VERSION
+    )]
+    UnsupportedDumpFormat(u32),
+
+    #[error("Invalid dump: {0}")]
+    InvalidDump(String),
+
+    #[error("Schema version mismatch: expected {expected}, found {found}")]
+    SchemaVersionMismatch { expected: i64, found: i64 },
+
+    #[error("Invalid timestamp: {0}")]
+    InvalidTimestamp(String),
+
+    #[error("Invalid date, expected YYYY-MM-DD: {0}")]
+    InvalidDate(String),
+
+    #[error("Invalid total number of cards, expected 0 to {MAX_TOTAL_CARDS}: {0}")]
+    InvalidTotalCards(i64),
+
+    #[error("Set not found: {0}")]
+    SetNotFound(String),
+
+    #[error("Rarity not found: {0}")]
+    RarityNotFound(String),
+
+    #[error("Set {set_code} does not contain rarity {rarity_code}")]
+    RarityNotInSet {
+        set_code: String,
+        rarity_code: String,
+    },
+
+    #[error("Set {set_code} still has printings of rarities: {}", .rarity_codes.join(", "))]
+    RaritiesInUse {
+        set_code: String,
+        rarity_codes: Vec<String>,
+    },
+
+    #[error("Database is not empty: {0}")]
+    DatabaseNotEmpty(String),
+
+    #[error("{entity} is still used by cards: {}", .cards.join(", "))]
+    HasDependents { entity: String, cards: Vec<String> },
+
+    #[error("Cannot reassign to '{0}': it does not exist or is the one being deleted")]
+    InvalidReassignTarget(String),
+
+    #[error(
+        "Deleted sets, groups and units go to the trash, so their cards cannot be removed \
+         along with them; reassign the cards or delete them first"
+    )]
+    CascadeIntoTrash,
+
     #[error("Database error: {0}")]
     Sqlx(#[from] sqlx::Error),
 }
 
 pub type DbResult<T> = Result<T, DbError>;
 
+/// The related data of a card that make up a [`FullCard`]: its name, set name, groups,
+/// units, skills, hearts, printings and type-specific data.
+type CardParts = (
+    String,
+    String,
+    Vec<String>,
+    Vec<String>,
+    Vec<String>,
+    HashMap<HeartColor, i64>,
+    Vec<Printing>,
+    Option<CardTypeSpecifics>,
+);
+
 /// Fetches a single, fully detailed card from the database by its ID.
 pub async fn fetch_full_card(pool: &Pool, id: i64) -> Result<FullCard, sqlx::Error> {
     // Query 1: Fetch the raw card data.
@@ -31,7 +122,7 @@ pub async fn fetch_full_card(pool: &Pool, id: i64) -> Result<FullCard, sqlx::Err
         .await?;
 
     // We can run the rest of the queries concurrently for better performance.
-    let (name, set_name, groups, units, skills, hearts, printings, type_specifics) = try_join!(
+    let parts = try_join!(
         // Query 2: Get the card name.
         fetch_name_for_card(pool, card.name_id),
         // Query 2: Get the set name.
@@ -50,8 +141,40 @@ pub async fn fetch_full_card(pool: &Pool, id: i64) -> Result<FullCard, sqlx::Err
         fetch_type_specifics(pool, id, card.card_type)
     )?;
 
-    // Assemble the final `FullCard` struct.
-    Ok(FullCard {
+    Ok(assemble_full_card(card, parts))
+}
+
+/// Same as [`fetch_full_card`], but within an existing transaction, so that it sees the
+/// transaction's uncommitted changes. The queries run one after another.
+pub async fn fetch_full_card_with_tx(
+    conn: &mut sqlx::SqliteConnection,
+    id: i64,
+) -> Result<FullCard, sqlx::Error> {
+    let card = sqlx::query_as::<_, Card>("SELECT * FROM cards WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    let parts = (
+        fetch_name_for_card(&mut *conn, card.name_id).await?,
+        fetch_set_name(&mut *conn, &card.set_code).await?,
+        fetch_groups_for_card(&mut *conn, id).await?,
+        fetch_units_for_card(&mut *conn, id).await?,
+        fetch_skills_for_card(&mut *conn, id).await?,
+        fetch_hearts_for_card(&mut *conn, id).await?,
+        fetch_printings_for_card(&mut *conn, id).await?,
+        fetch_type_specifics(&mut *conn, id, card.card_type).await?,
+    );
+
+    Ok(assemble_full_card(card, parts))
+}
+
+/// Assembles the final `FullCard` struct from a card and its related data.
+fn assemble_full_card(
+    card: Card,
+    (name, set_name, groups, units, skills, hearts, printings, type_specifics): CardParts,
+) -> FullCard {
+    FullCard {
         base: BaseCard {
             id: card.id,
             series_code: card.series_code,
@@ -67,21 +190,151 @@ pub async fn fetch_full_card(pool: &Pool, id: i64) -> Result<FullCard, sqlx::Err
         hearts,
         printings,
         type_specifics,
-    })
+    }
+}
+
+/// Fetches every distinct card referenced by a deck list, paired with its total copy count.
+///
+/// Entries that reference the same card are merged, preserving the order in which
+/// each card first appears.
+pub async fn fetch_deck_cards(pool: &Pool, deck: &[DeckEntry]) -> DbResult<Vec<(FullCard, i64)>> {
+    let mut counts: Vec<(i64, i64)> = Vec::new();
+    for entry in deck {
+        match counts.iter_mut().find(|(id, _)| *id == entry.card_id) {
+            Some((_, count)) => {
+                *count = count
+                    .checked_add(entry.count)
+                    .ok_or(DbError::TooManyCopies(entry.card_id))?;
+            }
+            None => counts.push((entry.card_id, entry.count)),
+        }
+    }
+
+    let mut cards = Vec::with_capacity(counts.len());
+    for (card_id, count) in counts {
+        match fetch_full_card(pool, card_id).await {
+            Ok(card) => cards.push((card, count)),
+            Err(sqlx::Error::RowNotFound) => return Err(DbError::CardNotFound(card_id)),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(cards)
+}
+
+/// Looks up the ID of the card stored under a card number, if there is one.
+pub async fn find_card_id(
+    pool: &Pool,
+    series_code: &str,
+    set_code: &str,
+    number_in_set: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT id FROM cards WHERE series_code = ? AND set_code = ? AND number_in_set = ?",
+    )
+    .bind(series_code)
+    .bind(set_code)
+    .bind(number_in_set)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Streams the card catalog as flattened rows, one per printing, ordered by card and printing ID.
+///
+/// Rows are fetched lazily by a background task and handed over through a bounded channel,
+/// so the catalog is never held in memory at once and a slow reader pauses the query.
+pub fn stream_card_export_rows(
+    pool: Pool,
+) -> impl Stream<Item = Result<CardExportRow, sqlx::Error>> + Send + 'static {
+    let (mut sender, receiver) = mpsc::channel(64);
+    tokio::spawn(async move {
+        let mut rows = sqlx::query_as::<_, CardExportRow>(
+        "SELECT
+            c.id AS card_id,
+            p.id AS printing_id,
+            c.series_code || '-' || c.set_code || '-' || c.number_in_set || '-' || p.rarity_code
+                AS card_identifier,
+            c.series_code,
+            c.set_code,
+            c.number_in_set,
+            n.name,
+            c.card_type,
+            p.rarity_code,
+            p.rarity_type,
+            p.image_url,
+            cc.cost,
+            cc.blades,
+            lc.score,
+            COALESCE(cc.blade_heart, lc.blade_heart) AS blade_heart,
+            lc.special_heart,
+            (SELECT count FROM card_hearts WHERE card_id = c.id AND color = 'Pink') AS heart_pink,
+            (SELECT count FROM card_hearts WHERE card_id = c.id AND color = 'Red') AS heart_red,
+            (SELECT count FROM card_hearts WHERE card_id = c.id AND color = 'Yellow') AS heart_yellow,
+            (SELECT count FROM card_hearts WHERE card_id = c.id AND color = 'Green') AS heart_green,
+            (SELECT count FROM card_hearts WHERE card_id = c.id AND color = 'Blue') AS heart_blue,
+            (SELECT count FROM card_hearts WHERE card_id = c.id AND color = 'Purple') AS heart_purple,
+            (SELECT count FROM card_hearts WHERE card_id = c.id AND color = 'Gray') AS heart_gray,
+            COALESCE((SELECT GROUP_CONCAT(g.name, '|') FROM card_groups cg
+                JOIN groups g ON g.id = cg.group_id WHERE cg.card_id = c.id), '') AS groups,
+            COALESCE((SELECT GROUP_CONCAT(u.name, '|') FROM card_units cu
+                JOIN units u ON u.id = cu.unit_id WHERE cu.card_id = c.id), '') AS units,
+            COALESCE((SELECT GROUP_CONCAT(s.text, '|') FROM card_skills cs
+                JOIN skills s ON s.id = cs.skill_id WHERE cs.card_id = c.id), '') AS skills
+         FROM printings p
+         JOIN cards c ON c.id = p.card_id
+         JOIN names n ON n.id = c.name_id
+         LEFT JOIN character_cards cc ON cc.card_id = c.id
+         LEFT JOIN live_cards lc ON lc.card_id = c.id
+         ORDER BY c.id, p.id",
+        )
+        .fetch(&pool);
+
+        while let Some(row) = rows.next().await {
+            // Stop reading once the client has gone away.
+            if sender.send(row).await.is_err() {
+                break;
+            }
+        }
+    });
+    receiver
 }
 
 /// Creates multiple new cards and all their related data within a single database transaction.
 pub async fn create_bulk_cards(
     pool: &Pool,
-    rarity_cache: &HashMap<String, RarityType>,
+    actor: &Actor,
+    rarity_cache: &HashMap<String, Rarity>,
     name_variant_cache: &HashMap<String, String>,
     group_variant_cache: &HashMap<String, String>,
     new_cards: Vec<CreateCard>,
 ) -> DbResult<Vec<FullCard>> {
-    let mut tx = pool.begin().await?;
+    create_bulk_cards_indexed(
+        pool,
+        actor,
+        rarity_cache,
+        name_variant_cache,
+        group_variant_cache,
+        new_cards,
+    )
+    .await
+    .map_err(|(_, e)| e)
+}
+
+/// Same as [`create_bulk_cards`], but reports the index of the card that caused a failure.
+///
+/// The index is `None` when the failure is not tied to a single card (e.g. the commit failed).
+pub async fn create_bulk_cards_indexed(
+    pool: &Pool,
+    actor: &Actor,
+    rarity_cache: &HashMap<String, Rarity>,
+    name_variant_cache: &HashMap<String, String>,
+    group_variant_cache: &HashMap<String, String>,
+    new_cards: Vec<CreateCard>,
+) -> Result<Vec<FullCard>, (Option<usize>, DbError)> {
+    let mut tx = pool.begin().await.map_err(|e| (None, e.into()))?;
     let mut created_card_ids = Vec::with_capacity(new_cards.len());
 
-    for card in new_cards {
+    for (index, card) in new_cards.into_iter().enumerate() {
         // We pass the transaction `tx` to `create_full_card_with_tx`.
         let card_id = create_full_card_with_tx(
             &mut tx,
@@ -90,22 +343,32 @@ pub async fn create_bulk_cards(
             group_variant_cache,
             card,
         )
-        .await?;
+        .await
+        .map_err(|e| (Some(index), e))?;
         created_card_ids.push(card_id);
     }
 
+    // Fetch all the newly created full cards, and record them along with their creation.
+    let mut full_cards = Vec::with_capacity(created_card_ids.len());
+    for card_id in created_card_ids {
+        full_cards.push(
+            fetch_full_card_with_tx(&mut tx, card_id)
+                .await
+                .map_err(|e| (None, e.into()))?,
+        );
+    }
+
+    let changes: Vec<_> = full_cards.iter().map(|card| (None, Some(card))).collect();
+    record_card_changes(&mut tx, actor, AuditAction::Create, &changes)
+        .await
+        .map_err(|e| (None, e.into()))?;
+
     if let Err(e) = tx.commit().await {
         // The transaction will be rolled back automatically when `tx` is dropped.
         // We log the error here to make debugging easier.
         eprintln!("Failed to commit transaction for bulk card creation: {}", e);
         // Propagate the error.
-        return Err(DbError::Sqlx(e));
-    }
-
-    // After successfully committing, fetch all the newly created full cards.
-    let mut full_cards = Vec::with_capacity(created_card_ids.len());
-    for card_id in created_card_ids {
-        full_cards.push(fetch_full_card(pool, card_id).await?);
+        return Err((None, DbError::Sqlx(e)));
     }
 
     Ok(full_cards)
@@ -114,7 +377,8 @@ pub async fn create_bulk_cards(
 /// Creates a new card and all its related data within a single database transaction.
 pub async fn create_full_card(
     pool: &Pool,
-    rarity_cache: &HashMap<String, RarityType>,
+    actor: &Actor,
+    rarity_cache: &HashMap<String, Rarity>,
     name_variant_cache: &HashMap<String, String>,
     group_variant_cache: &HashMap<String, String>,
     new_card: CreateCard,
@@ -128,23 +392,61 @@ pub async fn create_full_card(
         new_card,
     )
     .await?;
+
+    let card = fetch_full_card_with_tx(&mut tx, card_id).await?;
+    record_card_changes(&mut tx, actor, AuditAction::Create, &[(None, Some(&card))]).await?;
     tx.commit().await?;
-    fetch_full_card(pool, card_id).await.map_err(DbError::from)
+    Ok(card)
+}
+
+/// Refuses a rarity that a set does not declare. Sets that declare none allow any rarity.
+async fn check_rarity_in_set(
+    conn: &mut sqlx::SqliteConnection,
+    set_code: &str,
+    rarity_code: &str,
+) -> DbResult<()> {
+    let in_set: bool = sqlx::query_scalar(
+        "SELECT NOT EXISTS(SELECT 1 FROM set_rarities WHERE set_code = ?1)
+             OR EXISTS(SELECT 1 FROM set_rarities WHERE set_code = ?1 AND rarity_code = ?2)",
+    )
+    .bind(set_code)
+    .bind(rarity_code)
+    .fetch_one(conn)
+    .await?;
+    if !in_set {
+        return Err(DbError::RarityNotInSet {
+            set_code: set_code.to_string(),
+            rarity_code: rarity_code.to_string(),
+        });
+    }
+    Ok(())
 }
 
 /// Helper to create a card within an existing transaction.
 async fn create_full_card_with_tx(
     tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
-    rarity_cache: &HashMap<String, RarityType>,
+    rarity_cache: &HashMap<String, Rarity>,
     name_variant_cache: &HashMap<String, String>,
     group_variant_cache: &HashMap<String, String>,
     new_card: CreateCard,
 ) -> DbResult<i64> {
-    // 1a. Look up rarity type from the cache.
+    // 1a. Refuse sets that do not exist or are in the trash, since `cards.set_code` has
+    // no foreign key. Look up rarity type from the cache, refusing rarities the set does
+    // not contain.
+    let set_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sets WHERE set_code = ? AND deleted_at IS NULL)",
+    )
+    .bind(&new_card.set_code)
+    .fetch_one(&mut **tx)
+    .await?;
+    if !set_exists {
+        return Err(DbError::SetNotFound(new_card.set_code.clone()));
+    }
     let rarity_type = rarity_cache
         .get(&new_card.rarity_code)
-        .cloned()
-        .unwrap_or(RarityType::Regular);
+        .map(|rarity| rarity.rarity_type)
+        .ok_or_else(|| DbError::RarityNotFound(new_card.rarity_code.clone()))?;
+    check_rarity_in_set(tx, &new_card.set_code, &new_card.rarity_code).await?;
 
     // 1b. Normalize the card name using the cache.
     let canonical_name = name_variant_cache
@@ -228,7 +530,7 @@ async fn create_full_card_with_tx(
             .cloned()
             .unwrap_or_else(|| group_name.clone());
         let group_id_result: Result<i64, sqlx::Error> =
-            sqlx::query_scalar("SELECT id FROM groups WHERE name = ?")
+            sqlx::query_scalar("SELECT id FROM groups WHERE name = ? AND deleted_at IS NULL")
                 .bind(&canonical_group_name)
                 .fetch_one(&mut **tx)
                 .await;
@@ -249,7 +551,7 @@ async fn create_full_card_with_tx(
     // 6. Link units. This assumes units already exist.
     for unit_name in &new_card.units {
         let unit_id_result: Result<i64, sqlx::Error> =
-            sqlx::query_scalar("SELECT id FROM units WHERE name = ?")
+            sqlx::query_scalar("SELECT id FROM units WHERE name = ? AND deleted_at IS NULL")
                 .bind(unit_name)
                 .fetch_one(&mut **tx)
                 .await;
@@ -270,7 +572,7 @@ async fn create_full_card_with_tx(
         // Insert the skill text if it doesn't exist, then get its ID.
         // `ON CONFLICT(text) DO NOTHING` is safe and handles the case where the skill already exists.
         sqlx::query("INSERT INTO skills (text) VALUES (?) ON CONFLICT(text) DO NOTHING")
-            .bind(&skill_text)
+            .bind(skill_text)
             .execute(&mut **tx)
             .await?;
 
@@ -289,15 +591,94 @@ async fn create_full_card_with_tx(
     Ok(card_id)
 }
 
+/// Helper to write a card's type data, hearts, groups, units and skills within a
+/// transaction. Groups and units must already exist under the names given.
+pub(crate) async fn insert_card_details(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    card: &FullCard,
+) -> DbResult<()> {
+    match &card.type_specifics {
+        Some(CardTypeSpecifics::Character(c)) => {
+            sqlx::query("INSERT INTO character_cards (card_id, cost, blades, blade_heart) VALUES (?, ?, ?, ?)")
+                .bind(card.base.id).bind(c.cost).bind(c.blades).bind(c.blade_heart)
+                .execute(&mut **tx).await?;
+        }
+        Some(CardTypeSpecifics::Live(l)) => {
+            sqlx::query("INSERT INTO live_cards (card_id, score, blade_heart, special_heart) VALUES (?, ?, ?, ?)")
+                .bind(card.base.id).bind(l.score).bind(l.blade_heart).bind(l.special_heart)
+                .execute(&mut **tx).await?;
+        }
+        None => {}
+    }
+
+    for (color, count) in &card.hearts {
+        sqlx::query("INSERT INTO card_hearts (card_id, color, count) VALUES (?, ?, ?)")
+            .bind(card.base.id)
+            .bind(color)
+            .bind(*count)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    for group_name in &card.groups {
+        let group_id: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM groups WHERE name = ? AND deleted_at IS NULL")
+                .bind(group_name)
+                .fetch_optional(&mut **tx)
+                .await?;
+        let group_id = group_id.ok_or_else(|| DbError::GroupNotFound(group_name.clone()))?;
+        sqlx::query("INSERT INTO card_groups (card_id, group_id) VALUES (?, ?)")
+            .bind(card.base.id)
+            .bind(group_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    for unit_name in &card.units {
+        let unit_id: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM units WHERE name = ? AND deleted_at IS NULL")
+                .bind(unit_name)
+                .fetch_optional(&mut **tx)
+                .await?;
+        let unit_id = unit_id.ok_or_else(|| DbError::UnitNotFound(unit_name.clone()))?;
+        sqlx::query("INSERT INTO card_units (card_id, unit_id) VALUES (?, ?)")
+            .bind(card.base.id)
+            .bind(unit_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    for skill_text in &card.skills {
+        sqlx::query("INSERT INTO skills (text) VALUES (?) ON CONFLICT(text) DO NOTHING")
+            .bind(skill_text)
+            .execute(&mut **tx)
+            .await?;
+        let skill_id: i64 = sqlx::query_scalar("SELECT id FROM skills WHERE text = ?")
+            .bind(skill_text)
+            .fetch_one(&mut **tx)
+            .await?;
+        sqlx::query("INSERT INTO card_skills (card_id, skill_id) VALUES (?, ?)")
+            .bind(card.base.id)
+            .bind(skill_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(())
+}
+
 /// Helper function to fetch the name of a card from its name_id.
 ///
 /// # Arguments
-/// * `pool` - The database connection pool.
+/// * `executor` - The pool or connection to query.
 /// * `name_id` - The ID of the name to look up.
-async fn fetch_name_for_card(pool: &Pool, name_id: i64) -> Result<String, sqlx::Error> {
+async fn fetch_name_for_card(
+    executor: impl sqlx::SqliteExecutor<'_>,
+    name_id: i64,
+) -> Result<String, sqlx::Error> {
     let row: (String,) = sqlx::query_as("SELECT name FROM names WHERE id = ?")
         .bind(name_id)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
     Ok(row.0)
 }
@@ -305,12 +686,15 @@ async fn fetch_name_for_card(pool: &Pool, name_id: i64) -> Result<String, sqlx::
 /// Helper function to fetch the name of a set from its code.
 ///
 /// # Arguments
-/// * `pool` - The database connection pool.
+/// * `executor` - The pool or connection to query.
 /// * `set_code` - The code of the set to look up (e.g., "bp2").
-async fn fetch_set_name(pool: &Pool, set_code: &str) -> Result<String, sqlx::Error> {
+async fn fetch_set_name(
+    executor: impl sqlx::SqliteExecutor<'_>,
+    set_code: &str,
+) -> Result<String, sqlx::Error> {
     let row: (String,) = sqlx::query_as("SELECT name FROM sets WHERE set_code = ?")
         .bind(set_code)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
     Ok(row.0)
 }
@@ -318,65 +702,74 @@ async fn fetch_set_name(pool: &Pool, set_code: &str) -> Result<String, sqlx::Err
 /// Helper function to fetch all group names associated with a card.
 ///
 /// # Argumentss
-/// * `pool` - The database connection pool.
+/// * `executor` - The pool or connection to query.
 /// * `card_id` - The ID of the card.
-async fn fetch_groups_for_card(pool: &Pool, card_id: i64) -> Result<Vec<String>, sqlx::Error> {
+async fn fetch_groups_for_card(
+    executor: impl sqlx::SqliteExecutor<'_>,
+    card_id: i64,
+) -> Result<Vec<String>, sqlx::Error> {
     sqlx::query_scalar(
         "SELECT g.name FROM groups g
          JOIN card_groups cg ON g.id = cg.group_id
          WHERE cg.card_id = ?",
     )
     .bind(card_id)
-    .fetch_all(pool)
+    .fetch_all(executor)
     .await
 }
 
 /// Helper function to fetch all unit names associated with a card.
 ///
 /// # Arguments
-/// * `pool` - The database connection pool.
+/// * `executor` - The pool or connection to query.
 /// * `card_id` - The ID of the card.
-async fn fetch_units_for_card(pool: &Pool, card_id: i64) -> Result<Vec<String>, sqlx::Error> {
+async fn fetch_units_for_card(
+    executor: impl sqlx::SqliteExecutor<'_>,
+    card_id: i64,
+) -> Result<Vec<String>, sqlx::Error> {
     sqlx::query_scalar(
         "SELECT u.name FROM units u
          JOIN card_units cu ON u.id = cu.unit_id
          WHERE cu.card_id = ?",
     )
     .bind(card_id)
-    .fetch_all(pool)
+    .fetch_all(executor)
     .await
 }
 
 /// Helper function to fetch all skill texts associated with a card.
 ///
 /// # Arguments
-/// * `pool` - The database connection pool.
+/// * `executor` - The pool or connection to query.
 /// * `card_id` - The ID of the card.
-async fn fetch_skills_for_card(pool: &Pool, card_id: i64) -> Result<Vec<String>, sqlx::Error> {
+async fn fetch_skills_for_card(
+    executor: impl sqlx::SqliteExecutor<'_>,
+    card_id: i64,
+) -> Result<Vec<String>, sqlx::Error> {
     sqlx::query_scalar(
         "SELECT s.text FROM skills s
          JOIN card_skills cs ON s.id = cs.skill_id
          WHERE cs.card_id = ?",
     )
     .bind(card_id)
-    .fetch_all(pool)
+    .fetch_all(executor)
     .await
 }
 
 /// Helper function to fetch the heart counts for a card.
 ///
 /// # Arguments
-/// * `pool` - The database connection pool.
+/// * `executor` - The pool or connection to query.
 /// * `card_id` - The ID of the card.
 async fn fetch_hearts_for_card(
-    pool: &Pool,
+    executor: impl sqlx::SqliteExecutor<'_>,
     card_id: i64,
 ) -> Result<HashMap<HeartColor, i64>, sqlx::Error> {
     let hearts = sqlx::query_as::<_, (HeartColor, i64)>(
         "SELECT color, count FROM card_hearts WHERE card_id = ?",
     )
     .bind(card_id)
-    .fetch_all(pool)
+    .fetch_all(executor)
     .await?;
     Ok(hearts.into_iter().collect())
 }
@@ -384,23 +777,110 @@ async fn fetch_hearts_for_card(
 /// Helper function to fetch all printings for a card.
 ///
 /// # Arguments
-/// * `pool` - The database connection pool.
+/// * `executor` - The pool or connection to query.
 /// * `card_id` - The ID of the card.
-async fn fetch_printings_for_card(pool: &Pool, card_id: i64) -> Result<Vec<Printing>, sqlx::Error> {
+async fn fetch_printings_for_card(
+    executor: impl sqlx::SqliteExecutor<'_>,
+    card_id: i64,
+) -> Result<Vec<Printing>, sqlx::Error> {
     sqlx::query_as("SELECT * FROM printings WHERE card_id = ?")
         .bind(card_id)
-        .fetch_all(pool)
+        .fetch_all(executor)
         .await
 }
 
+/// Points a printing at a new image, its resized variants and its perceptual hash, and
+/// returns the updated printing.
+///
+/// The change to the card is recorded in the audit log when an actor is given. Regenerated
+/// variants of the same image are not recorded.
+pub async fn set_printing_image_urls(
+    pool: &Pool,
+    actor: Option<&Actor>,
+    printing_id: i64,
+    image_url: &str,
+    thumbnail_url: Option<&str>,
+    medium_url: Option<&str>,
+    perceptual_hash: u64,
+) -> DbResult<Printing> {
+    let mut tx = pool.begin().await?;
+    let card_id: Option<i64> = sqlx::query_scalar("SELECT card_id FROM printings WHERE id = ?")
+        .bind(printing_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some(card_id) = card_id else {
+        return Err(DbError::PrintingNotFound(printing_id));
+    };
+    let before = match actor {
+        Some(_) => Some(fetch_full_card_with_tx(&mut tx, card_id).await?),
+        None => None,
+    };
+
+    // SQLite integers are signed, so the hash is stored with its bits reinterpreted.
+    sqlx::query(
+        "UPDATE printings
+         SET image_url = ?, thumbnail_url = ?, medium_url = ?, perceptual_hash = ?
+         WHERE id = ?",
+    )
+    .bind(image_url)
+    .bind(thumbnail_url)
+    .bind(medium_url)
+    .bind(perceptual_hash as i64)
+    .bind(printing_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if let (Some(actor), Some(before)) = (actor, before) {
+        let after = fetch_full_card_with_tx(&mut tx, card_id).await?;
+        record_card_changes(
+            &mut tx,
+            actor,
+            AuditAction::Update,
+            &[(Some(&before), Some(&after))],
+        )
+        .await?;
+    }
+
+    let printing = sqlx::query_as("SELECT * FROM printings WHERE id = ?")
+        .bind(printing_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(printing)
+}
+
+/// Fetches the printing ID, card ID and perceptual hash of every hashed printing.
+pub async fn fetch_perceptual_hashes(pool: &Pool) -> Result<Vec<(i64, i64, u64)>, sqlx::Error> {
+    let rows: Vec<(i64, i64, i64)> = sqlx::query_as(
+        "SELECT id, card_id, perceptual_hash FROM printings WHERE perceptual_hash IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(printing_id, card_id, hash)| (printing_id, card_id, hash as u64))
+        .collect())
+}
+
+/// Fetches the ID and image URL of every printing whose image is in the local image store.
+pub async fn fetch_locally_stored_printings(
+    pool: &Pool,
+) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, image_url FROM printings WHERE image_url LIKE '/images/%' ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
 /// Helper function to fetch the type-specific data (Character or Live) for a card.
 ///
 /// # Arguments
-/// * `pool` - The database connection pool.
+/// * `executor` - The pool or connection to query.
 /// * `card_id` - The ID of the card.
 /// * `card_type` - The `CardType` enum for the card.
 async fn fetch_type_specifics(
-    pool: &Pool,
+    executor: impl sqlx::SqliteExecutor<'_>,
     card_id: i64,
     card_type: CardType,
 ) -> Result<Option<CardTypeSpecifics>, sqlx::Error> {
@@ -408,14 +888,14 @@ async fn fetch_type_specifics(
         CardType::Character => {
             sqlx::query_as::<_, CharacterCard>("SELECT * FROM character_cards WHERE card_id = ?")
                 .bind(card_id)
-                .fetch_optional(pool)
+                .fetch_optional(executor)
                 .await
                 .map(|opt| opt.map(CardTypeSpecifics::Character))
         }
         CardType::Live => {
             sqlx::query_as::<_, LiveCard>("SELECT * FROM live_cards WHERE card_id = ?")
                 .bind(card_id)
-                .fetch_optional(pool)
+                .fetch_optional(executor)
                 .await
                 .map(|opt| opt.map(CardTypeSpecifics::Live))
         }
@@ -423,112 +903,2071 @@ async fn fetch_type_specifics(
     }
 }
 
-/// Inserts a new rarity mapping into the database.
-pub async fn add_rarity(pool: &Pool, code: &str, r_type: RarityType) -> Result<(), sqlx::Error> {
-    sqlx::query("INSERT INTO rarities (rarity_code, rarity_type) VALUES (?, ?)")
-        .bind(code)
-        .bind(r_type)
-        .execute(pool)
-        .await?;
-    Ok(())
+/// The columns of a rarity, as selected into a [`Rarity`].
+const RARITY_COLUMNS: &str =
+    "rarity_code, rarity_type, display_name, sort_rank, is_secret, description";
+
+/// Inserts a new rarity into the database.
+pub async fn add_rarity(pool: &Pool, actor: &Actor, rarity: &Rarity) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(&format!(
+        "INSERT INTO rarities ({RARITY_COLUMNS}) VALUES (?, ?, ?, ?, ?, ?)"
+    ))
+    .bind(&rarity.rarity_code)
+    .bind(rarity.rarity_type)
+    .bind(&rarity.display_name)
+    .bind(rarity.sort_rank)
+    .bind(rarity.is_secret)
+    .bind(&rarity.description)
+    .execute(&mut *tx)
+    .await?;
+    let after = serde_json::to_value(rarity).expect("rarities serialize to JSON");
+    record_creation(
+        &mut tx,
+        actor,
+        EntityKind::Rarity,
+        &rarity.rarity_code,
+        &after,
+    )
+    .await?;
+    tx.commit().await
 }
 
-/// Deletes a rarity mapping from the database.
+/// Deletes a rarity mapping from the database, handling the printings that use it as
+/// `action` says.
 pub async fn delete_rarity(
     pool: &Pool,
+    actor: &Actor,
     code: &str,
-) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
-    sqlx::query("DELETE FROM rarities WHERE rarity_code = ?")
-        .bind(code)
-        .execute(pool)
-        .await
+    action: &DependentAction,
+) -> DbResult<sqlx::sqlite::SqliteQueryResult> {
+    delete_referenced(
+        pool,
+        actor,
+        EntityKind::Rarity,
+        code,
+        action,
+        "SELECT json_object('rarity_code', rarity_code, 'rarity_type', rarity_type,
+                 'display_name', display_name, 'sort_rank', sort_rank,
+                 'is_secret', json(iif(is_secret, 'true', 'false')), 'description', description)
+         FROM rarities WHERE rarity_code = ?",
+        "DELETE FROM rarities WHERE rarity_code = ?",
+    )
+    .await
 }
 
-/// Fetches all sets from the database.
-pub async fn fetch_all_sets(pool: &Pool) -> Result<Vec<crate::models::SetResponse>, sqlx::Error> {
-    sqlx::query_as("SELECT set_code, name FROM sets")
-        .fetch_all(pool)
-        .await
-}
+/// The columns of a set, as selected into a [`SetResponse`].
+const SET_COLUMNS: &str =
+    "set_code, name, release_date, product_type, total_cards, language, sort_order";
 
-/// Inserts a new set into the database.
-pub async fn add_set(pool: &Pool, set_code: &str, name: &str) -> Result<(), sqlx::Error> {
-    sqlx::query("INSERT INTO sets (set_code, name) VALUES (?, ?)")
-        .bind(set_code)
-        .bind(name)
-        .execute(pool)
-        .await?;
-    Ok(())
-}
+/// Selects a set as a JSON object, for the audit log.
+const SET_SNAPSHOT: &str = "json_object('set_code', set_code, 'name', name,
+    'release_date', release_date, 'product_type', product_type, 'total_cards', total_cards,
+    'language', language, 'sort_order', sort_order)";
 
-/// Deletes a set from the database by its code.
-pub async fn delete_set(
-    pool: &Pool,
-    set_code: &str,
-) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
-    sqlx::query("DELETE FROM sets WHERE set_code = ?")
-        .bind(set_code)
-        .execute(pool)
-        .await
-}
+/// Assigns the fields of a set given in a JSON payload bound as `?3`, leaving the fields
+/// it leaves out unchanged. Optional fields given as `null` are cleared.
+const SET_ASSIGNMENTS: &str = "name = COALESCE(json_extract(?3, '$.name'), name),
+    release_date = iif(json_type(?3, '$.release_date') IS NULL, release_date,
+        json_extract(?3, '$.release_date')),
+    product_type = iif(json_type(?3, '$.product_type') IS NULL, product_type,
+        json_extract(?3, '$.product_type')),
+    total_cards = iif(json_type(?3, '$.total_cards') IS NULL, total_cards,
+        json_extract(?3, '$.total_cards')),
+    language = COALESCE(json_extract(?3, '$.language'), language),
+    sort_order = iif(json_type(?3, '$.sort_order') IS NULL, sort_order,
+        json_extract(?3, '$.sort_order'))";
 
-/// Fetches all groups from the database.
-pub async fn fetch_all_groups(pool: &Pool) -> Result<Vec<String>, sqlx::Error> {
-    sqlx::query_scalar("SELECT name FROM groups")
-        .fetch_all(pool)
-        .await
+/// The largest official number of cards a set can have, as checked by the `sets` table.
+pub const MAX_TOTAL_CARDS: i64 = 999;
+
+/// Fetches all sets from the database, by their sort order.
+pub async fn fetch_all_sets(pool: &Pool) -> Result<Vec<SetResponse>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {SET_COLUMNS} FROM sets WHERE deleted_at IS NULL
+         ORDER BY sort_order IS NULL, sort_order, id"
+    ))
+    .fetch_all(pool)
+    .await
 }
 
-/// Inserts a new group into the database.
-pub async fn add_group(pool: &Pool, name: &str) -> Result<(), sqlx::Error> {
-    sqlx::query("INSERT INTO groups (name) VALUES (?)")
-        .bind(name)
-        .execute(pool)
-        .await?;
+/// Checks that a release date is a valid `YYYY-MM-DD` date.
+async fn validate_release_date(pool: &Pool, release_date: Option<&str>) -> DbResult<()> {
+    if let Some(release_date) = release_date {
+        let valid: bool = sqlx::query_scalar("SELECT date(?1) IS ?1")
+            .bind(release_date)
+            .fetch_one(pool)
+            .await?;
+        if !valid {
+            return Err(DbError::InvalidDate(release_date.to_string()));
+        }
+    }
     Ok(())
 }
 
-/// Deletes a group from the database by its name.
-pub async fn delete_group(
-    pool: &Pool,
-    name: &str,
-) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
-    sqlx::query("DELETE FROM groups WHERE name = ?")
-        .bind(name)
-        .execute(pool)
-        .await
+/// Checks that the official number of cards of a set is within bounds.
+fn validate_total_cards(total_cards: Option<i64>) -> DbResult<()> {
+    match total_cards {
+        Some(total) if !(0..=MAX_TOTAL_CARDS).contains(&total) => {
+            Err(DbError::InvalidTotalCards(total))
+        }
+        _ => Ok(()),
+    }
 }
 
-/// Fetches all units from the database.
-pub async fn fetch_all_units(pool: &Pool) -> Result<Vec<String>, sqlx::Error> {
-    sqlx::query_scalar("SELECT name FROM units")
-        .fetch_all(pool)
-        .await
-}
+/// Inserts a new set into the database.
+pub async fn add_set(pool: &Pool, actor: &Actor, set: &CreateSet) -> DbResult<()> {
+    validate_release_date(pool, set.release_date.as_deref()).await?;
+    validate_total_cards(set.total_cards)?;
+    let fields = serde_json::to_string(set).expect("sets serialize to JSON");
 
-/// Inserts a new unit into the database.
-pub async fn add_unit(pool: &Pool, name: &str) -> Result<(), sqlx::Error> {
-    sqlx::query("INSERT INTO units (name) VALUES (?)")
-        .bind(name)
-        .execute(pool)
+    let mut tx = pool.begin().await?;
+    sqlx::query("INSERT INTO sets (set_code, name) VALUES (?, ?)")
+        .bind(&set.set_code)
+        .bind(&set.name)
+        .execute(&mut *tx)
         .await?;
+    sqlx::query(&format!(
+        "UPDATE sets SET {SET_ASSIGNMENTS} WHERE set_code = ?2"
+    ))
+    .bind(&set.set_code)
+    .bind(&set.set_code)
+    .bind(&fields)
+    .execute(&mut *tx)
+    .await?;
+    let after: String = sqlx::query_scalar(&format!(
+        "SELECT {SET_SNAPSHOT} FROM sets WHERE set_code = ?"
+    ))
+    .bind(&set.set_code)
+    .fetch_one(&mut *tx)
+    .await?;
+    record_audit(
+        &mut tx,
+        actor,
+        AuditAction::Create,
+        EntityKind::Set,
+        &set.set_code,
+        None,
+        Some(&after),
+    )
+    .await?;
+    tx.commit().await?;
     Ok(())
 }
 
-/// Deletes a unit from the database by its name.
+/// A card of a set checklist joined with one of its printings, if it has any.
+type ChecklistRow = (
+    i64,
+    String,
+    String,
+    String,
+    CardType,
+    Option<i64>,
+    Option<String>,
+    Option<RarityType>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+/// Fetches a set that is not in the trash by its code, with every card of it in number
+/// order.
+///
+/// Returns `None` if no such set exists.
+pub async fn fetch_set_checklist(
+    pool: &Pool,
+    set_code: &str,
+) -> Result<Option<SetChecklist>, sqlx::Error> {
+    let set: Option<SetResponse> = sqlx::query_as(&format!(
+        "SELECT {SET_COLUMNS} FROM sets WHERE set_code = ? AND deleted_at IS NULL"
+    ))
+    .bind(set_code)
+    .fetch_optional(pool)
+    .await?;
+    let Some(set) = set else {
+        return Ok(None);
+    };
+
+    let rows: Vec<ChecklistRow> = sqlx::query_as(
+        "SELECT c.id, c.series_code || '-' || c.set_code || '-' || c.number_in_set,
+             c.number_in_set, n.name, c.card_type,
+             p.id, p.rarity_code, p.rarity_type, p.image_url, p.thumbnail_url, p.medium_url
+         FROM cards c
+         JOIN names n ON n.id = c.name_id
+         LEFT JOIN printings p ON p.card_id = c.id
+         WHERE c.set_code = ?
+         ORDER BY CAST(c.number_in_set AS INTEGER), c.number_in_set, c.series_code, p.id",
+    )
+    .bind(set_code)
+    .fetch_all(pool)
+    .await?;
+    let mut cards: Vec<ChecklistCard> = Vec::new();
+    for row in rows {
+        let card_id = row.0;
+        if cards.last().is_none_or(|card| card.card_id != card_id) {
+            cards.push(ChecklistCard {
+                card_id,
+                card_identifier: row.1,
+                number_in_set: row.2,
+                name: row.3,
+                card_type: row.4,
+                printings: Vec::new(),
+            });
+        }
+        if let (Some(card), (Some(id), Some(rarity_code), Some(rarity_type))) =
+            (cards.last_mut(), (row.5, row.6, row.7))
+        {
+            card.printings.push(Printing {
+                id,
+                card_id,
+                rarity_code,
+                rarity_type,
+                image_url: row.8,
+                thumbnail_url: row.9,
+                medium_url: row.10,
+            });
+        }
+    }
+
+    let numbers: std::collections::HashSet<i64> = cards
+        .iter()
+        .filter_map(|card| card.number_in_set.parse().ok())
+        .collect();
+    let missing_numbers = (1..=set.total_cards.unwrap_or(0))
+        .filter(|number| !numbers.contains(number))
+        .collect();
+
+    Ok(Some(SetChecklist {
+        set,
+        cards,
+        missing_numbers,
+    }))
+}
+
+/// Moves a set to the trash by its code, handling its cards as `action` says. Sets
+/// already in the trash are not affected, and `Cascade` is refused with
+/// `CascadeIntoTrash`, so that sets in the trash never have cards.
+pub async fn delete_set(
+    pool: &Pool,
+    actor: &Actor,
+    set_code: &str,
+    action: &DependentAction,
+) -> DbResult<sqlx::sqlite::SqliteQueryResult> {
+    delete_referenced(
+        pool,
+        actor,
+        EntityKind::Set,
+        set_code,
+        action,
+        &format!("SELECT {SET_SNAPSHOT} FROM sets WHERE set_code = ? AND deleted_at IS NULL"),
+        "UPDATE sets SET deleted_at = CURRENT_TIMESTAMP WHERE set_code = ? AND deleted_at IS NULL",
+    )
+    .await
+}
+
+/// Changes the code, name or other fields of a set, moving its cards along to a new
+/// code. Fields left out are not changed, and sets in the trash are not affected.
+pub async fn update_set(
+    pool: &Pool,
+    actor: &Actor,
+    set_code: &str,
+    update: &UpdateSet,
+) -> DbResult<sqlx::sqlite::SqliteQueryResult> {
+    validate_release_date(pool, update.release_date.clone().flatten().as_deref()).await?;
+    validate_total_cards(update.total_cards.flatten())?;
+    let fields = serde_json::to_string(update).expect("sets serialize to JSON");
+    rename_referenced(
+        pool,
+        actor,
+        EntityKind::Set,
+        set_code,
+        (
+            update.set_code.as_deref().unwrap_or(set_code),
+            Some(&fields),
+        ),
+        &format!("SELECT {SET_SNAPSHOT} FROM sets WHERE set_code = ? AND deleted_at IS NULL"),
+        &[
+            &format!(
+                "UPDATE sets SET set_code = ?1, {SET_ASSIGNMENTS}
+                 WHERE set_code = ?2 AND deleted_at IS NULL"
+            ),
+            "UPDATE cards SET set_code = ?1 WHERE set_code = ?2",
+        ],
+    )
+    .await
+}
+
+/// Snapshots the rarities a set declares, for the audit log.
+const SET_RARITIES_SNAPSHOT: &str = "SELECT json_object('set_code', set_code, 'rarities', json(
+         (SELECT json_group_array(rarity_code) FROM
+             (SELECT rarity_code FROM set_rarities r WHERE r.set_code = sets.set_code
+              ORDER BY rarity_code))))
+     FROM sets WHERE set_code = ? AND deleted_at IS NULL";
+
+/// Fetches the rarities a set declares, by rank, or `None` if no such set exists.
+pub async fn fetch_set_rarities(
+    pool: &Pool,
+    set_code: &str,
+) -> Result<Option<Vec<Rarity>>, sqlx::Error> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sets WHERE set_code = ? AND deleted_at IS NULL)",
+    )
+    .bind(set_code)
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Ok(None);
+    }
+
+    let rarities = sqlx::query_as(&format!(
+        "SELECT {RARITY_COLUMNS} FROM rarities JOIN set_rarities USING (rarity_code)
+         WHERE set_code = ? ORDER BY sort_rank, rarity_code"
+    ))
+    .bind(set_code)
+    .fetch_all(pool)
+    .await?;
+    Ok(Some(rarities))
+}
+
+/// Replaces the rarities a set declares. Cards of a set that declares rarities can only be
+/// created with one of them, so rarities its printings already have must stay declared,
+/// or the change is refused with `RaritiesInUse`.
+///
+/// Returns `false` if no such set exists.
+pub async fn set_set_rarities(
+    pool: &Pool,
+    actor: &Actor,
+    set_code: &str,
+    rarity_codes: &[String],
+) -> DbResult<bool> {
+    let mut tx = pool.begin().await?;
+    let Some(before): Option<String> = sqlx::query_scalar(SET_RARITIES_SNAPSHOT)
+        .bind(set_code)
+        .fetch_optional(&mut *tx)
+        .await?
+    else {
+        return Ok(false);
+    };
+
+    sqlx::query("DELETE FROM set_rarities WHERE set_code = ?")
+        .bind(set_code)
+        .execute(&mut *tx)
+        .await?;
+    for rarity_code in rarity_codes {
+        let known: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM rarities WHERE rarity_code = ?)")
+                .bind(rarity_code)
+                .fetch_one(&mut *tx)
+                .await?;
+        if !known {
+            return Err(DbError::RarityNotFound(rarity_code.clone()));
+        }
+        sqlx::query(
+            "INSERT INTO set_rarities (set_code, rarity_code) VALUES (?, ?)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(set_code)
+        .bind(rarity_code)
+        .execute(&mut *tx)
+        .await?;
+    }
+    if !rarity_codes.is_empty() {
+        let undeclared: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT printings.rarity_code FROM printings
+             JOIN cards ON cards.id = printings.card_id
+             WHERE cards.set_code = ?1 AND printings.rarity_code NOT IN
+                 (SELECT rarity_code FROM set_rarities WHERE set_code = ?1)
+             ORDER BY printings.rarity_code",
+        )
+        .bind(set_code)
+        .fetch_all(&mut *tx)
+        .await?;
+        if !undeclared.is_empty() {
+            return Err(DbError::RaritiesInUse {
+                set_code: set_code.to_string(),
+                rarity_codes: undeclared,
+            });
+        }
+    }
+
+    let after: String = sqlx::query_scalar(SET_RARITIES_SNAPSHOT)
+        .bind(set_code)
+        .fetch_one(&mut *tx)
+        .await?;
+    record_audit(
+        &mut tx,
+        actor,
+        AuditAction::Update,
+        EntityKind::Set,
+        set_code,
+        Some(&before),
+        Some(&after),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Fetches all groups from the database.
+pub async fn fetch_all_groups(pool: &Pool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT name FROM groups WHERE deleted_at IS NULL")
+        .fetch_all(pool)
+        .await
+}
+
+/// Inserts a new group into the database.
+pub async fn add_group(pool: &Pool, actor: &Actor, name: &str) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("INSERT INTO groups (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    let after = serde_json::json!({ "name": name });
+    record_creation(&mut tx, actor, EntityKind::Group, name, &after).await?;
+    tx.commit().await
+}
+
+/// Moves a group to the trash by its name, handling its cards as `action` says. Groups
+/// already in the trash are not affected, and `Cascade` is refused with
+/// `CascadeIntoTrash`, so that groups in the trash never have cards.
+pub async fn delete_group(
+    pool: &Pool,
+    actor: &Actor,
+    name: &str,
+    action: &DependentAction,
+) -> DbResult<sqlx::sqlite::SqliteQueryResult> {
+    delete_referenced(
+        pool,
+        actor,
+        EntityKind::Group,
+        name,
+        action,
+        "SELECT json_object('name', name) FROM groups WHERE name = ? AND deleted_at IS NULL",
+        "UPDATE groups SET deleted_at = CURRENT_TIMESTAMP WHERE name = ? AND deleted_at IS NULL",
+    )
+    .await
+}
+
+/// Renames a group, pointing its group variants to the new name. With
+/// `keep_old_as_variant`, the old name is kept as a variant of the new one. Groups in the
+/// trash are not affected.
+pub async fn rename_group(
+    pool: &Pool,
+    actor: &Actor,
+    name: &str,
+    new_name: &str,
+    keep_old_as_variant: bool,
+) -> DbResult<sqlx::sqlite::SqliteQueryResult> {
+    let mut statements = vec![
+        "UPDATE groups SET name = ?1 WHERE name = ?2 AND deleted_at IS NULL",
+        "UPDATE group_variants SET canonical_name = ?1 WHERE canonical_name = ?2",
+    ];
+    if keep_old_as_variant && name != new_name {
+        statements.push(
+            "INSERT INTO group_variants (variant_name, canonical_name) VALUES (?2, ?1)
+             ON CONFLICT (variant_name) DO UPDATE SET canonical_name = excluded.canonical_name",
+        );
+    }
+    rename_referenced(
+        pool,
+        actor,
+        EntityKind::Group,
+        name,
+        (new_name, None),
+        "SELECT json_object('name', name) FROM groups WHERE name = ? AND deleted_at IS NULL",
+        &statements,
+    )
+    .await
+}
+
+/// Fetches all units from the database.
+pub async fn fetch_all_units(pool: &Pool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT name FROM units WHERE deleted_at IS NULL")
+        .fetch_all(pool)
+        .await
+}
+
+/// Inserts a new unit into the database.
+pub async fn add_unit(pool: &Pool, actor: &Actor, name: &str) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("INSERT INTO units (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    let after = serde_json::json!({ "name": name });
+    record_creation(&mut tx, actor, EntityKind::Unit, name, &after).await?;
+    tx.commit().await
+}
+
+/// Moves a unit to the trash by its name, handling its cards as `action` says. Units
+/// already in the trash are not affected, and `Cascade` is refused with
+/// `CascadeIntoTrash`, so that units in the trash never have cards.
 pub async fn delete_unit(
     pool: &Pool,
+    actor: &Actor,
+    name: &str,
+    action: &DependentAction,
+) -> DbResult<sqlx::sqlite::SqliteQueryResult> {
+    delete_referenced(
+        pool,
+        actor,
+        EntityKind::Unit,
+        name,
+        action,
+        "SELECT json_object('name', name) FROM units WHERE name = ? AND deleted_at IS NULL",
+        "UPDATE units SET deleted_at = CURRENT_TIMESTAMP WHERE name = ? AND deleted_at IS NULL",
+    )
+    .await
+}
+
+/// Renames a unit. Units in the trash are not affected.
+pub async fn rename_unit(
+    pool: &Pool,
+    actor: &Actor,
     name: &str,
+    new_name: &str,
+) -> DbResult<sqlx::sqlite::SqliteQueryResult> {
+    rename_referenced(
+        pool,
+        actor,
+        EntityKind::Unit,
+        name,
+        (new_name, None),
+        "SELECT json_object('name', name) FROM units WHERE name = ? AND deleted_at IS NULL",
+        &["UPDATE units SET name = ?1 WHERE name = ?2 AND deleted_at IS NULL"],
+    )
+    .await
+}
+
+/// The table, key column and JSON snapshot of each kind of trash.
+fn trash_table(kind: TrashKind) -> (&'static str, &'static str, String) {
+    match kind {
+        TrashKind::Set => (
+            "sets",
+            "set_code",
+            format!("json_set({SET_SNAPSHOT}, '$.deleted_at', deleted_at)"),
+        ),
+        TrashKind::Group => (
+            "groups",
+            "name",
+            "json_object('name', name, 'deleted_at', deleted_at)".to_string(),
+        ),
+        TrashKind::Unit => (
+            "units",
+            "name",
+            "json_object('name', name, 'deleted_at', deleted_at)".to_string(),
+        ),
+    }
+}
+
+/// Fetches every deleted set, group and unit, most recently deleted first.
+pub async fn fetch_trash(pool: &Pool) -> Result<Vec<TrashItem>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT 'set' AS kind, set_code AS key, name, deleted_at FROM sets
+         WHERE deleted_at IS NOT NULL
+         UNION ALL
+         SELECT 'group', name, name, deleted_at FROM groups WHERE deleted_at IS NOT NULL
+         UNION ALL
+         SELECT 'unit', name, name, deleted_at FROM units WHERE deleted_at IS NOT NULL
+         ORDER BY deleted_at DESC, kind, key",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Checks whether a set, group or unit is in the trash.
+pub async fn is_in_trash(pool: &Pool, kind: TrashKind, key: &str) -> Result<bool, sqlx::Error> {
+    let (table, key_column, _) = trash_table(kind);
+    sqlx::query_scalar(&format!(
+        "SELECT EXISTS(SELECT 1 FROM {table} WHERE {key_column} = ? AND deleted_at IS NOT NULL)"
+    ))
+    .bind(key)
+    .fetch_one(pool)
+    .await
+}
+
+/// Restores a set, group or unit from the trash, recording it as created again. Items
+/// only go to the trash once no card belongs to them, so they are restored without any.
+///
+/// Returns `false` if no such item is in the trash.
+pub async fn restore_from_trash(
+    pool: &Pool,
+    actor: &Actor,
+    kind: TrashKind,
+    key: &str,
+) -> Result<bool, sqlx::Error> {
+    let (table, key_column, snapshot) = trash_table(kind);
+    let mut tx = pool.begin().await?;
+    let restored = sqlx::query(&format!(
+        "UPDATE {table} SET deleted_at = NULL WHERE {key_column} = ? AND deleted_at IS NOT NULL"
+    ))
+    .bind(key)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if restored {
+        let after: String = sqlx::query_scalar(&format!(
+            "SELECT json_remove({snapshot}, '$.deleted_at') FROM {table} WHERE {key_column} = ?"
+        ))
+        .bind(key)
+        .fetch_one(&mut *tx)
+        .await?;
+        record_audit(
+            &mut tx,
+            actor,
+            AuditAction::Create,
+            kind.into(),
+            key,
+            None,
+            Some(&after),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(restored)
+}
+
+/// Permanently deletes a set, group or unit from the trash.
+///
+/// Cards cannot belong to items in the trash, but should one still do, e.g. after the
+/// database was changed by hand, the purge is refused with `HasDependents`.
+///
+/// Returns `false` if no such item is in the trash.
+pub async fn purge_from_trash(
+    pool: &Pool,
+    actor: &Actor,
+    kind: TrashKind,
+    key: &str,
+) -> DbResult<bool> {
+    let (table, key_column, snapshot) = trash_table(kind);
+    let result = delete_referenced(
+        pool,
+        actor,
+        kind.into(),
+        key,
+        &DependentAction::Refuse,
+        &format!(
+            "SELECT {snapshot} FROM {table} WHERE {key_column} = ? AND deleted_at IS NOT NULL"
+        ),
+        &format!("DELETE FROM {table} WHERE {key_column} = ? AND deleted_at IS NOT NULL"),
+    )
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Fetches all name variant mappings from the database.
+pub async fn fetch_all_name_variants(pool: &Pool) -> Result<Vec<NameVariant>, sqlx::Error> {
+    sqlx::query_as("SELECT variant_name, canonical_name FROM name_variants ORDER BY variant_name")
+        .fetch_all(pool)
+        .await
+}
+
+/// Inserts a new name variant mapping into the database.
+pub async fn add_name_variant(
+    pool: &Pool,
+    actor: &Actor,
+    variant_name: &str,
+    canonical_name: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("INSERT INTO name_variants (variant_name, canonical_name) VALUES (?, ?)")
+        .bind(variant_name)
+        .bind(canonical_name)
+        .execute(&mut *tx)
+        .await?;
+    let after =
+        serde_json::json!({ "variant_name": variant_name, "canonical_name": canonical_name });
+    record_creation(
+        &mut tx,
+        actor,
+        EntityKind::NameVariant,
+        variant_name,
+        &after,
+    )
+    .await?;
+    tx.commit().await
+}
+
+/// Deletes a name variant mapping from the database.
+pub async fn delete_name_variant(
+    pool: &Pool,
+    actor: &Actor,
+    variant_name: &str,
 ) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
-    sqlx::query("DELETE FROM units WHERE name = ?")
-        .bind(name)
-        .execute(pool)
+    delete_audited(
+        pool,
+        actor,
+        EntityKind::NameVariant,
+        variant_name,
+        "SELECT json_object('variant_name', variant_name, 'canonical_name', canonical_name)
+         FROM name_variants WHERE variant_name = ?",
+        "DELETE FROM name_variants WHERE variant_name = ?",
+    )
+    .await
+}
+
+/// Fetches all group variant mappings from the database.
+pub async fn fetch_all_group_variants(pool: &Pool) -> Result<Vec<GroupVariant>, sqlx::Error> {
+    sqlx::query_as("SELECT variant_name, canonical_name FROM group_variants ORDER BY variant_name")
+        .fetch_all(pool)
         .await
 }
 
+/// Inserts a new group variant mapping into the database.
+pub async fn add_group_variant(
+    pool: &Pool,
+    actor: &Actor,
+    variant_name: &str,
+    canonical_name: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("INSERT INTO group_variants (variant_name, canonical_name) VALUES (?, ?)")
+        .bind(variant_name)
+        .bind(canonical_name)
+        .execute(&mut *tx)
+        .await?;
+    let after =
+        serde_json::json!({ "variant_name": variant_name, "canonical_name": canonical_name });
+    record_creation(
+        &mut tx,
+        actor,
+        EntityKind::GroupVariant,
+        variant_name,
+        &after,
+    )
+    .await?;
+    tx.commit().await
+}
+
+/// Deletes a group variant mapping from the database.
+pub async fn delete_group_variant(
+    pool: &Pool,
+    actor: &Actor,
+    variant_name: &str,
+) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
+    delete_audited(
+        pool,
+        actor,
+        EntityKind::GroupVariant,
+        variant_name,
+        "SELECT json_object('variant_name', variant_name, 'canonical_name', canonical_name)
+         FROM group_variants WHERE variant_name = ?",
+        "DELETE FROM group_variants WHERE variant_name = ?",
+    )
+    .await
+}
+
+/// Fetches all rarities from the database, by rank.
+pub async fn fetch_all_rarities(pool: &Pool) -> Result<Vec<Rarity>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {RARITY_COLUMNS} FROM rarities ORDER BY sort_rank, rarity_code"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+/// The tables of a kind of translation: the table of translations, its column referencing
+/// the text translated, and the table of that text with its key column.
+fn translation_table(
+    kind: TranslationKind,
+) -> (&'static str, &'static str, &'static str, &'static str) {
+    match kind {
+        TranslationKind::Name => ("name_translations", "name_id", "names", "name"),
+        TranslationKind::Group => ("group_translations", "group_id", "groups", "name"),
+        TranslationKind::Unit => ("unit_translations", "unit_id", "units", "name"),
+        TranslationKind::Set => ("set_translations", "set_id", "sets", "set_code"),
+        TranslationKind::Skill => ("skill_translations", "skill_id", "skills", "text"),
+    }
+}
+
+/// Fetches translations, optionally only of one kind or into one language, ordered by
+/// kind, key and language.
+pub async fn fetch_translations(
+    pool: &Pool,
+    kind: Option<TranslationKind>,
+    language: Option<&str>,
+) -> Result<Vec<Translation>, sqlx::Error> {
+    let kinds = match kind {
+        Some(kind) => vec![kind],
+        None => TranslationKind::ALL.to_vec(),
+    };
+
+    let mut translations = Vec::new();
+    for kind in kinds {
+        let (table, id_column, entity_table, key_column) = translation_table(kind);
+        let rows: Vec<(String, String, String)> = sqlx::query_as(&format!(
+            "SELECT e.{key_column}, t.language, t.text
+             FROM {table} t JOIN {entity_table} e ON e.id = t.{id_column}
+             WHERE ?1 IS NULL OR t.language = ?1
+             ORDER BY e.{key_column}, t.language"
+        ))
+        .bind(language)
+        .fetch_all(pool)
+        .await?;
+        translations.extend(rows.into_iter().map(|(key, language, text)| Translation {
+            kind,
+            key,
+            language,
+            text,
+        }));
+    }
+    Ok(translations)
+}
+
+/// Fetches the translations of texts of one kind, keyed by original text. Each text gets
+/// the translation into the earliest of `languages` it has one for; texts without any are
+/// left out.
+pub async fn fetch_localized_texts(
+    pool: &Pool,
+    kind: TranslationKind,
+    keys: &[&str],
+    languages: &[String],
+) -> Result<HashMap<String, String>, sqlx::Error> {
+    let (table, id_column, entity_table, key_column) = translation_table(kind);
+    let rows: Vec<(String, String, String)> = sqlx::query_as(&format!(
+        "SELECT e.{key_column}, t.language, t.text
+         FROM {table} t JOIN {entity_table} e ON e.id = t.{id_column}
+         WHERE e.{key_column} IN (SELECT value FROM json_each(?1))
+           AND t.language IN (SELECT value FROM json_each(?2))"
+    ))
+    .bind(serde_json::to_string(keys).expect("keys serialize to JSON"))
+    .bind(serde_json::to_string(languages).expect("languages serialize to JSON"))
+    .fetch_all(pool)
+    .await?;
+
+    let preference = |language: &str| languages.iter().position(|l| l == language);
+    let mut localized: HashMap<String, (Option<usize>, String)> = HashMap::new();
+    for (key, language, text) in rows {
+        let rank = preference(&language);
+        match localized.get(&key) {
+            Some((best, _)) if *best <= rank => {}
+            _ => {
+                localized.insert(key, (rank, text));
+            }
+        }
+    }
+    Ok(localized
+        .into_iter()
+        .map(|(key, (_, text))| (key, text))
+        .collect())
+}
+
+/// Inserts a translation without recording it in the audit log, e.g. when restoring a
+/// dump. Translations of texts that do not exist are skipped.
+pub async fn insert_translation(
+    conn: &mut sqlx::SqliteConnection,
+    translation: &Translation,
+) -> Result<(), sqlx::Error> {
+    let (table, id_column, entity_table, key_column) = translation_table(translation.kind);
+    sqlx::query(&format!(
+        "INSERT INTO {table} ({id_column}, language, text)
+         SELECT id, ?, ? FROM {entity_table} WHERE {key_column} = ?"
+    ))
+    .bind(&translation.language)
+    .bind(&translation.text)
+    .bind(&translation.key)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Inserts or replaces the translation of a name, set or skill into a language.
+///
+/// Returns `false` if the text translated does not exist.
+pub async fn upsert_translation(
+    pool: &Pool,
+    actor: &Actor,
+    translation: &Translation,
+) -> Result<bool, sqlx::Error> {
+    let (table, id_column, entity_table, key_column) = translation_table(translation.kind);
+    let mut tx = pool.begin().await?;
+    let Some(entity_id): Option<i64> = sqlx::query_scalar(&format!(
+        "SELECT id FROM {entity_table} WHERE {key_column} = ?"
+    ))
+    .bind(&translation.key)
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(false);
+    };
+
+    let before: Option<String> = sqlx::query_scalar(&format!(
+        "SELECT text FROM {table} WHERE {id_column} = ? AND language = ?"
+    ))
+    .bind(entity_id)
+    .bind(&translation.language)
+    .fetch_optional(&mut *tx)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT INTO {table} ({id_column}, language, text) VALUES (?, ?, ?)
+         ON CONFLICT ({id_column}, language) DO UPDATE SET text = excluded.text"
+    ))
+    .bind(entity_id)
+    .bind(&translation.language)
+    .bind(&translation.text)
+    .execute(&mut *tx)
+    .await?;
+
+    let snapshot = |text: &str| {
+        serde_json::to_string(&Translation {
+            text: text.to_string(),
+            ..translation.clone()
+        })
+        .expect("translations serialize to JSON")
+    };
+    let entity_key = format!(
+        "{}:{}:{}",
+        translation.kind.as_str(),
+        translation.language,
+        translation.key
+    );
+    record_audit(
+        &mut tx,
+        actor,
+        if before.is_some() {
+            AuditAction::Update
+        } else {
+            AuditAction::Create
+        },
+        EntityKind::Translation,
+        &entity_key,
+        before.as_deref().map(snapshot).as_deref(),
+        Some(&snapshot(&translation.text)),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
 /// Fetches all distinct canonical card names from the database.
 pub async fn fetch_all_card_names(pool: &Pool) -> Result<Vec<String>, sqlx::Error> {
     sqlx::query_scalar("SELECT name FROM names")
         .fetch_all(pool)
         .await
 }
+
+/// Fetches all collections from the database.
+pub async fn fetch_all_collections(pool: &Pool) -> Result<Vec<Collection>, sqlx::Error> {
+    sqlx::query_as("SELECT id, name, owner, api_key_id FROM collections ORDER BY id")
+        .fetch_all(pool)
+        .await
+}
+
+/// Fetches a single collection by its ID.
+pub async fn fetch_collection(pool: &Pool, id: i64) -> Result<Collection, sqlx::Error> {
+    sqlx::query_as("SELECT id, name, owner, api_key_id FROM collections WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Inserts a new collection into the database, owned by an API key, and returns it.
+pub async fn add_collection(
+    pool: &Pool,
+    name: &str,
+    owner: Option<&str>,
+    api_key_id: i64,
+) -> Result<Collection, sqlx::Error> {
+    let id = sqlx::query("INSERT INTO collections (name, owner, api_key_id) VALUES (?, ?, ?)")
+        .bind(name)
+        .bind(owner)
+        .bind(api_key_id)
+        .execute(pool)
+        .await?
+        .last_insert_rowid();
+    fetch_collection(pool, id).await
+}
+
+/// Deletes a collection. Its items and wants are removed along with it by their foreign
+/// keys.
+pub async fn delete_collection(
+    pool: &Pool,
+    id: i64,
+) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
+    sqlx::query("DELETE FROM collections WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+}
+
+/// Helper to check that a collection exists within an existing transaction.
+async fn ensure_collection_exists(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    collection_id: i64,
+) -> DbResult<()> {
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM collections WHERE id = ?")
+        .bind(collection_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    exists
+        .map(|_| ())
+        .ok_or(DbError::CollectionNotFound(collection_id))
+}
+
+/// Helper to check that a printing exists within an existing transaction.
+async fn ensure_printing_exists(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    printing_id: i64,
+) -> DbResult<()> {
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM printings WHERE id = ?")
+        .bind(printing_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    exists
+        .map(|_| ())
+        .ok_or(DbError::PrintingNotFound(printing_id))
+}
+
+/// Helper to store the owned quantity of a printing, removing the row when it reaches zero.
+async fn store_collection_quantity(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    collection_id: i64,
+    printing_id: i64,
+    quantity: i64,
+) -> DbResult<()> {
+    if quantity <= 0 {
+        sqlx::query("DELETE FROM collection_items WHERE collection_id = ? AND printing_id = ?")
+            .bind(collection_id)
+            .bind(printing_id)
+            .execute(&mut **tx)
+            .await?;
+    } else {
+        sqlx::query(
+            "INSERT INTO collection_items (collection_id, printing_id, quantity) VALUES (?, ?, ?)
+             ON CONFLICT(collection_id, printing_id) DO UPDATE SET quantity = excluded.quantity",
+        )
+        .bind(collection_id)
+        .bind(printing_id)
+        .bind(quantity)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Adds `delta` copies of a printing to a collection (or removes them when negative).
+///
+/// The quantity never drops below zero. Returns the new owned quantity, or
+/// `DbError::QuantityOverflow` if it would not fit in an `i64`.
+pub async fn adjust_collection_quantity(
+    pool: &Pool,
+    collection_id: i64,
+    printing_id: i64,
+    delta: i64,
+) -> DbResult<i64> {
+    let mut tx = pool.begin().await?;
+    ensure_collection_exists(&mut tx, collection_id).await?;
+    ensure_printing_exists(&mut tx, printing_id).await?;
+
+    let current: Option<i64> = sqlx::query_scalar(
+        "SELECT quantity FROM collection_items WHERE collection_id = ? AND printing_id = ?",
+    )
+    .bind(collection_id)
+    .bind(printing_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let quantity = current
+        .unwrap_or(0)
+        .checked_add(delta)
+        .ok_or(DbError::QuantityOverflow(printing_id))?
+        .max(0);
+
+    store_collection_quantity(&mut tx, collection_id, printing_id, quantity).await?;
+    tx.commit().await?;
+    Ok(quantity)
+}
+
+/// Sets the owned quantities of many printings at once within a single transaction.
+/// A quantity of zero removes the printing from the collection.
+pub async fn set_collection_quantities(
+    pool: &Pool,
+    collection_id: i64,
+    items: &[CollectionQuantity],
+) -> DbResult<()> {
+    let mut tx = pool.begin().await?;
+    ensure_collection_exists(&mut tx, collection_id).await?;
+
+    for item in items {
+        ensure_printing_exists(&mut tx, item.printing_id).await?;
+        store_collection_quantity(&mut tx, collection_id, item.printing_id, item.quantity).await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Fetches every printing owned in a collection, joined to its full card data.
+pub async fn fetch_collection_entries(
+    pool: &Pool,
+    collection_id: i64,
+) -> Result<Vec<CollectionEntry>, sqlx::Error> {
+    let rows: Vec<(i64, i64, i64)> = sqlx::query_as(
+        "SELECT ci.printing_id, ci.quantity, p.card_id FROM collection_items ci
+         JOIN printings p ON p.id = ci.printing_id
+         WHERE ci.collection_id = ?
+         ORDER BY p.card_id, p.id",
+    )
+    .bind(collection_id)
+    .fetch_all(pool)
+    .await?;
+
+    // Several printings may share a card, so only fetch each card once.
+    let mut cards: HashMap<i64, FullCard> = HashMap::new();
+    let mut entries = Vec::with_capacity(rows.len());
+    for (printing_id, quantity, card_id) in rows {
+        let card = match cards.get(&card_id) {
+            Some(card) => card.clone(),
+            None => {
+                let card = fetch_full_card(pool, card_id).await?;
+                cards.insert(card_id, card.clone());
+                card
+            }
+        };
+        entries.push(CollectionEntry {
+            printing_id,
+            quantity,
+            card,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Computes, for every set, how many of its printings a collection owns.
+pub async fn fetch_set_completion(
+    pool: &Pool,
+    collection_id: i64,
+) -> Result<Vec<SetCompletion>, sqlx::Error> {
+    let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(
+        "SELECT s.set_code, s.name, COUNT(ci.printing_id), COUNT(p.id) FROM sets s
+         LEFT JOIN cards c ON c.set_code = s.set_code
+         LEFT JOIN printings p ON p.card_id = c.id
+         LEFT JOIN collection_items ci ON ci.printing_id = p.id AND ci.collection_id = ?
+         WHERE s.deleted_at IS NULL
+         GROUP BY s.id
+         ORDER BY s.id",
+    )
+    .bind(collection_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(set_code, name, owned_printings, total_printings)| SetCompletion {
+                set_code,
+                name,
+                owned_printings,
+                total_printings,
+                percentage: if total_printings == 0 {
+                    0.0
+                } else {
+                    owned_printings as f64 * 100.0 / total_printings as f64
+                },
+            },
+        )
+        .collect())
+}
+
+/// Fetches every printing owned in a collection, with its card name and rarity.
+pub async fn fetch_owned_printings(
+    pool: &Pool,
+    collection_id: i64,
+) -> Result<Vec<OwnedPrinting>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT ci.printing_id, p.card_id, n.name, p.rarity_code, p.rarity_type, ci.quantity
+         FROM collection_items ci
+         JOIN printings p ON p.id = ci.printing_id
+         JOIN cards c ON c.id = p.card_id
+         JOIN names n ON n.id = c.name_id
+         WHERE ci.collection_id = ?
+         ORDER BY ci.printing_id",
+    )
+    .bind(collection_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Fetches the want list of a collection.
+pub async fn fetch_wants(pool: &Pool, collection_id: i64) -> Result<Vec<Want>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, collection_id, printing_id, card_id, quantity FROM collection_wants
+         WHERE collection_id = ?
+         ORDER BY id",
+    )
+    .bind(collection_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Adds a want to a collection's want list and returns it.
+pub async fn add_want(pool: &Pool, collection_id: i64, want: &CreateWant) -> DbResult<Want> {
+    let mut tx = pool.begin().await?;
+    ensure_collection_exists(&mut tx, collection_id).await?;
+    if let Some(printing_id) = want.printing_id {
+        ensure_printing_exists(&mut tx, printing_id).await?;
+    }
+    if let Some(card_id) = want.card_id {
+        let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM cards WHERE id = ?")
+            .bind(card_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if exists.is_none() {
+            return Err(DbError::CardNotFound(card_id));
+        }
+    }
+
+    let id = sqlx::query(
+        "INSERT INTO collection_wants (collection_id, printing_id, card_id, quantity)
+         VALUES (?, ?, ?, ?)",
+    )
+    .bind(collection_id)
+    .bind(want.printing_id)
+    .bind(want.card_id)
+    .bind(want.quantity)
+    .execute(&mut *tx)
+    .await?
+    .last_insert_rowid();
+    tx.commit().await?;
+
+    Ok(Want {
+        id,
+        collection_id,
+        printing_id: want.printing_id,
+        card_id: want.card_id,
+        quantity: want.quantity,
+    })
+}
+
+/// Deletes a want from a collection's want list.
+pub async fn delete_want(
+    pool: &Pool,
+    collection_id: i64,
+    want_id: i64,
+) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
+    sqlx::query("DELETE FROM collection_wants WHERE id = ? AND collection_id = ?")
+        .bind(want_id)
+        .bind(collection_id)
+        .execute(pool)
+        .await
+}
+
+/// Stores a new API key, given the hash of its secret.
+pub async fn insert_api_key(
+    pool: &Pool,
+    name: &str,
+    role: Role,
+    key_prefix: &str,
+    secret_hash: &str,
+) -> Result<ApiKey, sqlx::Error> {
+    let id = sqlx::query(
+        "INSERT INTO api_keys (name, role, key_prefix, secret_hash) VALUES (?, ?, ?, ?)",
+    )
+    .bind(name)
+    .bind(role)
+    .bind(key_prefix)
+    .bind(secret_hash)
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    sqlx::query_as::<_, ApiKey>(
+        "SELECT id, name, role, key_prefix, created_at, revoked_at FROM api_keys WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Fetches every API key, revoked ones included.
+pub async fn fetch_all_api_keys(pool: &Pool) -> Result<Vec<ApiKey>, sqlx::Error> {
+    sqlx::query_as::<_, ApiKey>(
+        "SELECT id, name, role, key_prefix, created_at, revoked_at FROM api_keys ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Fetches an unrevoked API key by its prefix, along with the hash of its secret.
+pub async fn fetch_active_api_key(
+    pool: &Pool,
+    key_prefix: &str,
+) -> Result<Option<(ApiKey, String)>, sqlx::Error> {
+    let row: Option<(i64, String, Role, String, String, String)> = sqlx::query_as(
+        "SELECT id, name, role, key_prefix, created_at, secret_hash FROM api_keys
+         WHERE key_prefix = ? AND revoked_at IS NULL",
+    )
+    .bind(key_prefix)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(
+        row.map(|(id, name, role, key_prefix, created_at, secret_hash)| {
+            (
+                ApiKey {
+                    id,
+                    name,
+                    role,
+                    key_prefix,
+                    created_at,
+                    revoked_at: None,
+                },
+                secret_hash,
+            )
+        }),
+    )
+}
+
+/// Revokes an API key. Revoked keys are kept, so that their names stay on record.
+pub async fn revoke_api_key(
+    pool: &Pool,
+    id: i64,
+) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
+    sqlx::query(
+        "UPDATE api_keys SET revoked_at = CURRENT_TIMESTAMP WHERE id = ? AND revoked_at IS NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+}
+
+/// Records a change in the audit log, as part of the transaction making it.
+///
+/// `before` and `after` are JSON snapshots of the entity.
+pub async fn record_audit(
+    conn: &mut sqlx::SqliteConnection,
+    actor: &Actor,
+    action: AuditAction,
+    entity_kind: EntityKind,
+    entity_key: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_log (actor, api_key_id, action, entity_kind, entity_key, before, after)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&actor.name)
+    .bind(actor.api_key_id)
+    .bind(action)
+    .bind(entity_kind)
+    .bind(entity_key)
+    .bind(before)
+    .bind(after)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+async fn record_creation(
+    conn: &mut sqlx::SqliteConnection,
+    actor: &Actor,
+    entity_kind: EntityKind,
+    entity_key: &str,
+    after: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    record_audit(
+        conn,
+        actor,
+        AuditAction::Create,
+        entity_kind,
+        entity_key,
+        None,
+        Some(&after.to_string()),
+    )
+    .await
+}
+
+/// Deletes a row of reference data by its key, recording its last state in the audit log.
+///
+/// `snapshot_sql` selects the row as a JSON object, and both queries bind the key once.
+/// `delete_sql` may also be an `UPDATE` that moves the row to the trash.
+async fn delete_audited(
+    pool: &Pool,
+    actor: &Actor,
+    entity_kind: EntityKind,
+    entity_key: &str,
+    snapshot_sql: &str,
+    delete_sql: &str,
+) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let result = delete_audited_with_tx(
+        &mut tx,
+        actor,
+        entity_kind,
+        entity_key,
+        snapshot_sql,
+        delete_sql,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(result)
+}
+
+/// Helper for [`delete_audited`] within an existing transaction.
+async fn delete_audited_with_tx(
+    conn: &mut sqlx::SqliteConnection,
+    actor: &Actor,
+    entity_kind: EntityKind,
+    entity_key: &str,
+    snapshot_sql: &str,
+    delete_sql: &str,
+) -> Result<sqlx::sqlite::SqliteQueryResult, sqlx::Error> {
+    let before: Option<String> = sqlx::query_scalar(snapshot_sql)
+        .bind(entity_key)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let result = sqlx::query(delete_sql)
+        .bind(entity_key)
+        .execute(&mut *conn)
+        .await?;
+    if let Some(before) = before {
+        record_audit(
+            conn,
+            actor,
+            AuditAction::Delete,
+            entity_kind,
+            entity_key,
+            Some(&before),
+            None,
+        )
+        .await?;
+    }
+    Ok(result)
+}
+
+/// Describes a set, group, unit or rarity in error messages.
+fn entity_label(kind: EntityKind, key: &str) -> String {
+    let kind = match kind {
+        EntityKind::Card => "Card",
+        EntityKind::Set => "Set",
+        EntityKind::Group => "Group",
+        EntityKind::Unit => "Unit",
+        EntityKind::Rarity => "Rarity",
+        EntityKind::NameVariant => "Name variant",
+        EntityKind::GroupVariant => "Group variant",
+        EntityKind::Translation => "Translation",
+    };
+    format!("{kind} '{key}'")
+}
+
+/// Fetches the cards that depend on a set, group, unit or rarity, as their IDs and
+/// identifiers. Rarities are used by printings, which are identified with their rarity.
+async fn fetch_dependents(
+    executor: impl sqlx::SqliteExecutor<'_>,
+    kind: EntityKind,
+    key: &str,
+) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    const IDENTIFIER: &str = "c.series_code || '-' || c.set_code || '-' || c.number_in_set";
+    let sql = match kind {
+        EntityKind::Set => {
+            format!("SELECT c.id, {IDENTIFIER} FROM cards c WHERE c.set_code = ? ORDER BY c.id")
+        }
+        EntityKind::Group => format!(
+            "SELECT c.id, {IDENTIFIER} FROM cards c
+             JOIN card_groups cg ON cg.card_id = c.id
+             JOIN groups g ON g.id = cg.group_id
+             WHERE g.name = ? ORDER BY c.id"
+        ),
+        EntityKind::Unit => format!(
+            "SELECT c.id, {IDENTIFIER} FROM cards c
+             JOIN card_units cu ON cu.card_id = c.id
+             JOIN units u ON u.id = cu.unit_id
+             WHERE u.name = ? ORDER BY c.id"
+        ),
+        EntityKind::Rarity => format!(
+            "SELECT c.id, {IDENTIFIER} || '-' || p.rarity_code FROM printings p
+             JOIN cards c ON c.id = p.card_id
+             WHERE p.rarity_code = ? ORDER BY p.id"
+        ),
+        EntityKind::Card
+        | EntityKind::NameVariant
+        | EntityKind::GroupVariant
+        | EntityKind::Translation => {
+            return Ok(Vec::new());
+        }
+    };
+    sqlx::query_as(&sql).bind(key).fetch_all(executor).await
+}
+
+/// Removes the printings of a rarity about to be deleted, or reassigns the cards that
+/// depend on reference data to another item. Sets, groups and units only go to the trash,
+/// so `Cascade` is refused for them with `CascadeIntoTrash`. Group reassignments also
+/// move the group variants along, and printings moved to another set or rarity are
+/// refused with `RarityNotInSet` unless the set declares their rarity.
+async fn apply_dependent_action(
+    conn: &mut sqlx::SqliteConnection,
+    kind: EntityKind,
+    key: &str,
+    action: &DependentAction,
+    card_ids: &[i64],
+) -> DbResult<()> {
+    let statements: Vec<&str> = match action {
+        DependentAction::Refuse => Vec::new(),
+        DependentAction::Cascade => match kind {
+            EntityKind::Set | EntityKind::Group | EntityKind::Unit => {
+                return Err(DbError::CascadeIntoTrash);
+            }
+            EntityKind::Rarity => vec![
+                "DELETE FROM collection_items
+                 WHERE printing_id IN (SELECT id FROM printings WHERE rarity_code = ?2)",
+                "DELETE FROM collection_wants
+                 WHERE printing_id IN (SELECT id FROM printings WHERE rarity_code = ?2)",
+                "DELETE FROM printings WHERE rarity_code = ?2",
+            ],
+            EntityKind::Card
+            | EntityKind::NameVariant
+            | EntityKind::GroupVariant
+            | EntityKind::Translation => Vec::new(),
+        },
+        DependentAction::ReassignTo(target) => {
+            let exists_sql = match kind {
+                EntityKind::Set => Some(
+                    "SELECT EXISTS(SELECT 1 FROM sets WHERE set_code = ? AND deleted_at IS NULL)",
+                ),
+                EntityKind::Group => Some(
+                    "SELECT EXISTS(SELECT 1 FROM groups WHERE name = ? AND deleted_at IS NULL)",
+                ),
+                EntityKind::Unit => {
+                    Some("SELECT EXISTS(SELECT 1 FROM units WHERE name = ? AND deleted_at IS NULL)")
+                }
+                EntityKind::Rarity => {
+                    Some("SELECT EXISTS(SELECT 1 FROM rarities WHERE rarity_code = ?)")
+                }
+                EntityKind::Card
+                | EntityKind::NameVariant
+                | EntityKind::GroupVariant
+                | EntityKind::Translation => None,
+            };
+            if let Some(exists_sql) = exists_sql {
+                let exists: bool = sqlx::query_scalar(exists_sql)
+                    .bind(target)
+                    .fetch_one(&mut *conn)
+                    .await?;
+                if !exists || target == key {
+                    return Err(DbError::InvalidReassignTarget(target.clone()));
+                }
+            }
+
+            match kind {
+                EntityKind::Set => vec!["UPDATE cards SET set_code = ?1 WHERE set_code = ?2"],
+                EntityKind::Group => vec![
+                    "INSERT OR IGNORE INTO card_groups (card_id, group_id)
+                     SELECT card_id, (SELECT id FROM groups WHERE name = ?1) FROM card_groups
+                     WHERE group_id = (SELECT id FROM groups WHERE name = ?2)",
+                    "DELETE FROM card_groups WHERE group_id = (SELECT id FROM groups WHERE name = ?2)",
+                    "UPDATE group_variants SET canonical_name = ?1 WHERE canonical_name = ?2",
+                ],
+                EntityKind::Unit => vec![
+                    "INSERT OR IGNORE INTO card_units (card_id, unit_id)
+                     SELECT card_id, (SELECT id FROM units WHERE name = ?1) FROM card_units
+                     WHERE unit_id = (SELECT id FROM units WHERE name = ?2)",
+                    "DELETE FROM card_units WHERE unit_id = (SELECT id FROM units WHERE name = ?2)",
+                ],
+                EntityKind::Rarity => vec![
+                    "UPDATE printings SET rarity_code = ?1,
+                         rarity_type = (SELECT rarity_type FROM rarities WHERE rarity_code = ?1)
+                     WHERE rarity_code = ?2",
+                ],
+                EntityKind::Card
+                | EntityKind::NameVariant
+                | EntityKind::GroupVariant
+                | EntityKind::Translation => Vec::new(),
+            }
+        }
+    };
+
+    let target = match action {
+        DependentAction::ReassignTo(target) => Some(target.as_str()),
+        _ => None,
+    };
+    for sql in statements {
+        sqlx::query(sql)
+            .bind(target)
+            .bind(key)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    // Printings moved to another set or rarity must have a rarity their set declares.
+    let moved_rarity = match (kind, target) {
+        (EntityKind::Set, Some(_)) => None,
+        (EntityKind::Rarity, Some(target)) => Some(target),
+        _ => return Ok(()),
+    };
+    for card_id in card_ids {
+        let printings: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT cards.set_code, printings.rarity_code
+             FROM printings JOIN cards ON cards.id = printings.card_id
+             WHERE cards.id = ?1 AND (?2 IS NULL OR printings.rarity_code = ?2)",
+        )
+        .bind(card_id)
+        .bind(moved_rarity)
+        .fetch_all(&mut *conn)
+        .await?;
+        for (set_code, rarity_code) in printings {
+            check_rarity_in_set(conn, &set_code, &rarity_code).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Deletes a set, group, unit or rarity like [`delete_audited`], first handling the cards
+/// that depend on it as `action` says.
+///
+/// With [`DependentAction::Refuse`], nothing is deleted while cards depend on it. The
+/// changes to dependent cards are recorded in their history.
+async fn delete_referenced(
+    pool: &Pool,
+    actor: &Actor,
+    kind: EntityKind,
+    key: &str,
+    action: &DependentAction,
+    snapshot_sql: &str,
+    delete_sql: &str,
+) -> DbResult<sqlx::sqlite::SqliteQueryResult> {
+    let mut tx = pool.begin().await?;
+    let exists: Option<String> = sqlx::query_scalar(snapshot_sql)
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if exists.is_none() {
+        return Ok(sqlx::sqlite::SqliteQueryResult::default());
+    }
+
+    let dependents = fetch_dependents(&mut *tx, kind, key).await?;
+    if dependents.is_empty() {
+        // Without cards the action still checks its target and moves group variants.
+        apply_dependent_action(&mut tx, kind, key, action, &[]).await?;
+        let result =
+            delete_audited_with_tx(&mut tx, actor, kind, key, snapshot_sql, delete_sql).await?;
+        tx.commit().await?;
+        return Ok(result);
+    }
+    if *action == DependentAction::Refuse {
+        return Err(DbError::HasDependents {
+            entity: entity_label(kind, key),
+            cards: dependents
+                .into_iter()
+                .map(|(_, identifier)| identifier)
+                .collect(),
+        });
+    }
+
+    let mut card_ids: Vec<i64> = dependents.iter().map(|(card_id, _)| *card_id).collect();
+    card_ids.sort_unstable();
+    card_ids.dedup();
+    let mut before = Vec::with_capacity(card_ids.len());
+    for card_id in &card_ids {
+        before.push(fetch_full_card_with_tx(&mut tx, *card_id).await?);
+    }
+
+    apply_dependent_action(&mut tx, kind, key, action, &card_ids).await?;
+    let result =
+        delete_audited_with_tx(&mut tx, actor, kind, key, snapshot_sql, delete_sql).await?;
+
+    let mut after = Vec::with_capacity(card_ids.len());
+    for card_id in &card_ids {
+        after.push(fetch_full_card_with_tx(&mut tx, *card_id).await?);
+    }
+    let changes: Vec<_> = before
+        .iter()
+        .zip(&after)
+        .map(|(before, after)| (Some(before), Some(after)))
+        .collect();
+    record_card_changes(&mut tx, actor, AuditAction::Update, &changes).await?;
+    tx.commit().await?;
+    Ok(result)
+}
+
+/// Renames a set, group or unit, updating what refers to it by its key.
+///
+/// Each of `statements` binds the new key as `?1`, the old key as `?2` and the extra
+/// value as `?3`, and the first one updates the row itself. `snapshot_sql` selects the
+/// row as a JSON object and binds its key once. The changes to the cards that depend on
+/// it are recorded in their history.
+async fn rename_referenced(
+    pool: &Pool,
+    actor: &Actor,
+    kind: EntityKind,
+    key: &str,
+    (new_key, extra): (&str, Option<&str>),
+    snapshot_sql: &str,
+    statements: &[&str],
+) -> DbResult<sqlx::sqlite::SqliteQueryResult> {
+    let Some((update_sql, other_sql)) = statements.split_first() else {
+        return Ok(sqlx::sqlite::SqliteQueryResult::default());
+    };
+    let mut tx = pool.begin().await?;
+    let before: Option<String> = sqlx::query_scalar(snapshot_sql)
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some(before) = before else {
+        return Ok(sqlx::sqlite::SqliteQueryResult::default());
+    };
+
+    let mut cards_before = Vec::new();
+    for (card_id, _) in fetch_dependents(&mut *tx, kind, key).await? {
+        cards_before.push(fetch_full_card_with_tx(&mut tx, card_id).await?);
+    }
+
+    let result = sqlx::query(update_sql)
+        .bind(new_key)
+        .bind(key)
+        .bind(extra)
+        .execute(&mut *tx)
+        .await?;
+    for sql in other_sql {
+        sqlx::query(sql)
+            .bind(new_key)
+            .bind(key)
+            .bind(extra)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let after: String = sqlx::query_scalar(snapshot_sql)
+        .bind(new_key)
+        .fetch_one(&mut *tx)
+        .await?;
+    record_audit(
+        &mut tx,
+        actor,
+        AuditAction::Update,
+        kind,
+        key,
+        Some(&before),
+        Some(&after),
+    )
+    .await?;
+
+    let mut cards_after = Vec::with_capacity(cards_before.len());
+    for card in &cards_before {
+        cards_after.push(fetch_full_card_with_tx(&mut tx, card.base.id).await?);
+    }
+    let changes: Vec<_> = cards_before
+        .iter()
+        .zip(&cards_after)
+        .map(|(before, after)| (Some(before), Some(after)))
+        .collect();
+    record_card_changes(&mut tx, actor, AuditAction::Update, &changes).await?;
+    tx.commit().await?;
+    Ok(result)
+}
+
+/// Records changes to cards in the audit log and their revision history, given their
+/// state before and after, as part of the transaction making them.
+///
+/// Cards created before revisions were kept get their state before the change stored
+/// as their first revision.
+async fn record_card_changes(
+    conn: &mut sqlx::SqliteConnection,
+    actor: &Actor,
+    action: AuditAction,
+    changes: &[(Option<&FullCard>, Option<&FullCard>)],
+) -> Result<(), sqlx::Error> {
+    let snapshot = |card: Option<&FullCard>| {
+        card.map(|card| serde_json::to_string(card).expect("cards serialize to JSON"))
+    };
+
+    for (before, after) in changes {
+        let Some(card_id) = before.or(*after).map(|card| card.base.id) else {
+            continue;
+        };
+        let before = snapshot(*before);
+        let after = snapshot(*after);
+        record_audit(
+            &mut *conn,
+            actor,
+            action,
+            EntityKind::Card,
+            &card_id.to_string(),
+            before.as_deref(),
+            after.as_deref(),
+        )
+        .await?;
+
+        let Some(after) = after else {
+            continue;
+        };
+        let latest: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(revision) FROM card_revisions WHERE card_id = ?")
+                .bind(card_id)
+                .fetch_one(&mut *conn)
+                .await?;
+        let mut revision = latest.unwrap_or(0);
+        let mut snapshots = Vec::with_capacity(2);
+        if let (None, Some(before)) = (latest, before) {
+            snapshots.push(before);
+        }
+        snapshots.push(after);
+        for snapshot in snapshots {
+            revision += 1;
+            sqlx::query(
+                "INSERT INTO card_revisions (card_id, revision, actor, snapshot) VALUES (?, ?, ?, ?)",
+            )
+            .bind(card_id)
+            .bind(revision)
+            .bind(&actor.name)
+            .bind(snapshot)
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+type RevisionRow = (i64, i64, String, String, String);
+
+fn revision_from_row(
+    (card_id, revision, created_at, actor, snapshot): RevisionRow,
+) -> Result<CardRevision, sqlx::Error> {
+    Ok(CardRevision {
+        card_id,
+        revision,
+        created_at,
+        actor,
+        card: serde_json::from_str(&snapshot).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+    })
+}
+
+/// Fetches every revision of a card, oldest first.
+///
+/// Returns `CardNotFound` if the card does not exist. Cards that have not been changed
+/// since revisions were first kept have none.
+pub async fn fetch_card_revisions(pool: &Pool, card_id: i64) -> DbResult<Vec<CardRevision>> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM cards WHERE id = ?)")
+        .bind(card_id)
+        .fetch_one(pool)
+        .await?;
+    if !exists {
+        return Err(DbError::CardNotFound(card_id));
+    }
+
+    let rows: Vec<RevisionRow> = sqlx::query_as(
+        "SELECT card_id, revision, created_at, actor, snapshot FROM card_revisions
+         WHERE card_id = ? ORDER BY revision",
+    )
+    .bind(card_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(revision_from_row)
+        .collect::<Result<_, _>>()?)
+}
+
+/// Fetches a single revision of a card, or its latest one if `revision` is `None`.
+pub async fn fetch_card_revision(
+    executor: impl sqlx::SqliteExecutor<'_>,
+    card_id: i64,
+    revision: Option<i64>,
+) -> DbResult<CardRevision> {
+    let row: Option<RevisionRow> = sqlx::query_as(
+        "SELECT card_id, revision, created_at, actor, snapshot FROM card_revisions
+         WHERE card_id = ?1 AND (?2 IS NULL OR revision = ?2)
+         ORDER BY revision DESC LIMIT 1",
+    )
+    .bind(card_id)
+    .bind(revision)
+    .fetch_optional(executor)
+    .await?;
+    match row {
+        Some(row) => Ok(revision_from_row(row)?),
+        None => Err(DbError::RevisionNotFound {
+            card_id,
+            revision: revision.unwrap_or(0),
+        }),
+    }
+}
+
+/// Reapplies an earlier revision of a card, recording the result as a new revision.
+///
+/// Everything about the card is restored except its printings, which keep their IDs:
+/// printings in the revision are updated in place, and printings added since are left
+/// as they are. Perceptual hashes of printings whose image changes are cleared until
+/// the caches are rebuilt.
+///
+/// Like a new card, the restored one must be in a set that is not in the trash, and its
+/// printings must have rarities that exist and that the set declares.
+pub async fn restore_card_revision(
+    pool: &Pool,
+    actor: &Actor,
+    card_id: i64,
+    revision: i64,
+) -> DbResult<FullCard> {
+    let mut tx = pool.begin().await?;
+    let before = match fetch_full_card_with_tx(&mut tx, card_id).await {
+        Ok(card) => card,
+        Err(sqlx::Error::RowNotFound) => return Err(DbError::CardNotFound(card_id)),
+        Err(e) => return Err(e.into()),
+    };
+    let snapshot = fetch_card_revision(&mut *tx, card_id, Some(revision)).await?;
+    let card: FullCard =
+        serde_json::from_value(snapshot.card).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+    let set_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sets WHERE set_code = ? AND deleted_at IS NULL)",
+    )
+    .bind(&card.base.set_code)
+    .fetch_one(&mut *tx)
+    .await?;
+    if !set_exists {
+        return Err(DbError::SetNotFound(card.base.set_code.clone()));
+    }
+    let mut rarity_types = Vec::with_capacity(card.printings.len());
+    for printing in &card.printings {
+        let rarity_type: Option<RarityType> =
+            sqlx::query_scalar("SELECT rarity_type FROM rarities WHERE rarity_code = ?")
+                .bind(&printing.rarity_code)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let rarity_type =
+            rarity_type.ok_or_else(|| DbError::RarityNotFound(printing.rarity_code.clone()))?;
+        check_rarity_in_set(&mut tx, &card.base.set_code, &printing.rarity_code).await?;
+        rarity_types.push(rarity_type);
+    }
+
+    sqlx::query("INSERT INTO names (name) VALUES (?) ON CONFLICT(name) DO NOTHING")
+        .bind(&card.base.name)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "UPDATE cards SET series_code = ?, set_code = ?, number_in_set = ?, card_type = ?,
+             name_id = (SELECT id FROM names WHERE name = ?)
+         WHERE id = ?",
+    )
+    .bind(&card.base.series_code)
+    .bind(&card.base.set_code)
+    .bind(&card.base.number_in_set)
+    .bind(card.base.card_type)
+    .bind(&card.base.name)
+    .bind(card_id)
+    .execute(&mut *tx)
+    .await?;
+
+    for table in [
+        "character_cards",
+        "live_cards",
+        "card_hearts",
+        "card_groups",
+        "card_units",
+        "card_skills",
+    ] {
+        sqlx::query(&format!("DELETE FROM {} WHERE card_id = ?", table))
+            .bind(card_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    insert_card_details(&mut tx, &card).await?;
+
+    for (printing, rarity_type) in card.printings.iter().zip(rarity_types) {
+        sqlx::query(
+            "UPDATE printings SET rarity_code = ?1, rarity_type = ?2,
+                 perceptual_hash = CASE WHEN image_url IS ?3 THEN perceptual_hash END,
+                 image_url = ?3, thumbnail_url = ?4, medium_url = ?5
+             WHERE id = ?6 AND card_id = ?7",
+        )
+        .bind(&printing.rarity_code)
+        .bind(rarity_type)
+        .bind(&printing.image_url)
+        .bind(&printing.thumbnail_url)
+        .bind(&printing.medium_url)
+        .bind(printing.id)
+        .bind(card_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let after = fetch_full_card_with_tx(&mut tx, card_id).await?;
+    record_card_changes(
+        &mut tx,
+        actor,
+        AuditAction::Update,
+        &[(Some(&before), Some(&after))],
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(after)
+}
+
+/// Searches the audit log, newest entries first.
+///
+/// Entries are included from `since` up to, but excluding, `until`.
+pub async fn fetch_audit_log(
+    pool: &Pool,
+    query: &AuditQuery,
+    limit: i64,
+) -> DbResult<Vec<AuditEntry>> {
+    for timestamp in [&query.since, &query.until].into_iter().flatten() {
+        let valid: bool = sqlx::query_scalar("SELECT datetime(?) IS NOT NULL")
+            .bind(timestamp)
+            .fetch_one(pool)
+            .await?;
+        if !valid {
+            return Err(DbError::InvalidTimestamp(timestamp.clone()));
+        }
+    }
+
+    type AuditRow = (
+        i64,
+        String,
+        Option<i64>,
+        String,
+        AuditAction,
+        EntityKind,
+        String,
+        Option<String>,
+        Option<String>,
+    );
+    let rows: Vec<AuditRow> = sqlx::query_as(
+        "SELECT id, actor, api_key_id, created_at, action, entity_kind, entity_key, before, after
+         FROM audit_log
+         WHERE (?1 IS NULL OR entity_kind = ?1)
+           AND (?2 IS NULL OR entity_key = ?2)
+           AND (?3 IS NULL OR actor = ?3)
+           AND (?4 IS NULL OR created_at >= datetime(?4))
+           AND (?5 IS NULL OR created_at < datetime(?5))
+         ORDER BY id DESC
+         LIMIT ?6",
+    )
+    .bind(query.entity_kind)
+    .bind(&query.entity_key)
+    .bind(&query.actor)
+    .bind(&query.since)
+    .bind(&query.until)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let parse = |json: Option<String>| json.and_then(|json| serde_json::from_str(&json).ok());
+    Ok(rows
+        .into_iter()
+        .map(
+            |(
+                id,
+                actor,
+                api_key_id,
+                created_at,
+                action,
+                entity_kind,
+                entity_key,
+                before,
+                after,
+            )| {
+                AuditEntry {
+                    id,
+                    actor,
+                    api_key_id,
+                    created_at,
+                    action,
+                    entity_kind,
+                    entity_key,
+                    before: parse(before),
+                    after: parse(after),
+                }
+            },
+        )
+        .collect())
+}
@@ -0,0 +1,315 @@
+use crate::Pool;
+use crate::db::{self, DbError, DbResult};
+use crate::models::{DatabaseDump, FullCard, PrintingHash, TrashKind};
+
+/// The current version of the [`DatabaseDump`] format, raised whenever tables are added
+/// to it or their fields change. Dumps of other versions are rejected.
+///
+/// Version 2 added image variants, perceptual hashes, set and rarity metadata, set
+/// rarities, translations and the trash.
+pub const DUMP_FORMAT_VERSION: u32 = 2;
+
+/// Parses a JSON [`DatabaseDump`], checking its format version first so that dumps of
+/// another version are reported as unsupported rather than as malformed.
+pub fn parse_dump(bytes: &[u8]) -> DbResult<DatabaseDump> {
+    #[derive(serde::Deserialize)]
+    struct Header {
+        format_version: u32,
+    }
+
+    let header: Header =
+        serde_json::from_slice(bytes).map_err(|e| DbError::InvalidDump(e.to_string()))?;
+    if header.format_version != DUMP_FORMAT_VERSION {
+        return Err(DbError::UnsupportedDumpFormat(header.format_version));
+    }
+    serde_json::from_slice(bytes).map_err(|e| DbError::InvalidDump(e.to_string()))
+}
+
+/// Returns the version of the latest successfully applied sqlx migration.
+pub async fn schema_version(pool: &Pool) -> Result<i64, sqlx::Error> {
+    let version: Option<i64> =
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+            .fetch_one(pool)
+            .await?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Serializes the whole card database into a single [`DatabaseDump`].
+///
+/// Only the card catalog and its reference data are included. Collections, want lists,
+/// API keys, the audit log, card revisions and the image files themselves are not.
+///
+/// Sets, groups and units in the trash are included, as they still hold their keys, and
+/// listed again in [`DatabaseDump::trash`] so that they are restored to the trash.
+pub async fn export_database(pool: &Pool) -> Result<DatabaseDump, sqlx::Error> {
+    let card_ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM cards ORDER BY id")
+        .fetch_all(pool)
+        .await?;
+    let mut cards = Vec::with_capacity(card_ids.len());
+    for card_id in card_ids {
+        cards.push(db::fetch_full_card(pool, card_id).await?);
+    }
+
+    Ok(DatabaseDump {
+        format_version: DUMP_FORMAT_VERSION,
+        schema_version: schema_version(pool).await?,
+        sets: sqlx::query_as(
+            "SELECT set_code, name, release_date, product_type, total_cards, language, sort_order
+             FROM sets ORDER BY id",
+        )
+        .fetch_all(pool)
+        .await?,
+        groups: sqlx::query_scalar("SELECT name FROM groups ORDER BY id")
+            .fetch_all(pool)
+            .await?,
+        units: sqlx::query_scalar("SELECT name FROM units ORDER BY id")
+            .fetch_all(pool)
+            .await?,
+        names: sqlx::query_scalar("SELECT name FROM names ORDER BY id")
+            .fetch_all(pool)
+            .await?,
+        rarities: sqlx::query_as(
+            "SELECT rarity_code, rarity_type, display_name, sort_rank, is_secret, description
+             FROM rarities ORDER BY rarity_code",
+        )
+        .fetch_all(pool)
+        .await?,
+        set_rarities: sqlx::query_as(
+            "SELECT set_code, rarity_code FROM set_rarities ORDER BY set_code, rarity_code",
+        )
+        .fetch_all(pool)
+        .await?,
+        name_variants: sqlx::query_as(
+            "SELECT variant_name, canonical_name FROM name_variants ORDER BY variant_name",
+        )
+        .fetch_all(pool)
+        .await?,
+        group_variants: sqlx::query_as(
+            "SELECT variant_name, canonical_name FROM group_variants ORDER BY variant_name",
+        )
+        .fetch_all(pool)
+        .await?,
+        skills: sqlx::query_scalar("SELECT text FROM skills ORDER BY id")
+            .fetch_all(pool)
+            .await?,
+        cards,
+        translations: db::fetch_translations(pool, None, None).await?,
+        perceptual_hashes: db::fetch_perceptual_hashes(pool)
+            .await?
+            .into_iter()
+            .map(|(printing_id, _, perceptual_hash)| PrintingHash {
+                printing_id,
+                perceptual_hash,
+            })
+            .collect(),
+        trash: db::fetch_trash(pool).await?,
+    })
+}
+
+/// Helper to restore a single card, with its original ID and printings, within a transaction.
+async fn import_card(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    card: &FullCard,
+) -> DbResult<()> {
+    sqlx::query("INSERT INTO names (name) VALUES (?) ON CONFLICT(name) DO NOTHING")
+        .bind(&card.base.name)
+        .execute(&mut **tx)
+        .await?;
+    let name_id: i64 = sqlx::query_scalar("SELECT id FROM names WHERE name = ?")
+        .bind(&card.base.name)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO cards (id, series_code, set_code, number_in_set, name_id, card_type)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(card.base.id)
+    .bind(&card.base.series_code)
+    .bind(&card.base.set_code)
+    .bind(&card.base.number_in_set)
+    .bind(name_id)
+    .bind(card.base.card_type)
+    .execute(&mut **tx)
+    .await?;
+
+    db::insert_card_details(tx, card).await?;
+
+    for printing in &card.printings {
+        sqlx::query(
+            "INSERT INTO printings
+                (id, card_id, rarity_code, rarity_type, image_url, thumbnail_url, medium_url)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(printing.id)
+        .bind(card.base.id)
+        .bind(&printing.rarity_code)
+        .bind(printing.rarity_type)
+        .bind(&printing.image_url)
+        .bind(&printing.thumbnail_url)
+        .bind(&printing.medium_url)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Restores a [`DatabaseDump`] into a database that holds no cards yet.
+///
+/// The dump must have been taken at the same schema version as the target database.
+/// Reference data seeded by the migrations is replaced by the contents of the dump,
+/// and everything is restored in dependency order within a single transaction.
+pub async fn import_database(pool: &Pool, dump: &DatabaseDump) -> DbResult<()> {
+    if dump.format_version != DUMP_FORMAT_VERSION {
+        return Err(DbError::UnsupportedDumpFormat(dump.format_version));
+    }
+    let expected = schema_version(pool).await?;
+    if dump.schema_version != expected {
+        return Err(DbError::SchemaVersionMismatch {
+            expected,
+            found: dump.schema_version,
+        });
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let card_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cards")
+        .fetch_one(&mut *tx)
+        .await?;
+    if card_count > 0 {
+        return Err(DbError::DatabaseNotEmpty(format!(
+            "{} cards already exist",
+            card_count
+        )));
+    }
+
+    // Clear the reference data seeded by the migrations, dependents first.
+    for table in [
+        "name_translations",
+        "group_translations",
+        "unit_translations",
+        "set_translations",
+        "skill_translations",
+        "card_skills",
+        "skills",
+        "group_variants",
+        "name_variants",
+        "set_rarities",
+        "rarities",
+        "names",
+        "units",
+        "groups",
+        "sets",
+    ] {
+        sqlx::query(&format!("DELETE FROM {}", table))
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    for set in &dump.sets {
+        sqlx::query(
+            "INSERT INTO sets
+                 (set_code, name, release_date, product_type, total_cards, language, sort_order)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&set.set_code)
+        .bind(&set.name)
+        .bind(&set.release_date)
+        .bind(set.product_type)
+        .bind(set.total_cards)
+        .bind(&set.language)
+        .bind(set.sort_order)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for group in &dump.groups {
+        sqlx::query("INSERT INTO groups (name) VALUES (?)")
+            .bind(group)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for unit in &dump.units {
+        sqlx::query("INSERT INTO units (name) VALUES (?)")
+            .bind(unit)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for item in &dump.trash {
+        let statement = match item.kind {
+            TrashKind::Set => "UPDATE sets SET deleted_at = ? WHERE set_code = ?",
+            TrashKind::Group => "UPDATE groups SET deleted_at = ? WHERE name = ?",
+            TrashKind::Unit => "UPDATE units SET deleted_at = ? WHERE name = ?",
+        };
+        sqlx::query(statement)
+            .bind(&item.deleted_at)
+            .bind(&item.key)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for name in &dump.names {
+        sqlx::query("INSERT INTO names (name) VALUES (?)")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for rarity in &dump.rarities {
+        sqlx::query(
+            "INSERT INTO rarities
+                 (rarity_code, rarity_type, display_name, sort_rank, is_secret, description)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&rarity.rarity_code)
+        .bind(rarity.rarity_type)
+        .bind(&rarity.display_name)
+        .bind(rarity.sort_rank)
+        .bind(rarity.is_secret)
+        .bind(&rarity.description)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for set_rarity in &dump.set_rarities {
+        sqlx::query("INSERT INTO set_rarities (set_code, rarity_code) VALUES (?, ?)")
+            .bind(&set_rarity.set_code)
+            .bind(&set_rarity.rarity_code)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for variant in &dump.name_variants {
+        sqlx::query("INSERT INTO name_variants (variant_name, canonical_name) VALUES (?, ?)")
+            .bind(&variant.variant_name)
+            .bind(&variant.canonical_name)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for variant in &dump.group_variants {
+        sqlx::query("INSERT INTO group_variants (variant_name, canonical_name) VALUES (?, ?)")
+            .bind(&variant.variant_name)
+            .bind(&variant.canonical_name)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for skill in &dump.skills {
+        sqlx::query("INSERT INTO skills (text) VALUES (?)")
+            .bind(skill)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for card in &dump.cards {
+        import_card(&mut tx, card).await?;
+    }
+    for translation in &dump.translations {
+        db::insert_translation(&mut tx, translation).await?;
+    }
+    for hash in &dump.perceptual_hashes {
+        // SQLite integers are signed, so the hash is stored with its bits reinterpreted.
+        sqlx::query("UPDATE printings SET perceptual_hash = ? WHERE id = ?")
+            .bind(hash.perceptual_hash as i64)
+            .bind(hash.printing_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use crate::{
+    AppState, auth, db,
+    models::{ApiKey, CreateApiKey, IssuedApiKey},
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+
+/// API handler to list every API key, revoked ones included. Requires the `admin` role.
+pub async fn get_all(
+    State(state): AppState,
+    _: auth::Admin,
+) -> Result<Json<Vec<ApiKey>>, (StatusCode, String)> {
+    db::fetch_all_api_keys(&state.pool)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// API handler to issue a new API key. Requires the `admin` role.
+///
+/// # Returns
+/// - `201 Created` with the key and its token, which is not shown again. Returns: [`IssuedApiKey`].
+pub async fn issue(
+    State(state): AppState,
+    _: auth::Admin,
+    Json(payload): Json<CreateApiKey>,
+) -> Result<(StatusCode, Json<IssuedApiKey>), (StatusCode, String)> {
+    let (key, token) = auth::issue_key(&state.pool, &payload.name, payload.role)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((StatusCode::CREATED, Json(IssuedApiKey { key, token })))
+}
+
+/// API handler to revoke an API key. Requires the `admin` role.
+///
+/// # Returns
+/// - `204 No Content` once the key can no longer be used.
+/// - `404 Not Found` if no unrevoked key has this ID.
+pub async fn revoke(
+    State(state): AppState,
+    _: auth::Admin,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let result = db::revoke_api_key(&state.pool, id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, format!("API key not found: {}", id)));
+    }
+    // Requests with the key are counted against their address again at once.
+    state.rate_limiter.forget_keys();
+    Ok(StatusCode::NO_CONTENT)
+}
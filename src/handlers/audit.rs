@@ -0,0 +1,36 @@
+use crate::{
+    AppState, auth,
+    db::{self, DbError},
+    models::{AuditEntry, AuditQuery},
+};
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+
+/// The default and maximum number of audit log entries returned at once.
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+const MAX_AUDIT_LIMIT: i64 = 1000;
+
+/// API handler to search the audit log by entity, actor and time range. Requires a key
+/// of any role.
+///
+/// # Returns
+/// - `200 OK` with the matching entries, newest first. Returns: `Vec<AuditEntry>`.
+/// - `400 Bad Request` if `since` or `until` is not a valid timestamp.
+pub async fn get_log(
+    State(state): AppState,
+    _: auth::Reader,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, (StatusCode, String)> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_LIMIT)
+        .clamp(1, MAX_AUDIT_LIMIT);
+    match db::fetch_audit_log(&state.pool, &query, limit).await {
+        Ok(entries) => Ok(Json(entries)),
+        Err(e @ DbError::InvalidTimestamp(_)) => Err((StatusCode::BAD_REQUEST, e.to_string())),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
@@ -1,30 +1,51 @@
 use crate::{
-    AppState,
+    AppState, auth, csv_import,
     db::{self, DbError},
-    models::{CreateCard, FullCard},
+    html_import,
+    localization::{self, Languages},
+    models::{
+        CardExportRow, CreateCard, CsvImportRequest, FullCard, HtmlImportRequest,
+        HtmlImportResponse, ImportRowError,
+    },
 };
 use axum::{
-    Json as AxumJson,
+    BoxError, Json as AxumJson,
+    body::Body,
     extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    http::{StatusCode, header},
+    response::{IntoResponse, Json, Response},
 };
+use futures::{StreamExt, stream};
+use std::collections::HashMap;
+
+/// The maximum size of a batch of saved pages accepted by the HTML import endpoint.
+pub const MAX_HTML_IMPORT_SIZE: usize = 64 * 1024 * 1024;
 
 /// API handler to get a single card by its ID.
+///
+/// The card is localized into the language of `?lang=` or the `Accept-Language` header,
+/// keeping the original text of anything not translated.
 pub async fn get_by_id(
     State(state): AppState,
     Path(id): Path<i64>,
+    Languages(languages): Languages,
 ) -> Result<Json<FullCard>, (StatusCode, String)> {
     match db::fetch_full_card(&state.pool, id).await {
-        Ok(card) => Ok(Json(card)),
+        Ok(mut card) => {
+            localization::localize_card(&state.pool, &mut card, &languages)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            Ok(Json(card))
+        }
         Err(sqlx::Error::RowNotFound) => Err((StatusCode::NOT_FOUND, "Card not found".to_string())),
         Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
     }
 }
 
-/// API handler to create a new card.
+/// API handler to create a new card. Requires the `editor` role.
 pub async fn create(
     State(state): AppState,
+    editor: auth::Editor,
     AxumJson(payload): AxumJson<CreateCard>,
 ) -> Result<(StatusCode, Json<FullCard>), (StatusCode, String)> {
     let rarity_cache = state.rarity_cache.read().await;
@@ -33,6 +54,7 @@ pub async fn create(
 
     match db::create_full_card(
         &state.pool,
+        &editor.actor(),
         &rarity_cache,
         &name_variant_cache,
         &group_variant_cache,
@@ -50,13 +72,19 @@ pub async fn create(
             // For missing entities, return a 400 Bad Request.
             Err((StatusCode::BAD_REQUEST, name))
         }
+        Err(
+            e @ (DbError::SetNotFound(_)
+            | DbError::RarityNotFound(_)
+            | DbError::RarityNotInSet { .. }),
+        ) => Err((StatusCode::BAD_REQUEST, e.to_string())),
         Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
     }
 }
 
-/// API handler to create multiple new cards in a single request.
+/// API handler to create multiple new cards in a single request. Requires the `editor` role.
 pub async fn create_bulk(
     State(state): AppState,
+    editor: auth::Editor,
     AxumJson(payload): AxumJson<Vec<CreateCard>>,
 ) -> Result<(StatusCode, Json<Vec<FullCard>>), (StatusCode, String)> {
     let rarity_cache = state.rarity_cache.read().await;
@@ -65,6 +93,7 @@ pub async fn create_bulk(
 
     match db::create_bulk_cards(
         &state.pool,
+        &editor.actor(),
         &rarity_cache,
         &name_variant_cache,
         &group_variant_cache,
@@ -82,11 +111,243 @@ pub async fn create_bulk(
             // For missing entities, return a 400 Bad Request.
             Err((StatusCode::BAD_REQUEST, name))
         }
+        Err(
+            e @ (DbError::SetNotFound(_)
+            | DbError::RarityNotFound(_)
+            | DbError::RarityNotInSet { .. }),
+        ) => Err((StatusCode::BAD_REQUEST, e.to_string())),
         Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
     }
 }
 
+/// API handler to import cards from a CSV sheet. Requires the `editor` role.
+///
+/// The rows are parsed with the column mapping from the payload and then created
+/// through the same path as `POST /cards/bulk`, in a single transaction.
+///
+/// # Returns
+/// - `201 Created` with the created cards.
+/// - `400 Bad Request` with a JSON array of per-row errors if any row is invalid.
+pub async fn import_csv(
+    State(state): AppState,
+    editor: auth::Editor,
+    AxumJson(payload): AxumJson<CsvImportRequest>,
+) -> Result<(StatusCode, Json<Vec<FullCard>>), Response> {
+    let cards = csv_import::parse_cards(&payload.csv, &payload.mapping)
+        .map_err(|errors| (StatusCode::BAD_REQUEST, Json(errors)).into_response())?;
+    let rows: Vec<usize> = (1..=cards.len()).collect();
+    let card_numbers = check_duplicate_card_numbers(&cards, &rows)
+        .map_err(|errors| (StatusCode::CONFLICT, Json(errors)).into_response())?;
+
+    let rarity_cache = state.rarity_cache.read().await;
+    let name_variant_cache = state.name_variant_cache.read().await;
+    let group_variant_cache = state.group_variant_cache.read().await;
+
+    match db::create_bulk_cards_indexed(
+        &state.pool,
+        &editor.actor(),
+        &rarity_cache,
+        &name_variant_cache,
+        &group_variant_cache,
+        cards,
+    )
+    .await
+    {
+        Ok(cards) => {
+            // Invalidate and refresh names cache
+            let mut names_cache = state.names_cache.write().await;
+            *names_cache = db::fetch_all_card_names(&state.pool)
+                .await
+                .unwrap_or_default();
+            Ok((StatusCode::CREATED, Json(cards)))
+        }
+        Err((Some(index), e)) => Err(import_row_error(index + 1, &card_numbers[index], e)),
+        Err((None, e)) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    }
+}
+
+/// Returns the card number of each card of an import, e.g. `PL!-BP01-001`, refusing
+/// imports that list a card number more than once with each repeat and the row it repeats.
+fn check_duplicate_card_numbers(
+    cards: &[CreateCard],
+    rows: &[usize],
+) -> Result<Vec<String>, Vec<ImportRowError>> {
+    let card_numbers: Vec<String> = cards
+        .iter()
+        .map(|card| {
+            format!(
+                "{}-{}-{}",
+                card.series_code, card.set_code, card.number_in_set
+            )
+        })
+        .collect();
+
+    let mut first_rows: HashMap<&str, usize> = HashMap::new();
+    let mut errors = Vec::new();
+    for (card_number, &row) in card_numbers.iter().zip(rows) {
+        match first_rows.get(card_number.as_str()) {
+            Some(first_row) => errors.push(ImportRowError {
+                row,
+                message: format!(
+                    "Card number {} is already listed at row {}.",
+                    card_number, first_row
+                ),
+            }),
+            None => {
+                first_rows.insert(card_number, row);
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(card_numbers)
+}
+
+/// Maps an error creating the card at a row of an import to a response.
+fn import_row_error(row: usize, card_number: &str, e: DbError) -> Response {
+    let (status, message) = match e {
+        DbError::GroupNotFound(_)
+        | DbError::UnitNotFound(_)
+        | DbError::SetNotFound(_)
+        | DbError::RarityNotFound(_)
+        | DbError::RarityNotInSet { .. } => (StatusCode::BAD_REQUEST, e.to_string()),
+        DbError::Sqlx(sqlx::Error::Database(ref db_err)) if db_err.is_unique_violation() => (
+            StatusCode::CONFLICT,
+            format!("Card number {} already exists.", card_number),
+        ),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    (status, Json(vec![ImportRowError { row, message }])).into_response()
+}
+
+/// API handler to import saved card detail pages of the official card list. Requires the `editor` role.
+///
+/// Every page is compared against the card stored under the same number. New cards are
+/// created through the bulk path unless `dry_run` is set; cards that already exist are
+/// only reported, with the fields that differ, and left untouched.
+pub async fn import_html(
+    State(state): AppState,
+    editor: auth::Editor,
+    AxumJson(payload): AxumJson<HtmlImportRequest>,
+) -> Result<(StatusCode, Json<HtmlImportResponse>), Response> {
+    let cards = html_import::parse_pages(&payload.pages)
+        .map_err(|errors| (StatusCode::BAD_REQUEST, Json(errors)).into_response())?;
+
+    let rarity_cache = state.rarity_cache.read().await;
+    let name_variant_cache = state.name_variant_cache.read().await;
+    let group_variant_cache = state.group_variant_cache.read().await;
+
+    let html_import::ImportPlan {
+        mut diff,
+        new_cards,
+        new_rows,
+    } = html_import::plan_import(
+        &state.pool,
+        &name_variant_cache,
+        &group_variant_cache,
+        cards,
+    )
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?;
+
+    let card_numbers = check_duplicate_card_numbers(&new_cards, &new_rows)
+        .map_err(|errors| (StatusCode::CONFLICT, Json(errors)).into_response())?;
+
+    if payload.dry_run || new_cards.is_empty() {
+        return Ok((
+            StatusCode::OK,
+            Json(HtmlImportResponse {
+                diff,
+                created: Vec::new(),
+            }),
+        ));
+    }
+
+    match db::create_bulk_cards_indexed(
+        &state.pool,
+        &editor.actor(),
+        &rarity_cache,
+        &name_variant_cache,
+        &group_variant_cache,
+        new_cards,
+    )
+    .await
+    {
+        Ok(created) => {
+            // Invalidate and refresh names cache
+            let mut names_cache = state.names_cache.write().await;
+            *names_cache = db::fetch_all_card_names(&state.pool)
+                .await
+                .unwrap_or_default();
+            for (card, row) in created.iter().zip(&new_rows) {
+                diff[row - 1].card_id = Some(card.base.id);
+            }
+            Ok((
+                StatusCode::CREATED,
+                Json(HtmlImportResponse { diff, created }),
+            ))
+        }
+        Err((Some(index), e)) => Err(import_row_error(new_rows[index], &card_numbers[index], e)),
+        Err((None, e)) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    }
+}
+
 /// API handler to get all cards (not yet implemented).
 pub async fn get_all() -> StatusCode {
     StatusCode::NOT_IMPLEMENTED
 }
+
+/// Serializes a single export row as a CSV record, optionally preceded by the header.
+fn csv_record(row: &CardExportRow, with_header: bool) -> Result<Vec<u8>, BoxError> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(with_header)
+        .from_writer(Vec::new());
+    writer.serialize(row)?;
+    Ok(writer.into_inner().map_err(|e| e.into_error())?)
+}
+
+/// Builds the CSV header line from the field names of [`CardExportRow`].
+fn csv_header() -> Result<Vec<u8>, BoxError> {
+    let mut bytes = csv_record(&CardExportRow::default(), true)?;
+    // The default row is all empty fields, so the header ends at the first line break.
+    let end = bytes
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |i| i + 1);
+    bytes.truncate(end);
+    Ok(bytes)
+}
+
+/// API handler to stream the card catalog as CSV, one row per printing.
+pub async fn export_csv(State(state): AppState) -> Response {
+    let rows = db::stream_card_export_rows(state.pool.clone()).map(|row| csv_record(&row?, false));
+    let body = Body::from_stream(stream::once(async { csv_header() }).chain(rows));
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"cards.csv\"",
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// API handler to stream the card catalog as JSON Lines, one object per printing.
+pub async fn export_ndjson(State(state): AppState) -> Response {
+    let rows = db::stream_card_export_rows(state.pool.clone()).map(|row| -> Result<_, BoxError> {
+        let mut line = serde_json::to_vec(&row?)?;
+        line.push(b'\n');
+        Ok(line)
+    });
+
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(rows),
+    )
+        .into_response()
+}
@@ -0,0 +1,378 @@
+use crate::{
+    AppState, auth,
+    db::{self, DbError},
+    localization::{self, Languages},
+    models::{
+        ApiKey, Collection, CollectionQuantity, CollectionView, CreateCollection, CreateWant, Role,
+        SetCompletion, TradeMatch, TradeMatchQuery, Want,
+    },
+    trades,
+};
+use axum::{
+    Json as AxumJson,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+
+/// Maps errors from collection updates to HTTP responses.
+fn map_collection_error(err: DbError) -> (StatusCode, String) {
+    match err {
+        DbError::CollectionNotFound(id) => (
+            StatusCode::NOT_FOUND,
+            format!("Collection not found: {}", id),
+        ),
+        DbError::PrintingNotFound(id) => (
+            StatusCode::BAD_REQUEST,
+            format!("Printing not found: {}", id),
+        ),
+        DbError::CardNotFound(id) => (StatusCode::BAD_REQUEST, format!("Card not found: {}", id)),
+        e @ DbError::QuantityOverflow(_) => (StatusCode::BAD_REQUEST, e.to_string()),
+        e => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// Handler to get all collections from the database.
+pub async fn get_all(
+    State(state): AppState,
+) -> Result<Json<Vec<Collection>>, (StatusCode, String)> {
+    db::fetch_all_collections(&state.pool)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// API handler to create a new collection. Takes an API key of any role, which alone
+/// may change the collection afterwards.
+pub async fn add(
+    State(state): AppState,
+    auth::Reader(key): auth::Reader,
+    AxumJson(payload): AxumJson<CreateCollection>,
+) -> Result<(StatusCode, Json<Collection>), (StatusCode, String)> {
+    match db::add_collection(&state.pool, &payload.name, payload.owner.as_deref(), key.id).await {
+        Ok(collection) => Ok((StatusCode::CREATED, Json(collection))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )),
+    }
+}
+
+/// API handler to get a collection with every card it contains.
+///
+/// The cards are localized into the language of `?lang=` or the `Accept-Language`
+/// header, like [`crate::handlers::cards::get_by_id`].
+///
+/// # Returns
+/// - `200 OK` with the collection and its cards.
+/// - `404 Not Found` if the collection does not exist.
+pub async fn get_by_id(
+    State(state): AppState,
+    Path(id): Path<i64>,
+    Languages(languages): Languages,
+) -> Result<Json<CollectionView>, (StatusCode, String)> {
+    let collection = match db::fetch_collection(&state.pool, id).await {
+        Ok(collection) => collection,
+        Err(sqlx::Error::RowNotFound) => {
+            return Err((StatusCode::NOT_FOUND, "Collection not found".to_string()));
+        }
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+
+    let mut cards = db::fetch_collection_entries(&state.pool, id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    localization::localize_cards(
+        &state.pool,
+        cards.iter_mut().map(|entry| &mut entry.card),
+        &languages,
+    )
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(CollectionView { collection, cards }))
+}
+
+/// API handler to delete a collection. Requires the key that created it, or the `admin`
+/// role.
+///
+/// # Returns
+/// - `204 No Content` if the collection was deleted.
+/// - `403 Forbidden` if the collection belongs to another key.
+/// - `404 Not Found` if the collection does not exist.
+pub async fn delete(
+    State(state): AppState,
+    auth::Reader(key): auth::Reader,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_own_collection(&state, id, &key).await?;
+    let result = db::delete_collection(&state.pool, id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Collection not found".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler to add copies of a printing to a collection. Requires the key that created
+/// the collection, or the `admin` role.
+///
+/// # Returns
+/// - `200 OK` with the new owned quantity.
+/// - `400 Bad Request` if the quantity is not positive or the new quantity would overflow.
+/// - `403 Forbidden` if the collection belongs to another key.
+pub async fn add_cards(
+    State(state): AppState,
+    auth::Reader(key): auth::Reader,
+    Path(id): Path<i64>,
+    AxumJson(payload): AxumJson<CollectionQuantity>,
+) -> Result<Json<CollectionQuantity>, (StatusCode, String)> {
+    require_own_collection(&state, id, &key).await?;
+    if payload.quantity <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "`quantity` must be positive.".to_string(),
+        ));
+    }
+
+    let quantity =
+        db::adjust_collection_quantity(&state.pool, id, payload.printing_id, payload.quantity)
+            .await
+            .map_err(map_collection_error)?;
+
+    Ok(Json(CollectionQuantity {
+        printing_id: payload.printing_id,
+        quantity,
+    }))
+}
+
+/// API handler to remove copies of a printing from a collection. Requires the key that
+/// created the collection, or the `admin` role.
+///
+/// Removing more copies than are owned leaves a quantity of zero.
+///
+/// # Returns
+/// - `200 OK` with the new owned quantity.
+pub async fn remove_cards(
+    State(state): AppState,
+    auth::Reader(key): auth::Reader,
+    Path(id): Path<i64>,
+    AxumJson(payload): AxumJson<CollectionQuantity>,
+) -> Result<Json<CollectionQuantity>, (StatusCode, String)> {
+    require_own_collection(&state, id, &key).await?;
+    if payload.quantity <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "`quantity` must be positive.".to_string(),
+        ));
+    }
+
+    let quantity =
+        db::adjust_collection_quantity(&state.pool, id, payload.printing_id, -payload.quantity)
+            .await
+            .map_err(map_collection_error)?;
+
+    Ok(Json(CollectionQuantity {
+        printing_id: payload.printing_id,
+        quantity,
+    }))
+}
+
+/// API handler to set the owned quantities of many printings at once. Requires the key
+/// that created the collection, or the `admin` role.
+///
+/// A quantity of zero removes the printing from the collection.
+pub async fn set_quantities(
+    State(state): AppState,
+    auth::Reader(key): auth::Reader,
+    Path(id): Path<i64>,
+    AxumJson(payload): AxumJson<Vec<CollectionQuantity>>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_own_collection(&state, id, &key).await?;
+    if let Some(item) = payload.iter().find(|item| item.quantity < 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Quantity for printing {} must not be negative.",
+                item.printing_id
+            ),
+        ));
+    }
+
+    db::set_collection_quantities(&state.pool, id, &payload)
+        .await
+        .map_err(map_collection_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler to get the completion percentage of every set for a collection.
+pub async fn completion(
+    State(state): AppState,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<SetCompletion>>, (StatusCode, String)> {
+    match db::fetch_collection(&state.pool, id).await {
+        Ok(_) => {}
+        Err(sqlx::Error::RowNotFound) => {
+            return Err((StatusCode::NOT_FOUND, "Collection not found".to_string()));
+        }
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+
+    db::fetch_set_completion(&state.pool, id)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Helper to look up a collection, reporting a missing one as `404 Not Found`.
+async fn require_collection(
+    state: &crate::ApiState,
+    id: i64,
+) -> Result<Collection, (StatusCode, String)> {
+    match db::fetch_collection(&state.pool, id).await {
+        Ok(collection) => Ok(collection),
+        Err(sqlx::Error::RowNotFound) => Err((
+            StatusCode::NOT_FOUND,
+            format!("Collection not found: {}", id),
+        )),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// Helper to look up a collection that the key of a request may change: one it created,
+/// or any collection for admins. Others are reported as `403 Forbidden`.
+async fn require_own_collection(
+    state: &crate::ApiState,
+    id: i64,
+    key: &ApiKey,
+) -> Result<Collection, (StatusCode, String)> {
+    let collection = require_collection(state, id).await?;
+    if key.role != Role::Admin && collection.api_key_id != Some(key.id) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("Collection {} belongs to another API key.", id),
+        ));
+    }
+    Ok(collection)
+}
+
+/// API handler to get the want list of a collection.
+pub async fn get_wants(
+    State(state): AppState,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Want>>, (StatusCode, String)> {
+    require_collection(&state, id).await?;
+    db::fetch_wants(&state.pool, id)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// API handler to add a printing or base card to a collection's want list. Requires the
+/// key that created the collection, or the `admin` role.
+///
+/// # Returns
+/// - `201 Created` with the new want.
+/// - `400 Bad Request` if the payload does not name exactly one printing or card.
+/// - `403 Forbidden` if the collection belongs to another key.
+/// - `409 Conflict` if the printing or card is already on the want list.
+pub async fn add_want(
+    State(state): AppState,
+    auth::Reader(key): auth::Reader,
+    Path(id): Path<i64>,
+    AxumJson(payload): AxumJson<CreateWant>,
+) -> Result<(StatusCode, Json<Want>), (StatusCode, String)> {
+    require_own_collection(&state, id, &key).await?;
+    if payload.printing_id.is_some() == payload.card_id.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Exactly one of `printing_id` or `card_id` must be set.".to_string(),
+        ));
+    }
+    if payload.quantity <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "`quantity` must be positive.".to_string(),
+        ));
+    }
+
+    match db::add_want(&state.pool, id, &payload).await {
+        Ok(want) => Ok((StatusCode::CREATED, Json(want))),
+        Err(DbError::Sqlx(sqlx::Error::Database(db_err))) if db_err.is_unique_violation() => Err((
+            StatusCode::CONFLICT,
+            "This card is already on the want list.".to_string(),
+        )),
+        Err(e) => Err(map_collection_error(e)),
+    }
+}
+
+/// API handler to remove a want from a collection's want list. Requires the key that
+/// created the collection, or the `admin` role.
+///
+/// # Returns
+/// - `204 No Content` if the want was removed.
+/// - `403 Forbidden` if the collection belongs to another key.
+/// - `404 Not Found` if the collection has no such want.
+pub async fn delete_want(
+    State(state): AppState,
+    auth::Reader(key): auth::Reader,
+    Path((id, want_id)): Path<(i64, i64)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_own_collection(&state, id, &key).await?;
+    let result = db::delete_want(&state.pool, id, want_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Want not found".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler to match the surplus and want lists of two collections.
+///
+/// # Returns
+/// - `200 OK` with the cards each side can give the other.
+/// - `404 Not Found` if either collection does not exist.
+pub async fn matches(
+    State(state): AppState,
+    Path((id, other_id)): Path<(i64, i64)>,
+    Query(query): Query<TradeMatchQuery>,
+) -> Result<Json<TradeMatch>, (StatusCode, String)> {
+    require_collection(&state, id).await?;
+    require_collection(&state, other_id).await?;
+
+    let pool = &state.pool;
+    let load = |collection_id| async move {
+        let owned = db::fetch_owned_printings(pool, collection_id).await?;
+        let wants = db::fetch_wants(pool, collection_id).await?;
+        Ok::<_, sqlx::Error>((owned, wants))
+    };
+    let ((owned, wants), (other_owned, other_wants)) = futures::try_join!(load(id), load(other_id))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let gives = trades::match_trades(
+        &owned,
+        &wants,
+        &other_owned,
+        &other_wants,
+        query.exclude_parallel,
+    );
+    let receives = trades::match_trades(
+        &other_owned,
+        &other_wants,
+        &owned,
+        &wants,
+        query.exclude_parallel,
+    );
+
+    Ok(Json(TradeMatch {
+        collection_id: id,
+        other_collection_id: other_id,
+        gives,
+        receives,
+    }))
+}
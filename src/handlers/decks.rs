@@ -0,0 +1,392 @@
+use crate::{
+    AppState,
+    db::{self, DbError},
+    image_store,
+    models::{
+        CardType, DeckEntry, DeckListCard, DeckListRequest, FullCard, ProbabilityRequest,
+        ProbabilityResponse, ProxySheetRequest, SimulationRequest, SimulationResponse,
+        TtsExportRequest, TtsSavedObject,
+    },
+    probability, proxy, simulation, tabletop,
+};
+use axum::{
+    Json as AxumJson,
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Json, Response},
+};
+use std::collections::HashMap;
+
+/// The maximum number of trials a single simulation request may run.
+const MAX_TRIALS: u32 = 1_000_000;
+
+/// The maximum number of copies of a single card in a deck. Energy cards are exempt.
+const MAX_COPIES: i64 = 4;
+
+/// The maximum size of the main deck, made up of member and Live cards.
+const MAX_MAIN_DECK_SIZE: i64 = 60;
+
+/// The maximum size of the Energy deck.
+const MAX_ENERGY_DECK_SIZE: i64 = 12;
+
+/// The maximum number of turns a probability table may cover.
+const MAX_TURNS: i64 = MAX_MAIN_DECK_SIZE;
+
+/// Loads the cards of a deck list, validating the copy counts and the deck size.
+pub(crate) async fn load_deck(
+    state: &crate::ApiState,
+    deck: &[DeckEntry],
+) -> Result<Vec<(FullCard, i64)>, (StatusCode, String)> {
+    if deck.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Deck must not be empty.".to_string(),
+        ));
+    }
+    if let Some(entry) = deck.iter().find(|entry| entry.count <= 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Card {} must have a positive count.", entry.card_id),
+        ));
+    }
+    // Bound the deck before loading it, since copies are expanded one by one later on.
+    let too_large = (
+        StatusCode::BAD_REQUEST,
+        format!(
+            "Deck must not hold more than {} cards.",
+            MAX_MAIN_DECK_SIZE + MAX_ENERGY_DECK_SIZE
+        ),
+    );
+    let total = deck
+        .iter()
+        .try_fold(0i64, |total, entry| total.checked_add(entry.count))
+        .ok_or_else(|| too_large.clone())?;
+    if total > MAX_MAIN_DECK_SIZE + MAX_ENERGY_DECK_SIZE {
+        return Err(too_large);
+    }
+
+    let cards = match db::fetch_deck_cards(&state.pool, deck).await {
+        Ok(cards) => cards,
+        Err(DbError::CardNotFound(id)) => {
+            return Err((StatusCode::BAD_REQUEST, format!("Card not found: {}", id)));
+        }
+        Err(e @ DbError::TooManyCopies(_)) => {
+            return Err((StatusCode::BAD_REQUEST, e.to_string()));
+        }
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+
+    if let Some((card, _)) = cards
+        .iter()
+        .find(|(card, count)| card.base.card_type != CardType::Energy && *count > MAX_COPIES)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Card {} must not have more than {} copies.",
+                card.base.id, MAX_COPIES
+            ),
+        ));
+    }
+    let (energy, main): (Vec<_>, Vec<_>) = cards
+        .iter()
+        .partition(|(card, _)| card.base.card_type == CardType::Energy);
+    let main_size: i64 = main.iter().map(|(_, count)| count).sum();
+    let energy_size: i64 = energy.iter().map(|(_, count)| count).sum();
+    if main_size > MAX_MAIN_DECK_SIZE || energy_size > MAX_ENERGY_DECK_SIZE {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Deck must not hold more than {} main deck cards and {} Energy cards.",
+                MAX_MAIN_DECK_SIZE, MAX_ENERGY_DECK_SIZE
+            ),
+        ));
+    }
+
+    Ok(cards)
+}
+
+/// Loads a single card, reporting a missing card as a bad request.
+pub(crate) async fn load_card(
+    state: &crate::ApiState,
+    id: i64,
+) -> Result<FullCard, (StatusCode, String)> {
+    match db::fetch_full_card(&state.pool, id).await {
+        Ok(card) => Ok(card),
+        Err(sqlx::Error::RowNotFound) => {
+            Err((StatusCode::BAD_REQUEST, format!("Card not found: {}", id)))
+        }
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// API handler to compute hypergeometric draw probabilities for a deck.
+///
+/// # Returns
+/// - `200 OK` with a per-turn probability table for each condition.
+/// - `400 Bad Request` if the deck, draw sizes or number of turns are invalid.
+pub async fn probability(
+    State(state): AppState,
+    AxumJson(mut payload): AxumJson<ProbabilityRequest>,
+) -> Result<Json<ProbabilityResponse>, (StatusCode, String)> {
+    // Cards are drawn from the main deck only, so the Energy deck is left out.
+    let deck: Vec<_> = load_deck(&state, &payload.deck)
+        .await?
+        .into_iter()
+        .filter(|(card, _)| card.base.card_type != CardType::Energy)
+        .collect();
+    let deck_size: i64 = deck.iter().map(|(_, count)| count).sum();
+
+    if payload.hand_size < 0 || payload.hand_size > deck_size {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Hand size must be between 0 and the deck size ({}).",
+                deck_size
+            ),
+        ));
+    }
+    if !(0..=deck_size).contains(&payload.draws_per_turn) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "`draws_per_turn` must be between 0 and the deck size ({}).",
+                deck_size
+            ),
+        ));
+    }
+    if !(0..=MAX_TURNS).contains(&payload.turns) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("`turns` must be between 0 and {}.", MAX_TURNS),
+        ));
+    }
+
+    // Normalize names so that variants match the canonical names on cards.
+    let name_variant_cache = state.name_variant_cache.read().await;
+    let group_variant_cache = state.group_variant_cache.read().await;
+    for condition in &mut payload.conditions {
+        condition
+            .filter
+            .canonicalize(&name_variant_cache, &group_variant_cache);
+    }
+
+    let conditions = probability::probability_tables(
+        &deck,
+        &payload.conditions,
+        payload.hand_size,
+        payload.draws_per_turn,
+        payload.turns,
+    );
+
+    Ok(Json(ProbabilityResponse {
+        deck_size,
+        conditions,
+    }))
+}
+
+/// API handler to simulate yells and estimate how often a stage clears a Live card.
+///
+/// # Returns
+/// - `200 OK` with the estimated success probability.
+/// - `400 Bad Request` if the stage, deck or Live card are invalid.
+pub async fn simulate(
+    State(state): AppState,
+    AxumJson(payload): AxumJson<SimulationRequest>,
+) -> Result<Json<SimulationResponse>, (StatusCode, String)> {
+    if payload.trials > MAX_TRIALS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("`trials` must not exceed {}.", MAX_TRIALS),
+        ));
+    }
+
+    if payload.stage.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Stage must not be empty.".to_string(),
+        ));
+    }
+
+    let deck = load_deck(&state, &payload.deck).await?;
+    let stage_entries: Vec<DeckEntry> = payload
+        .stage
+        .iter()
+        .map(|&card_id| DeckEntry { card_id, count: 1 })
+        .collect();
+    let stage = load_deck(&state, &stage_entries).await?;
+    if let Some((card, _)) = stage
+        .iter()
+        .find(|(card, _)| card.base.card_type != CardType::Character)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Card {} on stage is not a Character card.", card.base.id),
+        ));
+    }
+
+    let live = load_card(&state, payload.live_card_id).await?;
+    if live.base.card_type != CardType::Live {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Card {} is not a Live card.", live.base.id),
+        ));
+    }
+
+    // The simulation is CPU-bound, so keep it off the async runtime.
+    let result = tokio::task::spawn_blocking(move || {
+        simulation::simulate_live(&stage, &deck, &live, payload.trials, payload.seed)
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(result))
+}
+
+/// Makes the image URLs of a deck's printings absolute, for exports read outside the server.
+///
+/// Stored images are referenced by their path on this server, so without a configured
+/// public URL they stay relative.
+fn resolve_image_urls(state: &crate::ApiState, deck: &mut [(FullCard, i64)]) {
+    let Some(public_base_url) = &state.public_base_url else {
+        return;
+    };
+    for printing in deck
+        .iter_mut()
+        .flat_map(|(card, _)| card.printings.iter_mut())
+    {
+        for url in [
+            &mut printing.image_url,
+            &mut printing.thumbnail_url,
+            &mut printing.medium_url,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *url = image_store::absolute_url(public_base_url, url);
+        }
+    }
+}
+
+/// API handler to export a deck as a Tabletop Simulator saved object.
+///
+/// # Returns
+/// - `200 OK` with the saved object JSON.
+/// - `400 Bad Request` if the deck is invalid, a card has no printing with an image, or
+///   an image is stored locally while `PUBLIC_BASE_URL` is not set.
+pub async fn export_tts(
+    State(state): AppState,
+    AxumJson(payload): AxumJson<TtsExportRequest>,
+) -> Result<Json<TtsSavedObject>, (StatusCode, String)> {
+    if payload.card_back_url.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "`card_back_url` must not be empty.".to_string(),
+        ));
+    }
+
+    let mut deck = load_deck(&state, &payload.deck).await?;
+    if let Some((card, _)) = deck
+        .iter()
+        .find(|(card, _)| tabletop::preferred_printing(card).is_none())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Card {} has no printing with an image.", card.base.id),
+        ));
+    }
+    resolve_image_urls(&state, &mut deck);
+    // Tabletop Simulator loads faces by URL, so they must be reachable from outside.
+    if let Some((card, _)) = deck.iter().find(|(card, _)| {
+        tabletop::preferred_printing(card)
+            .and_then(|printing| printing.image_url.as_deref())
+            .is_some_and(|url| url.starts_with('/'))
+    }) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Card {} has a locally stored image, but `PUBLIC_BASE_URL` is not set.",
+                card.base.id
+            ),
+        ));
+    }
+
+    Ok(Json(tabletop::build_saved_object(
+        &deck,
+        &payload.card_back_url,
+    )))
+}
+
+/// API handler to export a deck as a plain card list for other virtual tabletops.
+pub async fn export_list(
+    State(state): AppState,
+    AxumJson(payload): AxumJson<DeckListRequest>,
+) -> Result<Json<Vec<DeckListCard>>, (StatusCode, String)> {
+    let mut deck = load_deck(&state, &payload.deck).await?;
+    resolve_image_urls(&state, &mut deck);
+    Ok(Json(tabletop::build_card_list(&deck)))
+}
+
+/// API handler to render a deck as a PDF of printable proxies.
+///
+/// Each card is printed from the first of its printings found in the local image store,
+/// or as a text-only proxy if none is.
+///
+/// # Returns
+/// - `200 OK` with the PDF document.
+/// - `400 Bad Request` if the deck is invalid.
+/// - `500 Internal Server Error` if the configured proxy font cannot be used.
+pub async fn export_pdf(
+    State(state): AppState,
+    AxumJson(payload): AxumJson<ProxySheetRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    let deck = load_deck(&state, &payload.deck).await?;
+
+    let mut images = HashMap::new();
+    for (card, _) in &deck {
+        for image_url in card.printings.iter().filter_map(|p| p.image_url.as_deref()) {
+            let Some(path) = image_store::local_path(&state.image_dir, image_url) else {
+                continue;
+            };
+            if let Ok(data) = tokio::fs::read(&path).await {
+                images.insert(card.base.id, data);
+                break;
+            }
+        }
+    }
+
+    let font = match &state.proxy_font {
+        Some(path) => Some(tokio::fs::read(path).await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read proxy font '{}': {}", path.display(), e),
+            )
+        })?),
+        None => None,
+    };
+
+    // Decoding images and writing the PDF is CPU-bound, so keep it off the async runtime.
+    let pdf = tokio::task::spawn_blocking(move || {
+        proxy::render_proxy_sheet(&deck, &images, payload.page_size, font.as_deref())
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Invalid proxy font: {}", e),
+        )
+    })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"proxies.pdf\"",
+            ),
+        ],
+        pdf,
+    )
+        .into_response())
+}
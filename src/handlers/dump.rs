@@ -0,0 +1,46 @@
+use crate::{AppState, auth, db::DbError, dump, models::DatabaseDump};
+use axum::{body::Bytes, extract::State, http::StatusCode, response::Json};
+
+/// The maximum size of a dump accepted by the import endpoint.
+pub const MAX_DUMP_SIZE: usize = 256 * 1024 * 1024;
+
+/// API handler to export the whole database as a versioned JSON dump.
+pub async fn export(State(state): AppState) -> Result<Json<DatabaseDump>, (StatusCode, String)> {
+    dump::export_database(&state.pool)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// API handler to restore a JSON dump into an empty database. Requires the `admin` role.
+///
+/// # Returns
+/// - `201 Created` once the dump is restored and the caches are reloaded.
+/// - `400 Bad Request` if the dump is malformed or its format version is not supported.
+/// - `409 Conflict` if the database already holds cards or was migrated to a different schema version.
+pub async fn import(
+    State(state): AppState,
+    _: auth::Admin,
+    body: Bytes,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let result = match dump::parse_dump(&body) {
+        Ok(payload) => dump::import_database(&state.pool, &payload).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => {}
+        Err(e @ (DbError::UnsupportedDumpFormat(_) | DbError::InvalidDump(_))) => {
+            return Err((StatusCode::BAD_REQUEST, e.to_string()));
+        }
+        Err(e @ (DbError::SchemaVersionMismatch { .. } | DbError::DatabaseNotEmpty(_))) => {
+            return Err((StatusCode::CONFLICT, e.to_string()));
+        }
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+
+    state
+        .reload_caches()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::CREATED)
+}
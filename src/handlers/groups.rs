@@ -1,10 +1,12 @@
 use crate::{
-    AppState, db,
-    models::{CreateGroup},
+    AppState, auth,
+    db::{self, DbError},
+    handlers,
+    models::{CreateGroup, DeleteQuery, DependentAction, RenameGroup, TrashKind},
 };
 use axum::{
     Json as AxumJson,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
 };
@@ -19,22 +21,28 @@ pub async fn get_all(State(state): AppState) -> Json<Vec<String>> {
     Json(cache.clone())
 }
 
-/// API handler to add a new group.
+/// API handler to add a new group. Requires the `editor` role.
 pub async fn add(
     State(state): AppState,
+    editor: auth::Editor,
     AxumJson(payload): AxumJson<CreateGroup>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    match db::add_group(&state.pool, &payload.name).await {
+    match db::add_group(&state.pool, &editor.actor(), &payload.name).await {
         Ok(_) => {
             // Invalidate and refresh cache
             let mut cache = state.groups_cache.write().await;
             *cache = db::fetch_all_groups(&state.pool).await.unwrap_or_default();
             Ok(StatusCode::CREATED)
         }
-        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => Err((
-            StatusCode::CONFLICT,
-            format!("Group with name '{}' already exists.", payload.name),
-        )),
+        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+            Err(handlers::already_exists_response(
+                &state.pool,
+                TrashKind::Group,
+                &payload.name,
+                format!("Group with name '{}' already exists.", payload.name),
+            )
+            .await)
+        }
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database error: {}", e),
@@ -42,19 +50,99 @@ pub async fn add(
     }
 }
 
-/// API handler to delete a group.
+/// API handler to rename a group. Requires the `editor` role.
+///
+/// Group variants of the old name are pointed to the new one, and with
+/// `keep_old_as_variant` the old name itself becomes a variant.
+///
+/// # Returns
+/// - `204 No Content` if the group was renamed.
+/// - `404 Not Found` if no such group exists, or it is in the trash.
+/// - `409 Conflict` if another group already has the new name, or a group in the trash
+///   still holds it.
+pub async fn rename(
+    State(state): AppState,
+    editor: auth::Editor,
+    Path(name): Path<String>,
+    AxumJson(payload): AxumJson<RenameGroup>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let result = match db::rename_group(
+        &state.pool,
+        &editor.actor(),
+        &name,
+        &payload.name,
+        payload.keep_old_as_variant,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(DbError::Sqlx(sqlx::Error::Database(db_err))) if db_err.is_unique_violation() => {
+            return Err(handlers::already_exists_response(
+                &state.pool,
+                TrashKind::Group,
+                &payload.name,
+                format!("Group with name '{}' already exists.", payload.name),
+            )
+            .await);
+        }
+        Err(e) => return Err(handlers::reference_error_response(e)),
+    };
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, format!("Group not found: {}", name)));
+    }
+
+    // Invalidate and refresh caches
+    let mut cache = state.groups_cache.write().await;
+    *cache = db::fetch_all_groups(&state.pool).await.unwrap_or_default();
+    let mut variants = state.group_variant_cache.write().await;
+    *variants = db::fetch_all_group_variants(&state.pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|variant| (variant.variant_name, variant.canonical_name))
+        .collect();
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler to move a group to the trash. Requires the `admin` role.
+///
+/// With `?reassign_to=` its cards are moved to another group, and variants of the group
+/// along with them. Cards cannot be removed from the group along with it, so that it can
+/// be restored from the trash as it was.
+///
+/// # Returns
+/// - `204 No Content` if the group was deleted.
+/// - `400 Bad Request` if the group to reassign to does not exist, or `cascade` is given.
+/// - `404 Not Found` if no such group exists, or it is already in the trash.
+/// - `409 Conflict` if cards belong to the group and neither option is given, listing them.
 pub async fn delete(
     State(state): AppState,
+    admin: auth::Admin,
     Path(name): Path<String>,
+    Query(query): Query<DeleteQuery>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let result = db::delete_group(&state.pool, &name)
+    let action = handlers::dependent_action(query)?;
+    let result = db::delete_group(&state.pool, &admin.actor(), &name, &action)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(handlers::reference_error_response)?;
 
-    if result.rows_affected() > 0 {
-        // Invalidate and refresh cache
-        let mut cache = state.groups_cache.write().await;
-        *cache = db::fetch_all_groups(&state.pool).await.unwrap_or_default();
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, format!("Group not found: {}", name)));
+    }
+
+    // Invalidate and refresh cache
+    let mut cache = state.groups_cache.write().await;
+    *cache = db::fetch_all_groups(&state.pool).await.unwrap_or_default();
+    if let DependentAction::ReassignTo(target) = &action {
+        let mut variants = state.group_variant_cache.write().await;
+        for canonical in variants
+            .values_mut()
+            .filter(|canonical| **canonical == name)
+        {
+            *canonical = target.clone();
+        }
     }
 
     Ok(StatusCode::NO_CONTENT)
@@ -0,0 +1,103 @@
+use crate::{
+    AppState, auth,
+    db::{self, DbError},
+    history,
+    localization::{self, Languages},
+    models::{CardRevision, CardRevisionDiff, FullCard, RevisionDiffQuery},
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+
+fn error_response(e: DbError) -> (StatusCode, String) {
+    match e {
+        DbError::CardNotFound(_) | DbError::RevisionNotFound { .. } => {
+            (StatusCode::NOT_FOUND, e.to_string())
+        }
+        DbError::GroupNotFound(_)
+        | DbError::UnitNotFound(_)
+        | DbError::SetNotFound(_)
+        | DbError::RarityNotFound(_)
+        | DbError::RarityNotInSet { .. } => (StatusCode::CONFLICT, e.to_string()),
+        DbError::Sqlx(sqlx::Error::Database(ref db_err)) if db_err.is_unique_violation() => {
+            (StatusCode::CONFLICT, e.to_string())
+        }
+        e => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// API handler to list the revisions of a card, oldest first.
+///
+/// # Returns
+/// - `200 OK` with every stored snapshot of the card. Returns: `Vec<CardRevision>`.
+/// - `404 Not Found` if the card does not exist.
+pub async fn get_history(
+    State(state): AppState,
+    Path(card_id): Path<i64>,
+) -> Result<Json<Vec<CardRevision>>, (StatusCode, String)> {
+    db::fetch_card_revisions(&state.pool, card_id)
+        .await
+        .map(Json)
+        .map_err(error_response)
+}
+
+/// API handler to compare two revisions of a card, field by field.
+///
+/// # Returns
+/// - `200 OK` with the fields changed from `from` to `to`, or to the latest revision if
+///   `to` is omitted. Returns: `CardRevisionDiff`.
+/// - `404 Not Found` if either revision does not exist.
+pub async fn diff(
+    State(state): AppState,
+    Path(card_id): Path<i64>,
+    Query(query): Query<RevisionDiffQuery>,
+) -> Result<Json<CardRevisionDiff>, (StatusCode, String)> {
+    let from = db::fetch_card_revision(&state.pool, card_id, Some(query.from))
+        .await
+        .map_err(error_response)?;
+    let to = db::fetch_card_revision(&state.pool, card_id, query.to)
+        .await
+        .map_err(error_response)?;
+    Ok(Json(CardRevisionDiff {
+        card_id,
+        from: from.revision,
+        to: to.revision,
+        changes: history::diff_snapshots(&from.card, &to.card),
+    }))
+}
+
+/// API handler to restore a card to an earlier revision. Requires the `editor` role.
+///
+/// The restored card is stored as a new revision, so restores can be undone as well. It
+/// is returned localized into the language of `?lang=` or the `Accept-Language` header.
+///
+/// # Returns
+/// - `200 OK` with the restored card. Returns: `FullCard`.
+/// - `404 Not Found` if the card or revision does not exist.
+/// - `409 Conflict` if the set, a rarity, group or unit of the revision no longer
+///   exists, the set is in the trash or no longer declares a rarity, or another card now
+///   has its number.
+pub async fn restore(
+    State(state): AppState,
+    editor: auth::Editor,
+    Path((card_id, revision)): Path<(i64, i64)>,
+    Languages(languages): Languages,
+) -> Result<Json<FullCard>, (StatusCode, String)> {
+    let mut card = db::restore_card_revision(&state.pool, &editor.actor(), card_id, revision)
+        .await
+        .map_err(error_response)?;
+
+    // The restored name may be new.
+    let mut names_cache = state.names_cache.write().await;
+    *names_cache = db::fetch_all_card_names(&state.pool)
+        .await
+        .unwrap_or_default();
+    drop(names_cache);
+
+    localization::localize_card(&state.pool, &mut card, &languages)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(card))
+}
@@ -0,0 +1,191 @@
+use crate::{
+    AppState, auth,
+    db::{self, DbError},
+    image_store,
+    localization::{self, Languages},
+    models::{IdentifyQuery, Printing, PrintingMatch},
+    perceptual_hash,
+};
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
+};
+
+/// The maximum size of an uploaded image.
+pub const MAX_IMAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// The default and maximum number of printings returned when identifying a card.
+const DEFAULT_IDENTIFY_LIMIT: usize = 5;
+const MAX_IDENTIFY_LIMIT: usize = 50;
+
+/// Stored images never change, as they are named after their content.
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// API handler to upload the image of a printing. Requires the `editor` role.
+///
+/// The body is the raw image (PNG, JPEG or WebP). It is stored locally under its content
+/// hash along with its resized variants, and the printing's `image_url` is pointed at
+/// `/images/:hash`. Its perceptual hash is recorded so that photos can be matched to it.
+///
+/// # Returns
+/// - `200 OK` with the updated printing.
+/// - `404 Not Found` if the printing does not exist.
+/// - `415 Unsupported Media Type` if the body is not a supported image.
+/// - `422 Unprocessable Entity` if the image cannot be decoded.
+pub async fn upload(
+    State(state): AppState,
+    editor: auth::Editor,
+    Path(printing_id): Path<i64>,
+    body: Bytes,
+) -> Result<Json<Printing>, (StatusCode, String)> {
+    if image_store::content_type(&body).is_none() {
+        return Err((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Images must be PNG, JPEG or WebP.".to_string(),
+        ));
+    }
+
+    // Hashing decodes the image, so undecodable images are rejected before anything is stored.
+    let perceptual_hash = image_store::hash_off_runtime(body.to_vec())
+        .await
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    let hash = image_store::store(&state.image_dir, &body)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let variants = image_store::store_variants(&state.image_dir, &hash, body.to_vec())
+        .await
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+
+    match db::set_printing_image_urls(
+        &state.pool,
+        Some(&editor.actor()),
+        printing_id,
+        &image_store::image_url(&hash),
+        Some(&variants.thumbnail_url),
+        Some(&variants.medium_url),
+        perceptual_hash,
+    )
+    .await
+    {
+        Ok(printing) => Ok(Json(printing)),
+        Err(DbError::PrintingNotFound(id)) => {
+            Err((StatusCode::NOT_FOUND, format!("Printing not found: {}", id)))
+        }
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// API handler to identify a card from a photo.
+///
+/// The body is the raw photo (PNG, JPEG or WebP). Its perceptual hash is compared with
+/// those of every locally stored printing image, without any external service. Photos
+/// should be cropped to the card for the best results. The cards of the matches are
+/// localized into the language of `?lang=` or the `Accept-Language` header.
+///
+/// # Returns
+/// - `200 OK` with the closest printings, nearest first. Returns: `Vec<PrintingMatch>`.
+/// - `415 Unsupported Media Type` if the body is not a supported image.
+/// - `422 Unprocessable Entity` if the image cannot be decoded.
+pub async fn identify(
+    State(state): AppState,
+    Query(query): Query<IdentifyQuery>,
+    Languages(languages): Languages,
+    body: Bytes,
+) -> Result<Json<Vec<PrintingMatch>>, (StatusCode, String)> {
+    if image_store::content_type(&body).is_none() {
+        return Err((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Images must be PNG, JPEG or WebP.".to_string(),
+        ));
+    }
+    let photo_hash = image_store::hash_off_runtime(body.to_vec())
+        .await
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+
+    let mut candidates: Vec<(u32, i64, i64)> = db::fetch_perceptual_hashes(&state.pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .map(|(printing_id, card_id, hash)| {
+            (
+                perceptual_hash::hamming_distance(photo_hash, hash),
+                printing_id,
+                card_id,
+            )
+        })
+        .collect();
+    candidates.sort_unstable();
+    candidates.truncate(
+        query
+            .limit
+            .unwrap_or(DEFAULT_IDENTIFY_LIMIT)
+            .min(MAX_IDENTIFY_LIMIT),
+    );
+
+    let mut matches = Vec::with_capacity(candidates.len());
+    for (distance, printing_id, card_id) in candidates {
+        let card = db::fetch_full_card(&state.pool, card_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        matches.push(PrintingMatch {
+            printing_id,
+            distance,
+            card,
+        });
+    }
+    localization::localize_cards(
+        &state.pool,
+        matches.iter_mut().map(|found| &mut found.card),
+        &languages,
+    )
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(matches))
+}
+
+/// API handler to serve a stored image, or one of its variants, by its file name.
+///
+/// Responses can be cached forever. The file name doubles as the `ETag`, so a client
+/// revalidating with `If-None-Match` gets `304 Not Modified`.
+pub async fn serve(
+    State(state): AppState,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let not_found = || (StatusCode::NOT_FOUND, "Image not found".to_string());
+    if !image_store::is_stored_image_name(&name) {
+        return Err(not_found());
+    }
+
+    let etag = format!("\"{}\"", name);
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, IMMUTABLE_CACHE_CONTROL.to_string()),
+    ];
+    let revalidated = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+    if revalidated {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let bytes = match tokio::fs::read(state.image_dir.join(&name)).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(not_found()),
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+    let content_type = image_store::content_type(&bytes).unwrap_or("application/octet-stream");
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        cache_headers,
+        bytes,
+    )
+        .into_response())
+}
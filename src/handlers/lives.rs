@@ -0,0 +1,50 @@
+use crate::{
+    AppState,
+    handlers::decks::{load_card, load_deck},
+    hearts::{self, HeartPool},
+    models::{CardType, DeckEntry, LiveCheckRequest, RequirementCheck},
+};
+use axum::{Json as AxumJson, extract::State, http::StatusCode, response::Json};
+
+/// API handler to check whether the members on stage can pay for a Live card.
+///
+/// # Returns
+/// - `200 OK` with the hearts that are missing or left over.
+/// - `400 Bad Request` if the stage or Live card are invalid.
+pub async fn check(
+    State(state): AppState,
+    AxumJson(payload): AxumJson<LiveCheckRequest>,
+) -> Result<Json<RequirementCheck>, (StatusCode, String)> {
+    let live = load_card(&state, payload.live_card_id).await?;
+    if live.base.card_type != CardType::Live {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Card {} is not a Live card.", live.base.id),
+        ));
+    }
+
+    let mut pool = HeartPool::default();
+    if !payload.stage.is_empty() {
+        let stage_entries: Vec<DeckEntry> = payload
+            .stage
+            .iter()
+            .map(|&card_id| DeckEntry { card_id, count: 1 })
+            .collect();
+        for (card, count) in load_deck(&state, &stage_entries).await? {
+            if card.base.card_type != CardType::Character {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("Card {} on stage is not a Character card.", card.base.id),
+                ));
+            }
+            for _ in 0..count {
+                pool.add_all(&card.hearts);
+            }
+        }
+    }
+    for blade_heart in payload.blade_hearts {
+        pool.add_blade_heart(blade_heart);
+    }
+
+    Ok(Json(hearts::check_requirements(&pool, &live.hearts)))
+}
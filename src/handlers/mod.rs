@@ -1,7 +1,81 @@
+pub mod api_keys;
+pub mod audit;
 pub mod cards;
+pub mod collections;
+pub mod decks;
+pub mod dump;
 pub mod groups;
+pub mod history;
+pub mod images;
+pub mod lives;
 pub mod names;
 pub mod rarities;
 pub mod sets;
+pub mod translations;
+pub mod trash;
 pub mod units;
-pub mod variants;
\ No newline at end of file
+pub mod variants;
+
+use crate::{
+    Pool,
+    db::{self, DbError},
+    models::{DeleteQuery, DependentAction, TrashKind},
+};
+use axum::http::StatusCode;
+
+/// Reads what to do with dependent cards from the query of a delete request.
+pub(crate) fn dependent_action(
+    query: DeleteQuery,
+) -> Result<DependentAction, (StatusCode, String)> {
+    query.action().ok_or((
+        StatusCode::BAD_REQUEST,
+        "Use either `cascade` or `reassign_to`, not both.".to_string(),
+    ))
+}
+
+/// Maps an error deleting or renaming a set, group, unit or rarity to a response.
+///
+/// Deletes that cards still depend on, renames to a key already taken, and changes that
+/// would give a card the number or rarity of another, or a rarity its set does not
+/// declare, are conflicts.
+pub(crate) fn reference_error_response(e: DbError) -> (StatusCode, String) {
+    match e {
+        DbError::HasDependents { .. }
+        | DbError::RarityNotInSet { .. }
+        | DbError::RaritiesInUse { .. } => (StatusCode::CONFLICT, e.to_string()),
+        DbError::InvalidReassignTarget(_)
+        | DbError::CascadeIntoTrash
+        | DbError::InvalidDate(_)
+        | DbError::InvalidTotalCards(_)
+        | DbError::RarityNotFound(_) => (StatusCode::BAD_REQUEST, e.to_string()),
+        DbError::Sqlx(sqlx::Error::Database(ref db_err)) if db_err.is_unique_violation() => {
+            (StatusCode::CONFLICT, e.to_string())
+        }
+        e => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// Maps adding a set, group or unit whose key is already taken to a conflict, pointing
+/// at the trash if a deleted item still holds the key.
+pub(crate) async fn already_exists_response(
+    pool: &Pool,
+    kind: TrashKind,
+    key: &str,
+    message: String,
+) -> (StatusCode, String) {
+    match db::is_in_trash(pool, kind, key).await {
+        Ok(true) => {
+            let kind = kind.as_str();
+            (
+                StatusCode::CONFLICT,
+                format!(
+                    "A {kind} '{key}' is in the trash. Restore it with \
+                     `POST /trash/{kind}/{key}/restore`, or purge it with \
+                     `DELETE /trash/{kind}/{key}` first."
+                ),
+            )
+        }
+        Ok(false) => (StatusCode::CONFLICT, message),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
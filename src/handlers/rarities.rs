@@ -1,32 +1,42 @@
 use crate::{
-    AppState, db,
-    models::{CreateRarity, RarityType},
+    AppState, auth, db, handlers,
+    models::{CreateRarity, DeleteQuery, Rarity},
 };
 use axum::{
     Json as AxumJson,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
 };
-use std::collections::HashMap;
-
-/// API handler to get all rarity mappings from the cache.
-pub async fn get_all(State(state): AppState) -> Json<HashMap<String, RarityType>> {
+/// API handler to get all rarities from the cache, by rank.
+pub async fn get_all(State(state): AppState) -> Json<Vec<Rarity>> {
     let cache = state.rarity_cache.read().await;
-    Json(cache.clone())
+    let mut rarities: Vec<_> = cache.values().cloned().collect();
+    rarities.sort_by(|a, b| (a.sort_rank, &a.rarity_code).cmp(&(b.sort_rank, &b.rarity_code)));
+    Json(rarities)
 }
 
-/// API handler to get the type of a single rarity.
-pub async fn get_by_code(State(state): AppState, Path(code): Path<String>) -> Json<RarityType> {
+/// API handler to get a single rarity.
+///
+/// # Returns
+/// - `200 OK` with the rarity.
+/// - `404 Not Found` if no such rarity exists.
+pub async fn get_by_code(
+    State(state): AppState,
+    Path(code): Path<String>,
+) -> Result<Json<Rarity>, (StatusCode, String)> {
     let cache = state.rarity_cache.read().await;
-    // Look up the code in the cache, defaulting to Regular if not found.
-    let rarity_type = cache.get(&code).cloned().unwrap_or(RarityType::Regular);
-    Json(rarity_type)
+    cache
+        .get(&code)
+        .cloned()
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("Rarity not found: {}", code)))
 }
 
-/// API handler to add a new rarity mapping.
+/// API handler to add a new rarity. Requires the `editor` role.
 pub async fn add(
     State(state): AppState,
+    editor: auth::Editor,
     AxumJson(payload): AxumJson<CreateRarity>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     // Acquire a write lock first to serialize access to this resource.
@@ -41,16 +51,17 @@ pub async fn add(
     }
 
     // Now, attempt the database insert.
-    match db::add_rarity(&state.pool, &payload.rarity_code, payload.rarity_type).await {
+    let rarity = Rarity::from(payload);
+    match db::add_rarity(&state.pool, &editor.actor(), &rarity).await {
         Ok(_) => {
             // If the DB insert succeeds, update the cache and return success.
-            cache.insert(payload.rarity_code, payload.rarity_type);
+            cache.insert(rarity.rarity_code.clone(), rarity);
             Ok(StatusCode::CREATED)
         }
         // The DB can still fail with a unique violation if another process modified it.
         Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => Err((
             StatusCode::CONFLICT,
-            format!("Rarity '{}' already exists.", payload.rarity_code),
+            format!("Rarity '{}' already exists.", rarity.rarity_code),
         )),
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -59,23 +70,40 @@ pub async fn add(
     }
 }
 
-/// API handler to delete a rarity mapping.
+/// API handler to delete a rarity. Requires the `admin` role.
+///
+/// With `?cascade=true` the printings of the rarity are removed, and with `?reassign_to=`
+/// they are moved to another rarity.
+///
+/// # Returns
+/// - `204 No Content` if the rarity was deleted.
+/// - `400 Bad Request` if the rarity to reassign to does not exist.
+/// - `404 Not Found` if no such rarity exists.
+/// - `409 Conflict` if printings use the rarity and neither option is given, listing
+///   them, or if a card would end up with two printings of the same rarity or with a
+///   rarity its set does not declare.
 pub async fn delete(
     State(state): AppState,
+    admin: auth::Admin,
     Path(code): Path<String>,
+    Query(query): Query<DeleteQuery>,
 ) -> Result<StatusCode, (StatusCode, String)> {
+    let action = handlers::dependent_action(query)?;
+
     // Acquire a write lock first to ensure the cache and DB operations are atomic.
     let mut cache = state.rarity_cache.write().await;
 
     // Attempt to delete from the database.
-    let result = db::delete_rarity(&state.pool, &code)
+    let result = db::delete_rarity(&state.pool, &admin.actor(), &code, &action)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(handlers::reference_error_response)?;
 
-    // If the row was successfully deleted from the DB, remove it from the cache.
-    if result.rows_affected() > 0 {
-        cache.remove(&code);
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, format!("Rarity not found: {}", code)));
     }
 
+    // The row was deleted from the DB, so remove it from the cache.
+    cache.remove(&code);
+
     Ok(StatusCode::NO_CONTENT)
 }
@@ -1,39 +1,171 @@
 use crate::{
-    AppState, db,
-    models::{CreateSet, SetResponse},
+    AppState, auth,
+    db::{self, DbError},
+    handlers,
+    localization::{self, Languages},
+    models::{
+        CreateSet, DeleteQuery, Rarity, SetChecklist, SetListQuery, SetRarities, SetResponse,
+        SetSort, SortDirection, TrashKind, UpdateSet,
+    },
 };
 use axum::{
     Json, Json as AxumJson,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
 };
+use std::cmp::Ordering;
+
+/// Compares two optional fields, placing missing ones last in either direction.
+fn compare_present_first<T: Ord>(a: Option<T>, b: Option<T>, order: SortDirection) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if order == SortDirection::Desc => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
 
 /// Handler to get all sets from the database.
 ///
+/// With `?sort=release_date`, `sort_order`, `set_code` or `name`, and optionally
+/// `&order=desc`, sets are sorted by that field. Otherwise they are listed by their sort
+/// order.
+///
 /// # Returns
 /// - `200 OK` with a JSON array of all sets.
 /// - `500 Internal Server Error` if there's a database error.
-pub async fn get_all(State(state): AppState) -> Json<Vec<SetResponse>> {
-    let cache = state.sets_cache.read().await;
-    Json(cache.clone())
+pub async fn get_all(
+    State(state): AppState,
+    Query(query): Query<SetListQuery>,
+) -> Json<Vec<SetResponse>> {
+    let mut sets = state.sets_cache.read().await.clone();
+    let order = query.order;
+    match query.sort {
+        Some(SetSort::ReleaseDate) => sets.sort_by(|a, b| {
+            compare_present_first(a.release_date.as_ref(), b.release_date.as_ref(), order)
+        }),
+        Some(SetSort::SortOrder) => {
+            sets.sort_by(|a, b| compare_present_first(a.sort_order, b.sort_order, order))
+        }
+        Some(SetSort::SetCode) => {
+            sets.sort_by(|a, b| compare_present_first(Some(&a.set_code), Some(&b.set_code), order))
+        }
+        Some(SetSort::Name) => {
+            sets.sort_by(|a, b| compare_present_first(Some(&a.name), Some(&b.name), order))
+        }
+        None if order == SortDirection::Desc => sets.reverse(),
+        None => {}
+    }
+    Json(sets)
 }
 
-/// API handler to add a new set.
+/// Handler to get the checklist of a set: every card and printing of it in number order,
+/// with the numbers missing from its declared total. The set and card names are
+/// localized into the language of `?lang=` or the `Accept-Language` header.
+///
+/// # Returns
+/// - `200 OK` with the checklist.
+/// - `404 Not Found` if no such set exists, or it is in the trash.
+pub async fn checklist(
+    State(state): AppState,
+    Path(set_code): Path<String>,
+    Languages(languages): Languages,
+) -> Result<Json<SetChecklist>, (StatusCode, String)> {
+    match db::fetch_set_checklist(&state.pool, &set_code).await {
+        Ok(Some(mut checklist)) => {
+            localization::localize_checklist(&state.pool, &mut checklist, &languages)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            Ok(Json(checklist))
+        }
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            format!("Set not found: {}", set_code),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )),
+    }
+}
+
+/// Handler to get the rarities a set declares, by rank.
+///
+/// # Returns
+/// - `200 OK` with the rarities, empty if the set declares none.
+/// - `404 Not Found` if no such set exists, or it is in the trash.
+pub async fn rarities(
+    State(state): AppState,
+    Path(set_code): Path<String>,
+) -> Result<Json<Vec<Rarity>>, (StatusCode, String)> {
+    match db::fetch_set_rarities(&state.pool, &set_code).await {
+        Ok(Some(rarities)) => Ok(Json(rarities)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            format!("Set not found: {}", set_code),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )),
+    }
+}
+
+/// API handler to replace the rarities a set declares. Requires the `editor` role.
+///
+/// Once a set declares rarities, cards of it can only be created with one of them. An
+/// empty list accepts any known rarity again.
+///
+/// # Returns
+/// - `204 No Content` if the rarities were replaced.
+/// - `400 Bad Request` if a rarity does not exist.
+/// - `404 Not Found` if no such set exists, or it is in the trash.
+/// - `409 Conflict` if printings of the set have a rarity left out, listing them.
+pub async fn set_rarities(
+    State(state): AppState,
+    editor: auth::Editor,
+    Path(set_code): Path<String>,
+    AxumJson(payload): AxumJson<SetRarities>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let found = db::set_set_rarities(&state.pool, &editor.actor(), &set_code, &payload.rarities)
+        .await
+        .map_err(handlers::reference_error_response)?;
+
+    if !found {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Set not found: {}", set_code),
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler to add a new set. Requires the `editor` role.
 pub async fn add(
     State(state): AppState,
+    editor: auth::Editor,
     AxumJson(payload): AxumJson<CreateSet>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    match db::add_set(&state.pool, &payload.set_code, &payload.name).await {
+    match db::add_set(&state.pool, &editor.actor(), &payload).await {
         Ok(_) => {
             // Invalidate and refresh cache
             let mut cache = state.sets_cache.write().await;
             *cache = db::fetch_all_sets(&state.pool).await.unwrap_or_default();
             Ok(StatusCode::CREATED)
         }
-        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => Err((
-            StatusCode::CONFLICT,
-            format!("Set with code '{}' already exists.", payload.set_code),
-        )),
+        Err(DbError::Sqlx(sqlx::Error::Database(db_err))) if db_err.is_unique_violation() => {
+            Err(handlers::already_exists_response(
+                &state.pool,
+                TrashKind::Set,
+                &payload.set_code,
+                format!("Set with code '{}' already exists.", payload.set_code),
+            )
+            .await)
+        }
+        Err(e @ (DbError::InvalidDate(_) | DbError::InvalidTotalCards(_))) => {
+            Err((StatusCode::BAD_REQUEST, e.to_string()))
+        }
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database error: {}", e),
@@ -41,20 +173,93 @@ pub async fn add(
     }
 }
 
-/// API handler to delete a set.
+/// API handler to change the code, name or other fields of a set. Requires the `editor`
+/// role.
+///
+/// Fields left out are not changed, and optional ones given as `null` are cleared. Cards
+/// of the set are moved along to a new code, which is recorded in their history.
+///
+/// # Returns
+/// - `204 No Content` if the set was updated.
+/// - `400 Bad Request` if no field is given, or the release date or total is invalid.
+/// - `404 Not Found` if no such set exists, or it is in the trash.
+/// - `409 Conflict` if another set already has the new code, or a set in the trash still
+///   holds it.
+pub async fn update(
+    State(state): AppState,
+    editor: auth::Editor,
+    Path(set_code): Path<String>,
+    AxumJson(payload): AxumJson<UpdateSet>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if payload.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Give at least one field to change.".to_string(),
+        ));
+    }
+
+    let result = match db::update_set(&state.pool, &editor.actor(), &set_code, &payload).await {
+        Ok(result) => result,
+        Err(DbError::Sqlx(sqlx::Error::Database(db_err))) if db_err.is_unique_violation() => {
+            let new_code = payload.set_code.as_deref().unwrap_or(&set_code);
+            return Err(handlers::already_exists_response(
+                &state.pool,
+                TrashKind::Set,
+                new_code,
+                format!("Set with code '{new_code}' already exists."),
+            )
+            .await);
+        }
+        Err(e) => return Err(handlers::reference_error_response(e)),
+    };
+
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Set not found: {}", set_code),
+        ));
+    }
+
+    // Invalidate and refresh cache
+    let mut cache = state.sets_cache.write().await;
+    *cache = db::fetch_all_sets(&state.pool).await.unwrap_or_default();
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler to move a set to the trash. Requires the `admin` role.
+///
+/// With `?reassign_to=` its cards are moved to another set. Cards cannot be removed
+/// along with the set, so that it can be restored from the trash as it was.
+///
+/// # Returns
+/// - `204 No Content` if the set was deleted.
+/// - `400 Bad Request` if the set to reassign to does not exist, or `cascade` is given.
+/// - `404 Not Found` if no such set exists, or it is already in the trash.
+/// - `409 Conflict` if cards belong to the set and are not reassigned, listing them, or
+///   if a reassigned card would take the number of another or has a rarity the other set
+///   does not declare.
 pub async fn delete(
     State(state): AppState,
+    admin: auth::Admin,
     Path(set_code): Path<String>,
+    Query(query): Query<DeleteQuery>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let result = db::delete_set(&state.pool, &set_code)
+    let action = handlers::dependent_action(query)?;
+    let result = db::delete_set(&state.pool, &admin.actor(), &set_code, &action)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(handlers::reference_error_response)?;
 
-    if result.rows_affected() > 0 {
-        // Invalidate and refresh cache
-        let mut cache = state.sets_cache.write().await;
-        *cache = db::fetch_all_sets(&state.pool).await.unwrap_or_default();
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Set not found: {}", set_code),
+        ));
     }
 
+    // Invalidate and refresh cache
+    let mut cache = state.sets_cache.write().await;
+    *cache = db::fetch_all_sets(&state.pool).await.unwrap_or_default();
+
     Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,79 @@
+use crate::{
+    AppState, auth, db, localization,
+    models::{Translation, TranslationQuery},
+};
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+
+/// Handler to list translations, optionally only of one `kind` or into one `language`.
+///
+/// # Returns
+/// - `200 OK` with the translations, by kind, key and language. Returns: `Vec<Translation>`.
+/// - `400 Bad Request` if the language code is invalid.
+pub async fn get_all(
+    State(state): AppState,
+    Query(query): Query<TranslationQuery>,
+) -> Result<Json<Vec<Translation>>, (StatusCode, String)> {
+    let language = match query.language.as_deref() {
+        Some(language) => Some(localization::normalize_language(language).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid language code: {}", language),
+            )
+        })?),
+        None => None,
+    };
+
+    match db::fetch_translations(&state.pool, query.kind, language.as_deref()).await {
+        Ok(translations) => Ok(Json(translations)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )),
+    }
+}
+
+/// API handler to submit a translation, replacing any earlier one of the same text into
+/// the same language. Requires the `editor` role.
+///
+/// # Returns
+/// - `204 No Content` if the translation was saved.
+/// - `400 Bad Request` if the language code is invalid or the translation is empty.
+/// - `404 Not Found` if the name, set or skill translated does not exist.
+pub async fn submit(
+    State(state): AppState,
+    editor: auth::Editor,
+    Json(mut payload): Json<Translation>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    payload.language = localization::normalize_language(&payload.language).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid language code: {}", payload.language),
+        )
+    })?;
+    if payload.text.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Translations cannot be empty.".to_string(),
+        ));
+    }
+
+    match db::upsert_translation(&state.pool, &editor.actor(), &payload).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            format!(
+                "Nothing to translate: {} '{}'",
+                payload.kind.as_str(),
+                payload.key
+            ),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )),
+    }
+}
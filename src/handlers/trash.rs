@@ -0,0 +1,83 @@
+use crate::{
+    ApiState, AppState, auth, db, handlers,
+    models::{TrashItem, TrashKind},
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+
+/// Refreshes the cache listing the given kind of reference data.
+async fn refresh_cache(state: &ApiState, kind: TrashKind) {
+    match kind {
+        TrashKind::Set => {
+            *state.sets_cache.write().await =
+                db::fetch_all_sets(&state.pool).await.unwrap_or_default();
+        }
+        TrashKind::Group => {
+            *state.groups_cache.write().await =
+                db::fetch_all_groups(&state.pool).await.unwrap_or_default();
+        }
+        TrashKind::Unit => {
+            *state.units_cache.write().await =
+                db::fetch_all_units(&state.pool).await.unwrap_or_default();
+        }
+    }
+}
+
+/// API handler to list deleted sets, groups and units, most recently deleted first.
+/// Requires a key of any role.
+pub async fn get_all(
+    State(state): AppState,
+    _: auth::Reader,
+) -> Result<Json<Vec<TrashItem>>, (StatusCode, String)> {
+    db::fetch_trash(&state.pool)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// API handler to restore a set, group or unit from the trash. Requires the `admin` role.
+///
+/// Items only go to the trash once no card belongs to them, so they come back without
+/// cards.
+///
+/// # Returns
+/// - `204 No Content` if the item was restored.
+/// - `404 Not Found` if no such item is in the trash.
+pub async fn restore(
+    State(state): AppState,
+    admin: auth::Admin,
+    Path((kind, key)): Path<(TrashKind, String)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let restored = db::restore_from_trash(&state.pool, &admin.actor(), kind, &key)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !restored {
+        return Err((StatusCode::NOT_FOUND, format!("Not in the trash: {}", key)));
+    }
+
+    refresh_cache(&state, kind).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler to permanently delete a set, group or unit from the trash. Requires the
+/// `admin` role.
+///
+/// # Returns
+/// - `204 No Content` if the item was purged.
+/// - `404 Not Found` if no such item is in the trash.
+/// - `409 Conflict` if cards still belong to it, listing them. Items only go to the trash
+///   once no card belongs to them, so this only happens to data changed by hand.
+pub async fn purge(
+    State(state): AppState,
+    admin: auth::Admin,
+    Path((kind, key)): Path<(TrashKind, String)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match db::purge_from_trash(&state.pool, &admin.actor(), kind, &key).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((StatusCode::NOT_FOUND, format!("Not in the trash: {}", key))),
+        Err(e) => Err(handlers::reference_error_response(e)),
+    }
+}
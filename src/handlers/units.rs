@@ -1,10 +1,12 @@
 use crate::{
-    AppState, db,
-    models::{CreateUnit},
+    AppState, auth,
+    db::{self, DbError},
+    handlers,
+    models::{CreateUnit, DeleteQuery, RenameUnit, TrashKind},
 };
 use axum::{
     Json, Json as AxumJson,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
 };
 
@@ -18,22 +20,28 @@ pub async fn get_all(State(state): AppState) -> Json<Vec<String>> {
     Json(cache.clone())
 }
 
-/// API handler to add a new unit.
+/// API handler to add a new unit. Requires the `editor` role.
 pub async fn add(
     State(state): AppState,
+    editor: auth::Editor,
     AxumJson(payload): AxumJson<CreateUnit>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    match db::add_unit(&state.pool, &payload.name).await {
+    match db::add_unit(&state.pool, &editor.actor(), &payload.name).await {
         Ok(_) => {
             // Invalidate and refresh cache
             let mut cache = state.units_cache.write().await;
             *cache = db::fetch_all_units(&state.pool).await.unwrap_or_default();
             Ok(StatusCode::CREATED)
         }
-        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => Err((
-            StatusCode::CONFLICT,
-            format!("Unit with name '{}' already exists.", payload.name),
-        )),
+        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+            Err(handlers::already_exists_response(
+                &state.pool,
+                TrashKind::Unit,
+                &payload.name,
+                format!("Unit with name '{}' already exists.", payload.name),
+            )
+            .await)
+        }
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database error: {}", e),
@@ -41,20 +49,72 @@ pub async fn add(
     }
 }
 
-/// API handler to delete a unit.
+/// API handler to rename a unit. Requires the `editor` role.
+///
+/// # Returns
+/// - `204 No Content` if the unit was renamed.
+/// - `404 Not Found` if no such unit exists, or it is in the trash.
+/// - `409 Conflict` if another unit already has the new name, or a unit in the trash
+///   still holds it.
+pub async fn rename(
+    State(state): AppState,
+    editor: auth::Editor,
+    Path(name): Path<String>,
+    AxumJson(payload): AxumJson<RenameUnit>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let result = match db::rename_unit(&state.pool, &editor.actor(), &name, &payload.name).await {
+        Ok(result) => result,
+        Err(DbError::Sqlx(sqlx::Error::Database(db_err))) if db_err.is_unique_violation() => {
+            return Err(handlers::already_exists_response(
+                &state.pool,
+                TrashKind::Unit,
+                &payload.name,
+                format!("Unit with name '{}' already exists.", payload.name),
+            )
+            .await);
+        }
+        Err(e) => return Err(handlers::reference_error_response(e)),
+    };
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, format!("Unit not found: {}", name)));
+    }
+
+    // Invalidate and refresh cache
+    let mut cache = state.units_cache.write().await;
+    *cache = db::fetch_all_units(&state.pool).await.unwrap_or_default();
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler to move a unit to the trash. Requires the `admin` role.
+///
+/// With `?reassign_to=` its cards are moved to another unit. Cards cannot be removed
+/// from the unit along with it, so that it can be restored from the trash as it was.
+///
+/// # Returns
+/// - `204 No Content` if the unit was deleted.
+/// - `400 Bad Request` if the unit to reassign to does not exist, or `cascade` is given.
+/// - `404 Not Found` if no such unit exists, or it is already in the trash.
+/// - `409 Conflict` if cards belong to the unit and neither option is given, listing them.
 pub async fn delete(
     State(state): AppState,
+    admin: auth::Admin,
     Path(name): Path<String>,
+    Query(query): Query<DeleteQuery>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    let result = db::delete_unit(&state.pool, &name)
+    let action = handlers::dependent_action(query)?;
+    let result = db::delete_unit(&state.pool, &admin.actor(), &name, &action)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(handlers::reference_error_response)?;
 
-    if result.rows_affected() > 0 {
-        // Invalidate and refresh cache
-        let mut cache = state.units_cache.write().await;
-        *cache = db::fetch_all_units(&state.pool).await.unwrap_or_default();
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, format!("Unit not found: {}", name)));
     }
 
+    // Invalidate and refresh cache
+    let mut cache = state.units_cache.write().await;
+    *cache = db::fetch_all_units(&state.pool).await.unwrap_or_default();
+
     Ok(StatusCode::NO_CONTENT)
 }
@@ -1,4 +1,4 @@
-use crate::{AppState, models::CreateNameVariant};
+use crate::{AppState, auth, db, models::CreateNameVariant};
 use axum::{
     Json as AxumJson,
     extract::{Path, State},
@@ -13,9 +13,10 @@ pub async fn get_all(State(state): AppState) -> Json<HashMap<String, String>> {
     Json(cache.clone())
 }
 
-/// API handler to add a new name variant mapping.
+/// API handler to add a new name variant mapping. Requires the `editor` role.
 pub async fn add(
     State(state): AppState,
+    editor: auth::Editor,
     AxumJson(payload): AxumJson<CreateNameVariant>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     let mut cache = state.name_variant_cache.write().await;
@@ -27,11 +28,13 @@ pub async fn add(
         ));
     }
 
-    match sqlx::query("INSERT INTO name_variants (variant_name, canonical_name) VALUES (?, ?)")
-        .bind(&payload.variant_name)
-        .bind(&payload.canonical_name)
-        .execute(&state.pool)
-        .await
+    match db::add_name_variant(
+        &state.pool,
+        &editor.actor(),
+        &payload.variant_name,
+        &payload.canonical_name,
+    )
+    .await
     {
         Ok(_) => {
             cache.insert(payload.variant_name, payload.canonical_name);
@@ -48,16 +51,15 @@ pub async fn add(
     }
 }
 
-/// API handler to delete a name variant mapping.
+/// API handler to delete a name variant mapping. Requires the `admin` role.
 pub async fn delete(
     State(state): AppState,
+    admin: auth::Admin,
     Path(variant): Path<String>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     let mut cache = state.name_variant_cache.write().await;
 
-    let result = sqlx::query("DELETE FROM name_variants WHERE variant_name = ?")
-        .bind(&variant)
-        .execute(&state.pool)
+    let result = db::delete_name_variant(&state.pool, &admin.actor(), &variant)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
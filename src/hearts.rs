@@ -0,0 +1,198 @@
+use crate::models::{BladeHeartColor, HeartColor, RequirementCheck};
+use std::collections::HashMap;
+
+/// The six concrete heart colors. `Gray` is not a color of its own but a wildcard.
+pub const COLORS: [HeartColor; 6] = [
+    HeartColor::Pink,
+    HeartColor::Red,
+    HeartColor::Yellow,
+    HeartColor::Green,
+    HeartColor::Blue,
+    HeartColor::Purple,
+];
+
+/// The hearts available to pay for a Live card.
+///
+/// Colored hearts can only pay for their own color (or a Gray requirement),
+/// while wildcards can pay for anything.
+#[derive(Debug, Clone, Default)]
+pub struct HeartPool {
+    pub colored: HashMap<HeartColor, i64>,
+    pub wildcards: i64,
+}
+
+impl HeartPool {
+    /// Adds `count` hearts of the given color. Gray hearts are treated as wildcards.
+    pub fn add(&mut self, color: HeartColor, count: i64) {
+        if color == HeartColor::Gray {
+            self.wildcards += count;
+        } else {
+            *self.colored.entry(color).or_insert(0) += count;
+        }
+    }
+
+    /// Adds every heart in a card's heart map.
+    pub fn add_all(&mut self, hearts: &HashMap<HeartColor, i64>) {
+        for (color, count) in hearts {
+            self.add(*color, *count);
+        }
+    }
+
+    /// Adds a single heart from a blade heart. `BladeHeartColor::All` is a wildcard.
+    pub fn add_blade_heart(&mut self, blade_heart: BladeHeartColor) {
+        match blade_heart.heart_color() {
+            Some(color) => self.add(color, 1),
+            None => self.wildcards += 1,
+        }
+    }
+}
+
+/// Works out how a pool of hearts pays for a Live card's heart requirements.
+///
+/// Colored requirements must be paid with hearts of the same color, falling back
+/// to wildcards. Gray requirements are then paid with any leftover colored hearts
+/// before spending the remaining wildcards. This greedy order is optimal, since a
+/// colored heart can pay for fewer requirements than a wildcard.
+pub fn check_requirements(
+    pool: &HeartPool,
+    required: &HashMap<HeartColor, i64>,
+) -> RequirementCheck {
+    let mut wildcards = pool.wildcards;
+    let mut leftover: HashMap<HeartColor, i64> = HashMap::new();
+    let mut missing: HashMap<HeartColor, i64> = HashMap::new();
+
+    // 1. Pay colored requirements with matching hearts, then with wildcards.
+    for color in COLORS {
+        let have = pool.colored.get(&color).copied().unwrap_or(0);
+        let need = required.get(&color).copied().unwrap_or(0);
+        if have >= need {
+            if have > need {
+                leftover.insert(color, have - need);
+            }
+            continue;
+        }
+        let from_wildcards = (need - have).min(wildcards);
+        wildcards -= from_wildcards;
+        if need - have > from_wildcards {
+            missing.insert(color, need - have - from_wildcards);
+        }
+    }
+
+    // 2. Pay Gray requirements with leftover colored hearts, then with wildcards.
+    let mut gray_needed = required.get(&HeartColor::Gray).copied().unwrap_or(0);
+    for color in COLORS {
+        if gray_needed == 0 {
+            break;
+        }
+        if let Some(count) = leftover.get_mut(&color) {
+            let used = (*count).min(gray_needed);
+            *count -= used;
+            gray_needed -= used;
+        }
+    }
+    let from_wildcards = gray_needed.min(wildcards);
+    wildcards -= from_wildcards;
+    gray_needed -= from_wildcards;
+    if gray_needed > 0 {
+        missing.insert(HeartColor::Gray, gray_needed);
+    }
+
+    leftover.retain(|_, count| *count > 0);
+    RequirementCheck {
+        satisfied: missing.is_empty(),
+        missing,
+        leftover,
+        leftover_wildcards: wildcards,
+    }
+}
+
+/// Returns `true` if the pool of hearts can pay for the required hearts.
+pub fn can_satisfy(pool: &HeartPool, required: &HashMap<HeartColor, i64>) -> bool {
+    check_requirements(pool, required).satisfied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hearts(entries: &[(HeartColor, i64)]) -> HashMap<HeartColor, i64> {
+        entries.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_can_satisfy_exact_colors() {
+        let mut pool = HeartPool::default();
+        pool.add_all(&hearts(&[(HeartColor::Pink, 2), (HeartColor::Blue, 1)]));
+
+        assert!(can_satisfy(&pool, &hearts(&[(HeartColor::Pink, 2)])));
+        assert!(!can_satisfy(&pool, &hearts(&[(HeartColor::Pink, 3)])));
+        assert!(!can_satisfy(&pool, &hearts(&[(HeartColor::Red, 1)])));
+    }
+
+    #[test]
+    fn test_can_satisfy_gray_requirement_with_leftovers() {
+        let mut pool = HeartPool::default();
+        pool.add_all(&hearts(&[(HeartColor::Pink, 2), (HeartColor::Blue, 1)]));
+
+        let required = hearts(&[(HeartColor::Pink, 1), (HeartColor::Gray, 2)]);
+        assert!(can_satisfy(&pool, &required));
+
+        let required = hearts(&[(HeartColor::Pink, 1), (HeartColor::Gray, 3)]);
+        assert!(!can_satisfy(&pool, &required));
+    }
+
+    #[test]
+    fn test_can_satisfy_with_wildcards() {
+        let mut pool = HeartPool::default();
+        pool.add(HeartColor::Red, 1);
+        pool.add_blade_heart(BladeHeartColor::All);
+        pool.add_blade_heart(BladeHeartColor::Green);
+
+        let required = hearts(&[
+            (HeartColor::Red, 1),
+            (HeartColor::Purple, 1),
+            (HeartColor::Green, 1),
+        ]);
+        assert!(can_satisfy(&pool, &required));
+
+        let required = hearts(&[(HeartColor::Purple, 2)]);
+        assert!(!can_satisfy(&pool, &required));
+    }
+
+    #[test]
+    fn test_check_requirements_reports_missing_and_leftover() {
+        let mut pool = HeartPool::default();
+        pool.add_all(&hearts(&[(HeartColor::Pink, 3), (HeartColor::Red, 1)]));
+        pool.add_blade_heart(BladeHeartColor::All);
+
+        let required = hearts(&[
+            (HeartColor::Pink, 1),
+            (HeartColor::Blue, 2),
+            (HeartColor::Gray, 1),
+        ]);
+        let check = check_requirements(&pool, &required);
+
+        // The wildcard covers one Blue; the second Blue cannot be paid.
+        assert!(!check.satisfied);
+        assert_eq!(check.missing, hearts(&[(HeartColor::Blue, 1)]));
+        // One leftover Pink pays for the Gray requirement.
+        assert_eq!(
+            check.leftover,
+            hearts(&[(HeartColor::Pink, 1), (HeartColor::Red, 1)])
+        );
+        assert_eq!(check.leftover_wildcards, 0);
+    }
+
+    #[test]
+    fn test_check_requirements_missing_gray() {
+        let mut pool = HeartPool::default();
+        pool.add(HeartColor::Green, 1);
+
+        let required = hearts(&[(HeartColor::Green, 1), (HeartColor::Gray, 2)]);
+        let check = check_requirements(&pool, &required);
+
+        assert!(!check.satisfied);
+        assert_eq!(check.missing, hearts(&[(HeartColor::Gray, 2)]));
+        assert!(check.leftover.is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::models::FieldDiff;
+use serde_json::Value;
+
+/// Lists the fields that differ between two snapshots of a card, in field order.
+///
+/// Objects and lists are compared field by field and item by item, so a changed image
+/// URL is reported as `printings.0.image_url` rather than as a change to the whole list
+/// of printings. A field missing from one side is reported with a `null` value there.
+pub fn diff_snapshots(from: &Value, to: &Value) -> Vec<FieldDiff> {
+    let mut changes = Vec::new();
+    diff_values(&mut changes, "", from, to);
+    changes
+}
+
+fn diff_values(changes: &mut Vec<FieldDiff>, path: &str, from: &Value, to: &Value) {
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            let mut keys: Vec<&String> = from.keys().chain(to.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                diff_values(
+                    changes,
+                    &child(key),
+                    from.get(key).unwrap_or(&Value::Null),
+                    to.get(key).unwrap_or(&Value::Null),
+                );
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => {
+            for index in 0..from.len().max(to.len()) {
+                diff_values(
+                    changes,
+                    &child(&index.to_string()),
+                    from.get(index).unwrap_or(&Value::Null),
+                    to.get(index).unwrap_or(&Value::Null),
+                );
+            }
+        }
+        (from, to) if from != to => changes.push(FieldDiff {
+            field: path.to_string(),
+            from: from.clone(),
+            to: to.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_nested_changes() {
+        let from = json!({
+            "name": "Kousaka Honoka",
+            "hearts": { "Pink": 2 },
+            "printings": [{ "id": 1, "image_url": "a.png" }],
+        });
+        let to = json!({
+            "name": "Kousaka Honoka",
+            "hearts": { "Pink": 2, "Red": 1 },
+            "printings": [{ "id": 1, "image_url": "b.png" }, { "id": 2 }],
+        });
+
+        let fields: Vec<_> = diff_snapshots(&from, &to)
+            .into_iter()
+            .map(|change| (change.field, change.from, change.to))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("hearts.Red".to_string(), Value::Null, json!(1)),
+                (
+                    "printings.0.image_url".to_string(),
+                    json!("a.png"),
+                    json!("b.png")
+                ),
+                ("printings.1".to_string(), Value::Null, json!({ "id": 2 })),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_identical_snapshots() {
+        let card = json!({ "name": "Kousaka Honoka", "skills": ["Draw a card."] });
+        assert!(diff_snapshots(&card, &card).is_empty());
+    }
+}
@@ -0,0 +1,712 @@
+use crate::models::{
+    CardTypeSpecifics, CreateCard, CreateCardTypeSpecifics, FieldChange, FullCard, ImportDiffEntry,
+    ImportDiffStatus, ImportRowError,
+};
+use crate::{Pool, db};
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// The official card list, which relative image URLs on saved pages point into.
+const OFFICIAL_SITE: &str = "https://llofficial-cardgame.com";
+
+/// The fields a card detail page can provide, keyed by the labels used on the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Field {
+    Identifier,
+    Rarity,
+    Name,
+    CardType,
+    Groups,
+    Units,
+    Cost,
+    Blades,
+    Hearts,
+    BladeHeart,
+    Score,
+    SpecialHeart,
+    Skills,
+}
+
+/// Maps a field label to the field it provides. Both the Japanese labels of the
+/// official card list and their English equivalents are recognised.
+fn field_for_label(label: &str) -> Option<Field> {
+    let label: String = label
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, ':' | '：'))
+        .collect::<String>()
+        .to_lowercase();
+    let field = match label.as_str() {
+        "カード番号" | "cardno." | "cardno" | "cardnumber" => Field::Identifier,
+        "レアリティ" | "rarity" => Field::Rarity,
+        "カード名" | "cardname" | "name" => Field::Name,
+        "カードタイプ" | "種類" | "cardtype" | "type" => Field::CardType,
+        "作品名" | "作品" | "series" | "group" | "groups" => Field::Groups,
+        "参加ユニット" | "ユニット" | "unit" | "units" => Field::Units,
+        "コスト" | "cost" => Field::Cost,
+        "ブレード" | "blade" | "blades" => Field::Blades,
+        "基本ハート" | "必要ハート" | "ハート" | "hearts" | "heart" => Field::Hearts,
+        "ブレードハート" | "bladeheart" => Field::BladeHeart,
+        "スコア" | "score" => Field::Score,
+        "特殊ハート" | "specialheart" => Field::SpecialHeart,
+        "テキスト" | "効果" | "能力" | "text" | "skill" | "skills" => Field::Skills,
+        _ => return None,
+    };
+    Some(field)
+}
+
+/// Words identifying each heart color, in the serialized form used by `POST /cards`.
+/// Icons are matched through their alt text or, failing that, their file name.
+const HEART_COLORS: [(&str, &[&str]); 7] = [
+    ("Pink", &["heart01", "桃", "ピンク", "pink"]),
+    ("Red", &["heart02", "赤", "レッド", "red"]),
+    ("Yellow", &["heart03", "黄", "イエロー", "yellow"]),
+    ("Green", &["heart04", "緑", "グリーン", "green"]),
+    ("Blue", &["heart05", "青", "ブルー", "blue"]),
+    ("Purple", &["heart06", "紫", "パープル", "purple"]),
+    ("Gray", &["heart00", "灰", "グレー", "gray", "grey"]),
+];
+
+/// Words identifying a blade heart that counts as any color.
+const ALL_COLOR_WORDS: [&str; 4] = ["heart_all", "heartall", "オール", "all"];
+
+/// Renders the content of an element as plain text.
+///
+/// Icons are replaced by their alt text (or their file name if they have none), so that
+/// heart icons and ability icons survive. With `bracket_icons`, icon text is wrapped in
+/// square brackets, as in the printed skill text. `<br>` and paragraph boundaries become
+/// line breaks.
+fn rich_text(element: ElementRef, bracket_icons: bool) -> String {
+    let mut text = String::new();
+    for node in element.descendants() {
+        match node.value() {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(e) if e.name() == "img" => {
+                let icon = e
+                    .attr("alt")
+                    .map(str::trim)
+                    .filter(|alt| !alt.is_empty())
+                    .or_else(|| {
+                        e.attr("src")
+                            .and_then(|src| src.rsplit('/').next())
+                            .and_then(|file| file.split('.').next())
+                    })
+                    .unwrap_or_default();
+                if bracket_icons {
+                    text.push('[');
+                    text.push_str(icon);
+                    text.push(']');
+                } else {
+                    text.push(' ');
+                    text.push_str(icon);
+                    text.push(' ');
+                }
+            }
+            Node::Element(e) if matches!(e.name(), "br" | "p" | "li" | "div") => text.push('\n'),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Converts full-width digits to ASCII, as the official pages use both.
+fn normalize_digits(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '０'..='９' => char::from_u32(c as u32 - '０' as u32 + '0' as u32).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Collapses runs of whitespace into single spaces and trims the result.
+fn collapse_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Treats dashes and blanks, used on the pages for "none", as missing values.
+fn non_empty(value: &str) -> Option<&str> {
+    let value = value.trim();
+    match value {
+        "" | "-" | "－" | "―" | "ー" | "なし" => None,
+        _ => Some(value),
+    }
+}
+
+/// Collects the label/value pairs of a page from `<dt>`/`<dd>` and `<th>`/`<td>` pairs.
+/// The first occurrence of a field wins.
+fn collect_fields(document: &Html) -> HashMap<Field, ElementRef<'_>> {
+    let labels = Selector::parse("dt, th").expect("valid selector");
+    let mut fields = HashMap::new();
+    for label in document.select(&labels) {
+        let Some(field) = field_for_label(&label.text().collect::<String>()) else {
+            continue;
+        };
+        let value_name = if label.value().name() == "dt" {
+            "dd"
+        } else {
+            "td"
+        };
+        let value = label
+            .next_siblings()
+            .filter_map(ElementRef::wrap)
+            .find(|sibling| sibling.value().name() == value_name);
+        if let Some(value) = value {
+            fields.entry(field).or_insert(value);
+        }
+    }
+    fields
+}
+
+/// Finds the color named at the start of `text`, returning it with the matched length.
+fn heart_color_at(text: &str) -> Option<(&'static str, usize)> {
+    HEART_COLORS.iter().find_map(|(color, words)| {
+        words
+            .iter()
+            .find(|word| text.starts_with(*word))
+            .map(|word| (*color, word.len()))
+    })
+}
+
+/// Parses a heart list such as `赤×2 緑×1`, or a row of heart icons each followed by a count.
+/// A color without a count counts once.
+fn parse_hearts(value: &str) -> Result<Map<String, Value>, String> {
+    let text = normalize_digits(value).to_lowercase().replace('_', "");
+    let mut hearts = Map::new();
+    let mut current: Option<&str> = None;
+    let mut rest = text.as_str();
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((color, len)) = heart_color_at(rest) {
+            if let Some(color) = current.replace(color) {
+                add_hearts(&mut hearts, color, 1)?;
+            }
+            rest = &rest[len..];
+        } else if c.is_ascii_digit() {
+            let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+            let count = digits
+                .parse::<i64>()
+                .map_err(|_| format!("Invalid heart count '{}'.", digits))?;
+            match current.take() {
+                Some(color) => add_hearts(&mut hearts, color, count)?,
+                None => return Err(format!("Heart count '{}' has no color.", digits)),
+            }
+            rest = &rest[digits.len()..];
+        } else {
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if let Some(color) = current {
+        add_hearts(&mut hearts, color, 1)?;
+    }
+    Ok(hearts)
+}
+
+fn add_hearts(hearts: &mut Map<String, Value>, color: &str, count: i64) -> Result<(), String> {
+    let total = hearts
+        .get(color)
+        .and_then(Value::as_i64)
+        .unwrap_or(0)
+        .checked_add(count)
+        .ok_or_else(|| format!("Too many {} hearts.", color))?;
+    hearts.insert(color.to_string(), Value::from(total));
+    Ok(())
+}
+
+/// Parses a blade heart, which is either a single color or a heart of any color.
+fn parse_blade_heart(value: &str) -> Option<&'static str> {
+    let text = value.to_lowercase().replace('_', "");
+    if ALL_COLOR_WORDS
+        .iter()
+        .any(|word| text.contains(&word.replace('_', "")))
+    {
+        return Some("All");
+    }
+    (0..text.len())
+        .filter(|&i| text.is_char_boundary(i))
+        .find_map(|i| heart_color_at(&text[i..]))
+        .map(|(color, _)| color)
+        // Gray hearts only appear as requirements, never as blade hearts.
+        .filter(|color| *color != "Gray")
+}
+
+fn parse_card_type(value: &str) -> Result<&'static str, String> {
+    let text = value.trim().to_lowercase();
+    if text.contains("メンバー") || text.contains("member") || text.contains("character") {
+        Ok("Character")
+    } else if text.contains("ライブ") || text.contains("live") {
+        Ok("Live")
+    } else if text.contains("エネルギー") || text.contains("energy") {
+        Ok("Energy")
+    } else {
+        Err(format!("Unknown card type '{}'.", value.trim()))
+    }
+}
+
+fn parse_special_heart(value: &str) -> Result<Option<&'static str>, String> {
+    let text = value.to_lowercase();
+    if text.contains("ドロー") || text.contains("draw") {
+        Ok(Some("Draw"))
+    } else if text.contains("スコア") || text.contains("score") {
+        Ok(Some("Score"))
+    } else {
+        non_empty(value)
+            .map(|other| Err(format!("Unknown special heart '{}'.", other)))
+            .transpose()
+    }
+}
+
+/// Splits a list value such as the groups of a card on line breaks and slashes.
+fn parse_list(value: &str) -> Vec<Value> {
+    value
+        .split(['\n', '/', '／', '、'])
+        .map(collapse_whitespace)
+        .filter(|item| non_empty(item).is_some())
+        .map(Value::String)
+        .collect()
+}
+
+/// Resolves the card image of the page, an `<img>` pointing into the card list.
+fn card_image_url(document: &Html) -> Option<String> {
+    let images = Selector::parse("img[src]").expect("valid selector");
+    let src = document
+        .select(&images)
+        .filter_map(|img| img.value().attr("src"))
+        .find(|src| src.contains("cardlist"))?;
+    Some(
+        if src.starts_with("http://") || src.starts_with("https://") {
+            src.to_string()
+        } else if let Some(rest) = src.strip_prefix("//") {
+            format!("https://{}", rest)
+        } else {
+            format!(
+                "{}/{}",
+                OFFICIAL_SITE,
+                src.trim_start_matches("../")
+                    .trim_start_matches("./")
+                    .trim_start_matches('/')
+            )
+        },
+    )
+}
+
+/// Converts a saved card detail page into a [`CreateCard`].
+///
+/// Like the CSV importer, the page is first shaped into the JSON document accepted by
+/// `POST /cards`, so that it goes through exactly the same validation.
+pub fn parse_page(html: &str) -> Result<CreateCard, String> {
+    let document = Html::parse_document(html);
+    let fields = collect_fields(&document);
+    let text = |field: Field| {
+        fields
+            .get(&field)
+            .map(|value| rich_text(*value, false))
+            .filter(|value| non_empty(value).is_some())
+    };
+    let integer = |field: Field, label: &str| -> Result<Option<i64>, String> {
+        text(field)
+            .map(|value| {
+                let value = normalize_digits(value.trim());
+                value
+                    .parse::<i64>()
+                    .map_err(|_| format!("Field '{}' must be an integer, got '{}'.", label, value))
+            })
+            .transpose()
+    };
+
+    let mut card = Map::new();
+
+    let mut identifier = text(Field::Identifier)
+        .map(|value| collapse_whitespace(&value))
+        .ok_or("Missing card number (カード番号).")?;
+    if let Some(rarity) = text(Field::Rarity).map(|value| collapse_whitespace(&value))
+        && !identifier.ends_with(&format!("-{}", rarity))
+    {
+        identifier = format!("{}-{}", identifier, rarity);
+    }
+    card.insert("card_identifier".to_string(), Value::String(identifier));
+
+    let name = text(Field::Name).or_else(|| {
+        let headings = Selector::parse("h1, h2, h3").expect("valid selector");
+        document
+            .select(&headings)
+            .map(|heading| heading.text().collect::<String>())
+            .find(|heading| non_empty(heading).is_some())
+    });
+    let name = name.ok_or("Missing card name (カード名).")?;
+    card.insert(
+        "name".to_string(),
+        Value::String(collapse_whitespace(&name)),
+    );
+
+    let card_type = text(Field::CardType).ok_or("Missing card type (カードタイプ).")?;
+    card.insert(
+        "card_type".to_string(),
+        Value::String(parse_card_type(&card_type)?.to_string()),
+    );
+
+    for (field, key, label) in [
+        (Field::Cost, "cost", "コスト"),
+        (Field::Blades, "blades", "ブレード"),
+        (Field::Score, "score", "スコア"),
+    ] {
+        if let Some(value) = integer(field, label)? {
+            card.insert(key.to_string(), Value::from(value));
+        }
+    }
+
+    if let Some(value) = text(Field::Hearts) {
+        let hearts = parse_hearts(&value)?;
+        if !hearts.is_empty() {
+            card.insert("hearts".to_string(), Value::Object(hearts));
+        }
+    }
+    if let Some(color) = text(Field::BladeHeart).and_then(|value| parse_blade_heart(&value)) {
+        card.insert("blade_heart".to_string(), Value::String(color.to_string()));
+    }
+    if let Some(value) = text(Field::SpecialHeart)
+        && let Some(special) = parse_special_heart(&value)?
+    {
+        card.insert(
+            "special_heart".to_string(),
+            Value::String(special.to_string()),
+        );
+    }
+
+    for (field, key) in [(Field::Groups, "groups"), (Field::Units, "units")] {
+        let items = text(field)
+            .map(|value| parse_list(&value))
+            .unwrap_or_default();
+        card.insert(key.to_string(), Value::Array(items));
+    }
+
+    let skills: Vec<Value> = fields
+        .get(&Field::Skills)
+        .map(|value| rich_text(*value, true))
+        .unwrap_or_default()
+        .lines()
+        .map(collapse_whitespace)
+        .filter(|line| non_empty(line).is_some())
+        .map(Value::String)
+        .collect();
+    card.insert("skills".to_string(), Value::Array(skills));
+
+    if let Some(image_url) = card_image_url(&document) {
+        card.insert("image_url".to_string(), Value::String(image_url));
+    }
+
+    serde_json::from_value(Value::Object(card)).map_err(|e| e.to_string())
+}
+
+/// Parses saved card detail pages into [`CreateCard`] payloads.
+///
+/// Every page is parsed even if earlier pages fail. Errors report pages by their
+/// position in the request, counting from 1.
+pub fn parse_pages(pages: &[String]) -> Result<Vec<CreateCard>, Vec<ImportRowError>> {
+    let mut cards = Vec::new();
+    let mut errors = Vec::new();
+    for (index, page) in pages.iter().enumerate() {
+        match parse_page(page) {
+            Ok(card) => cards.push(card),
+            Err(message) => errors.push(ImportRowError {
+                row: index + 1,
+                message,
+            }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(cards)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Records a change if the existing and incoming values differ.
+fn compare<T: Serialize + PartialEq>(
+    changes: &mut Vec<FieldChange>,
+    field: &str,
+    existing: T,
+    incoming: T,
+) {
+    if existing != incoming {
+        changes.push(FieldChange {
+            field: field.to_string(),
+            existing: serde_json::to_value(existing).unwrap_or_default(),
+            incoming: serde_json::to_value(incoming).unwrap_or_default(),
+        });
+    }
+}
+
+fn sorted(mut items: Vec<String>) -> Vec<String> {
+    items.sort();
+    items
+}
+
+/// Lists the fields in which an imported card differs from the card already stored
+/// under the same number.
+///
+/// Names and groups are canonicalized through the variant caches first, exactly as
+/// they would be when the card is created, so spelling variants are not reported.
+pub fn diff_card(
+    existing: &FullCard,
+    incoming: &CreateCard,
+    name_variant_cache: &HashMap<String, String>,
+    group_variant_cache: &HashMap<String, String>,
+) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    let name = name_variant_cache
+        .get(&incoming.name)
+        .unwrap_or(&incoming.name);
+    compare(&mut changes, "name", &existing.base.name, name);
+    compare(
+        &mut changes,
+        "card_type",
+        existing.base.card_type,
+        incoming.card_type,
+    );
+
+    let groups = incoming
+        .groups
+        .iter()
+        .map(|group| group_variant_cache.get(group).unwrap_or(group).clone())
+        .collect();
+    compare(
+        &mut changes,
+        "groups",
+        sorted(existing.groups.clone()),
+        sorted(groups),
+    );
+    compare(
+        &mut changes,
+        "units",
+        sorted(existing.units.clone()),
+        sorted(incoming.units.clone()),
+    );
+    compare(&mut changes, "skills", &existing.skills, &incoming.skills);
+
+    let (hearts, cost, blades, score, blade_heart, special_heart) = match &incoming.type_specifics {
+        Some(CreateCardTypeSpecifics::Character(c)) => (
+            c.hearts.clone(),
+            Some(c.cost),
+            Some(c.blades),
+            None,
+            c.blade_heart,
+            None,
+        ),
+        Some(CreateCardTypeSpecifics::Live(l)) => (
+            l.hearts.clone(),
+            None,
+            None,
+            Some(l.score),
+            l.blade_heart,
+            l.special_heart,
+        ),
+        None => (HashMap::new(), None, None, None, None, None),
+    };
+    compare(&mut changes, "hearts", &existing.hearts, &hearts);
+    let existing_specifics = match &existing.type_specifics {
+        Some(CardTypeSpecifics::Character(c)) => {
+            (Some(c.cost), Some(c.blades), None, c.blade_heart, None)
+        }
+        Some(CardTypeSpecifics::Live(l)) => {
+            (None, None, Some(l.score), l.blade_heart, l.special_heart)
+        }
+        None => (None, None, None, None, None),
+    };
+    compare(&mut changes, "cost", existing_specifics.0, cost);
+    compare(&mut changes, "blades", existing_specifics.1, blades);
+    compare(&mut changes, "score", existing_specifics.2, score);
+    compare(
+        &mut changes,
+        "blade_heart",
+        existing_specifics.3,
+        blade_heart,
+    );
+    compare(
+        &mut changes,
+        "special_heart",
+        existing_specifics.4,
+        special_heart,
+    );
+
+    match existing
+        .printings
+        .iter()
+        .find(|printing| printing.rarity_code == incoming.rarity_code)
+    {
+        Some(printing) => {
+            // Pages saved without their images should not erase a known image.
+            if incoming.image_url.is_some() {
+                compare(
+                    &mut changes,
+                    "image_url",
+                    &printing.image_url,
+                    &incoming.image_url,
+                );
+            }
+        }
+        None => changes.push(FieldChange {
+            field: "rarity_code".to_string(),
+            existing: existing
+                .printings
+                .iter()
+                .map(|printing| printing.rarity_code.clone())
+                .collect(),
+            incoming: Value::String(incoming.rarity_code.clone()),
+        }),
+    }
+
+    changes
+}
+
+/// The result of comparing imported cards against the database.
+#[derive(Debug)]
+pub struct ImportPlan {
+    /// The diff of every imported card, in import order.
+    pub diff: Vec<ImportDiffEntry>,
+    /// The cards that are not stored yet, to be created.
+    pub new_cards: Vec<CreateCard>,
+    /// The row of each new card, to report creation errors against.
+    pub new_rows: Vec<usize>,
+}
+
+/// Compares imported cards against the cards stored under the same numbers.
+///
+/// Cards that are not stored yet are set aside for creation; the others are diffed
+/// with [`diff_card`].
+pub async fn plan_import(
+    pool: &Pool,
+    name_variant_cache: &HashMap<String, String>,
+    group_variant_cache: &HashMap<String, String>,
+    cards: Vec<CreateCard>,
+) -> Result<ImportPlan, sqlx::Error> {
+    let mut plan = ImportPlan {
+        diff: Vec::with_capacity(cards.len()),
+        new_cards: Vec::new(),
+        new_rows: Vec::new(),
+    };
+    for (index, card) in cards.into_iter().enumerate() {
+        let row = index + 1;
+        let card_identifier = format!(
+            "{}-{}-{}-{}",
+            card.series_code, card.set_code, card.number_in_set, card.rarity_code
+        );
+        let existing =
+            db::find_card_id(pool, &card.series_code, &card.set_code, &card.number_in_set).await?;
+
+        let entry = match existing {
+            Some(card_id) => {
+                let stored = db::fetch_full_card(pool, card_id).await?;
+                let changes = diff_card(&stored, &card, name_variant_cache, group_variant_cache);
+                ImportDiffEntry {
+                    row,
+                    card_identifier,
+                    status: if changes.is_empty() {
+                        ImportDiffStatus::Unchanged
+                    } else {
+                        ImportDiffStatus::Changed
+                    },
+                    card_id: Some(card_id),
+                    changes,
+                }
+            }
+            None => {
+                plan.new_rows.push(row);
+                plan.new_cards.push(card);
+                ImportDiffEntry {
+                    row,
+                    card_identifier,
+                    status: ImportDiffStatus::New,
+                    card_id: None,
+                    changes: Vec::new(),
+                }
+            }
+        };
+        plan.diff.push(entry);
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BladeHeartColor, CardType, HeartColor, SpecialHeart};
+
+    #[test]
+    fn test_parse_hearts() {
+        let hearts = parse_hearts("赤×2 緑×1").unwrap();
+        assert_eq!(hearts["Red"], 2);
+        assert_eq!(hearts["Green"], 1);
+
+        // Icons rendered through their file names, with full-width counts.
+        let hearts = parse_hearts(" heart_01  ×２  heart_01  heart_00 ×３").unwrap();
+        assert_eq!(hearts["Pink"], 3);
+        assert_eq!(hearts["Gray"], 3);
+
+        assert!(parse_hearts("×2").is_err());
+        assert!(parse_hearts("赤×9223372036854775807 赤×1").is_err());
+    }
+
+    #[test]
+    fn test_parse_page_table_layout() {
+        let html = r#"
+            <html><body>
+            <h1>ラブライブ！シリーズ オフィシャルカードゲーム</h1>
+            <img src="/wp-content/images/cardlist/BP01/PL!-bp1-019-L.png">
+            <table>
+              <tr><th>カード番号</th><td>PL!-bp1-019</td></tr>
+              <tr><th>レアリティ</th><td>L</td></tr>
+              <tr><th>カード名</th><td>Snow halation</td></tr>
+              <tr><th>カードタイプ</th><td>ライブ</td></tr>
+              <tr><th>作品名</th><td>ラブライブ！</td></tr>
+              <tr><th>スコア</th><td>３</td></tr>
+              <tr><th>必要ハート</th><td><img alt="桃" src="heart_01.png">×2<img alt="灰" src="heart_00.png">×4</td></tr>
+              <tr><th>ブレードハート</th><td><img src="/img/b_heart_all.png"></td></tr>
+              <tr><th>特殊ハート</th><td>ドロー</td></tr>
+              <tr><th>テキスト</th><td>-</td></tr>
+            </table>
+            </body></html>
+        "#;
+        let card = parse_page(html).unwrap();
+        assert_eq!(card.card_type, CardType::Live);
+        assert_eq!(card.name, "Snow halation");
+        assert_eq!(card.rarity_code, "L");
+        assert_eq!(card.set_code, "bp1");
+        assert_eq!(card.groups, vec!["ラブライブ！"]);
+        assert!(card.skills.is_empty());
+        assert_eq!(
+            card.image_url.as_deref(),
+            Some(
+                "https://llofficial-cardgame.com/wp-content/images/cardlist/BP01/PL!-bp1-019-L.png"
+            )
+        );
+        match card.type_specifics {
+            Some(CreateCardTypeSpecifics::Live(l)) => {
+                assert_eq!(l.score, 3);
+                assert_eq!(l.hearts.get(&HeartColor::Pink), Some(&2));
+                assert_eq!(l.hearts.get(&HeartColor::Gray), Some(&4));
+                assert_eq!(l.blade_heart, Some(BladeHeartColor::All));
+                assert_eq!(l.special_heart, Some(SpecialHeart::Draw));
+            }
+            _ => panic!("Expected Live type specifics"),
+        }
+    }
+
+    #[test]
+    fn test_parse_page_reports_missing_fields() {
+        let errors = parse_pages(&[
+            "<dl><dt>カード名</dt><dd>高坂穂乃果</dd></dl>".to_string(),
+            "<dl><dt>カード番号</dt><dd>PL!-bp1-001-R</dd><dt>カード名</dt><dd>高坂穂乃果</dd><dt>カードタイプ</dt><dd>魔法</dd></dl>".to_string(),
+        ])
+        .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].row, 1);
+        assert!(errors[0].message.contains("カード番号"));
+        assert_eq!(errors[1].row, 2);
+        assert!(errors[1].message.contains("Unknown card type"));
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{Pool, db, perceptual_hash};
+use image::{ImageFormat, codecs::jpeg::JpegEncoder, imageops::FilterType};
+use sha2::{Digest, Sha256};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// The path under which stored images are served, followed by their hash.
+pub const IMAGE_ROUTE: &str = "/images/";
+
+/// Maps an image URL to the file holding that image in the local image store.
+///
+/// Images are stored under the final path segment of their URL, so a folder of images
+/// mirrored from the official card list can be used as is. Returns `None` if the URL
+/// has no usable file name.
+pub fn local_path(image_dir: &Path, image_url: &str) -> Option<PathBuf> {
+    let path = image_url.split(['?', '#']).next().unwrap_or_default();
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    if file_name.is_empty() || file_name == "." || file_name == ".." || file_name.contains('\\') {
+        return None;
+    }
+    Some(image_dir.join(file_name))
+}
+
+/// Detects the content type of an image, or `None` if it is not a supported format.
+///
+/// Only the formats the proxy sheet renderer can decode are accepted.
+pub fn content_type(bytes: &[u8]) -> Option<&'static str> {
+    match image::guess_format(bytes).ok()? {
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        ImageFormat::WebP => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Hashes the content of an image. Stored images are named after this hash.
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Checks whether a string is a content hash, and so safe to use as a file name.
+pub fn is_content_hash(value: &str) -> bool {
+    value.len() == 64
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// A resized copy of a stored image.
+///
+/// Variants are encoded as JPEG, which keeps card art small; the WebP encoder available
+/// to us is lossless only and produces far larger files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageVariant {
+    /// For card grids.
+    Thumbnail,
+    /// For card detail pages.
+    Medium,
+}
+
+impl ImageVariant {
+    pub const ALL: [ImageVariant; 2] = [ImageVariant::Thumbnail, ImageVariant::Medium];
+
+    fn suffix(self) -> &'static str {
+        match self {
+            ImageVariant::Thumbnail => "thumb",
+            ImageVariant::Medium => "medium",
+        }
+    }
+
+    /// The width of the variant. Images narrower than this are not upscaled.
+    pub fn max_width(self) -> u32 {
+        match self {
+            ImageVariant::Thumbnail => 200,
+            ImageVariant::Medium => 600,
+        }
+    }
+
+    /// The file name of this variant of a stored image.
+    pub fn file_name(self, hash: &str) -> String {
+        format!("{}-{}", hash, self.suffix())
+    }
+}
+
+/// The JPEG quality of resized variants.
+const VARIANT_QUALITY: u8 = 85;
+
+/// Checks whether a file name refers to a stored image or one of its variants.
+pub fn is_stored_image_name(name: &str) -> bool {
+    is_content_hash(name)
+        || ImageVariant::ALL.iter().any(|variant| {
+            name.strip_suffix(variant.suffix())
+                .and_then(|rest| rest.strip_suffix('-'))
+                .is_some_and(is_content_hash)
+        })
+}
+
+/// Extracts the content hash from the URL of a stored image.
+pub fn hash_from_url(image_url: &str) -> Option<&str> {
+    image_url
+        .strip_prefix(IMAGE_ROUTE)
+        .filter(|hash| is_content_hash(hash))
+}
+
+/// The URL of a variant of a stored image.
+pub fn variant_url(hash: &str, variant: ImageVariant) -> String {
+    image_url(&variant.file_name(hash))
+}
+
+/// Renders a variant of an image: scaled down to the variant's width and encoded as JPEG.
+pub fn render_variant(bytes: &[u8], variant: ImageVariant) -> image::ImageResult<Vec<u8>> {
+    let image = image::load_from_memory(bytes)?;
+    let image = if image.width() > variant.max_width() {
+        let height = (image.height() as u64 * variant.max_width() as u64 / image.width() as u64)
+            .max(1) as u32;
+        image.resize_exact(variant.max_width(), height, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    let mut encoded = Vec::new();
+    JpegEncoder::new_with_quality(&mut encoded, VARIANT_QUALITY).encode_image(&image.to_rgb8())?;
+    Ok(encoded)
+}
+
+/// Writes a file so that readers never observe it partially written.
+///
+/// Every writer gets a temporary file of its own, so concurrent writes of the same file
+/// never interleave; the last one to finish wins.
+async fn write_atomically(image_dir: &Path, file_name: &str, bytes: &[u8]) -> std::io::Result<()> {
+    tokio::fs::create_dir_all(image_dir).await?;
+    let image_dir = image_dir.to_path_buf();
+    let path = image_dir.join(file_name);
+    let bytes = bytes.to_vec();
+    tokio::task::spawn_blocking(move || {
+        let mut file = tempfile::NamedTempFile::new_in(&image_dir)?;
+        file.write_all(&bytes)?;
+        file.persist(&path).map_err(|e| e.error)?;
+        Ok(())
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// The URLs of the variants of a stored image, as reported on a printing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantUrls {
+    pub thumbnail_url: String,
+    pub medium_url: String,
+}
+
+/// Renders and stores every variant of a stored image, replacing existing ones.
+pub async fn store_variants(
+    image_dir: &Path,
+    hash: &str,
+    bytes: Vec<u8>,
+) -> std::io::Result<VariantUrls> {
+    // Resizing is CPU bound, so keep it off the async runtime.
+    let variants = tokio::task::spawn_blocking(move || {
+        ImageVariant::ALL
+            .iter()
+            .map(|&variant| render_variant(&bytes, variant).map(|encoded| (variant, encoded)))
+            .collect::<image::ImageResult<Vec<_>>>()
+    })
+    .await
+    .map_err(std::io::Error::other)?
+    .map_err(std::io::Error::other)?;
+
+    for (variant, encoded) in &variants {
+        write_atomically(image_dir, &variant.file_name(hash), encoded).await?;
+    }
+    Ok(VariantUrls {
+        thumbnail_url: variant_url(hash, ImageVariant::Thumbnail),
+        medium_url: variant_url(hash, ImageVariant::Medium),
+    })
+}
+
+/// Computes the perceptual hash of an image. Decoding is CPU bound, so it is kept off
+/// the async runtime.
+pub async fn hash_off_runtime(bytes: Vec<u8>) -> std::io::Result<u64> {
+    tokio::task::spawn_blocking(move || perceptual_hash::hash_image_bytes(&bytes))
+        .await
+        .map_err(std::io::Error::other)?
+        .map_err(std::io::Error::other)
+}
+
+/// The outcome of [`regenerate_variants`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RegenerationReport {
+    /// The number of distinct stored images whose variants and hashes were regenerated.
+    pub images: usize,
+    /// The number of printings that were updated.
+    pub printings: usize,
+    /// The hashes of stored images whose original file is missing or cannot be decoded.
+    pub failed: Vec<String>,
+}
+
+/// Re-renders the variants and recomputes the perceptual hash of every locally stored
+/// printing image, and updates the printings accordingly. This is needed after the
+/// variant sizes were changed, and for images stored before variants or hashes existed.
+pub async fn regenerate_variants(
+    pool: &Pool,
+    image_dir: &Path,
+) -> db::DbResult<RegenerationReport> {
+    let mut printings_by_hash: Vec<(String, Vec<i64>)> = Vec::new();
+    for (printing_id, image_url) in db::fetch_locally_stored_printings(pool).await? {
+        let Some(hash) = hash_from_url(&image_url) else {
+            continue;
+        };
+        match printings_by_hash
+            .iter_mut()
+            .find(|(known, _)| known == hash)
+        {
+            Some((_, ids)) => ids.push(printing_id),
+            None => printings_by_hash.push((hash.to_string(), vec![printing_id])),
+        }
+    }
+
+    let mut report = RegenerationReport::default();
+    for (hash, printing_ids) in printings_by_hash {
+        let Ok(bytes) = tokio::fs::read(image_dir.join(&hash)).await else {
+            report.failed.push(hash);
+            continue;
+        };
+        let perceptual_hash = hash_off_runtime(bytes.clone()).await;
+        let (Ok(urls), Ok(perceptual_hash)) = (
+            store_variants(image_dir, &hash, bytes).await,
+            perceptual_hash,
+        ) else {
+            report.failed.push(hash);
+            continue;
+        };
+
+        report.images += 1;
+        for printing_id in printing_ids {
+            db::set_printing_image_urls(
+                pool,
+                None,
+                printing_id,
+                &image_url(&hash),
+                Some(&urls.thumbnail_url),
+                Some(&urls.medium_url),
+                perceptual_hash,
+            )
+            .await?;
+            report.printings += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// The URL of a stored image. It resolves back to the stored file through [`local_path`].
+pub fn image_url(hash: &str) -> String {
+    format!("{}{}", IMAGE_ROUTE, hash)
+}
+
+/// Resolves an image URL against the public URL of the server, so that it can be loaded
+/// from outside of it. URLs that are already absolute are returned as they are.
+pub fn absolute_url(public_base_url: &str, image_url: &str) -> String {
+    if image_url.starts_with('/') {
+        format!("{}{}", public_base_url, image_url)
+    } else {
+        image_url.to_string()
+    }
+}
+
+/// Stores an image under its content hash and returns the hash.
+///
+/// An image that is already stored is not written again, so uploading the same image
+/// for several printings keeps a single copy.
+pub async fn store(image_dir: &Path, bytes: &[u8]) -> std::io::Result<String> {
+    let hash = content_hash(bytes);
+    let path = image_dir.join(&hash);
+    if tokio::fs::try_exists(&path).await? {
+        return Ok(hash);
+    }
+
+    write_atomically(image_dir, &hash, bytes).await?;
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_path_uses_last_segment() {
+        let dir = Path::new("images");
+        assert_eq!(
+            local_path(
+                dir,
+                "https://llofficial-cardgame.com/cardlist/BP01/PL!-BP01-001-R.png?v=2"
+            ),
+            Some(dir.join("PL!-BP01-001-R.png"))
+        );
+        assert_eq!(local_path(dir, "https://example.com/"), None);
+        assert_eq!(local_path(dir, "https://example.com/.."), None);
+    }
+
+    #[test]
+    fn test_stored_image_url_resolves_to_hash() {
+        let hash = content_hash(b"image");
+        assert!(is_content_hash(&hash));
+        assert!(!is_content_hash("../etc/passwd"));
+        assert!(is_stored_image_name(
+            &ImageVariant::Thumbnail.file_name(&hash)
+        ));
+        assert!(!is_stored_image_name(&format!("{}-large", hash)));
+        assert_eq!(hash_from_url(&image_url(&hash)), Some(hash.as_str()));
+        assert_eq!(
+            local_path(Path::new("images"), &image_url(&hash)),
+            Some(Path::new("images").join(&hash))
+        );
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::RgbImage::new(width, height)
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+        png.into_inner()
+    }
+
+    #[test]
+    fn test_render_variant_scales_down_only() {
+        let thumbnail = render_variant(&png(630, 880), ImageVariant::Thumbnail).unwrap();
+        assert_eq!(content_type(&thumbnail), Some("image/jpeg"));
+        let thumbnail = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (200, 279));
+
+        let medium = render_variant(&png(20, 28), ImageVariant::Medium).unwrap();
+        assert_eq!(image::load_from_memory(&medium).unwrap().width(), 20);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::Pool;
+use serde::Serialize;
+
+/// A single inconsistency found by [`check_database`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IntegrityProblem {
+    /// The name of the check that failed.
+    pub check: &'static str,
+    pub detail: String,
+}
+
+/// Checks that return one row per problem, with a single text column describing it.
+///
+/// SQLite does not enforce most of these relations, as the tables reference each other
+/// by code or name rather than through foreign keys.
+const CHECKS: [(&str, &str); 8] = [
+    (
+        "card_set",
+        "SELECT series_code || '-' || set_code || '-' || number_in_set || ' has unknown set ' || set_code
+         FROM cards WHERE set_code NOT IN (SELECT set_code FROM sets)",
+    ),
+    (
+        "card_name",
+        "SELECT series_code || '-' || set_code || '-' || number_in_set || ' has no name'
+         FROM cards WHERE name_id NOT IN (SELECT id FROM names)",
+    ),
+    (
+        "card_type_data",
+        "SELECT c.series_code || '-' || c.set_code || '-' || c.number_in_set || ' is a '
+             || c.card_type || ' card without matching type data'
+         FROM cards c
+         WHERE (c.card_type = 'Character') <> EXISTS (SELECT 1 FROM character_cards WHERE card_id = c.id)
+            OR (c.card_type = 'Live') <> EXISTS (SELECT 1 FROM live_cards WHERE card_id = c.id)",
+    ),
+    (
+        "card_hearts",
+        "SELECT series_code || '-' || set_code || '-' || number_in_set || ' is a '
+             || card_type || ' card without hearts'
+         FROM cards
+         WHERE card_type IN ('Character', 'Live')
+           AND id NOT IN (SELECT card_id FROM card_hearts WHERE count > 0)",
+    ),
+    (
+        "card_printings",
+        "SELECT series_code || '-' || set_code || '-' || number_in_set || ' has no printings'
+         FROM cards WHERE id NOT IN (SELECT card_id FROM printings)",
+    ),
+    (
+        "placeholder_rarity",
+        "SELECT 'rarity ' || rarity_code || ' has a placeholder display name and sort rank'
+         FROM rarities WHERE display_name = rarity_code AND sort_rank = 1000",
+    ),
+    (
+        "group_variant",
+        "SELECT 'group variant ' || variant_name || ' maps to unknown group ' || canonical_name
+         FROM group_variants WHERE canonical_name NOT IN (SELECT name FROM groups)",
+    ),
+    (
+        "orphaned_links",
+        "SELECT 'card_groups row for missing card ' || card_id FROM card_groups
+             WHERE card_id NOT IN (SELECT id FROM cards)
+         UNION ALL
+         SELECT 'card_units row for missing card ' || card_id FROM card_units
+             WHERE card_id NOT IN (SELECT id FROM cards)
+         UNION ALL
+         SELECT 'card_skills row for missing card ' || card_id FROM card_skills
+             WHERE card_id NOT IN (SELECT id FROM cards)
+         UNION ALL
+         SELECT 'card_hearts row for missing card ' || card_id FROM card_hearts
+             WHERE card_id NOT IN (SELECT id FROM cards)",
+    ),
+];
+
+/// Checks the database for corruption and for data that violates the assumptions of the
+/// API, such as cards in unknown sets or Character cards without hearts.
+///
+/// Returns every problem found; an empty list means the database is consistent.
+pub async fn check_database(pool: &Pool) -> Result<Vec<IntegrityProblem>, sqlx::Error> {
+    let mut problems = Vec::new();
+
+    let integrity: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?;
+    problems.extend(
+        integrity
+            .into_iter()
+            .filter(|row| row != "ok")
+            .map(|detail| IntegrityProblem {
+                check: "integrity_check",
+                detail,
+            }),
+    );
+
+    let foreign_keys: Vec<(String, Option<i64>, String)> =
+        sqlx::query_as("SELECT \"table\", rowid, parent FROM pragma_foreign_key_check")
+            .fetch_all(pool)
+            .await?;
+    problems.extend(
+        foreign_keys
+            .into_iter()
+            .map(|(table, rowid, parent)| IntegrityProblem {
+                check: "foreign_key_check",
+                detail: format!(
+                    "{} row {} references a missing {}",
+                    table,
+                    rowid.map_or_else(|| "?".to_string(), |rowid| rowid.to_string()),
+                    parent
+                ),
+            }),
+    );
+
+    for (check, query) in CHECKS {
+        let details: Vec<String> = sqlx::query_scalar(query).fetch_all(pool).await?;
+        problems.extend(
+            details
+                .into_iter()
+                .map(|detail| IntegrityProblem { check, detail }),
+        );
+    }
+
+    Ok(problems)
+}
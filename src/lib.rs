@@ -1,15 +1,32 @@
-use crate::models::RarityType;
+use crate::models::Rarity;
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     routing::{get, post},
 };
 use sqlx::sqlite::SqlitePoolOptions;
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 use tokio::sync::RwLock;
 
+pub mod auth;
+pub mod csv_import;
 pub mod db;
+pub mod dump;
 pub mod handlers;
+pub mod hearts;
+pub mod history;
+pub mod html_import;
+pub mod image_store;
+pub mod integrity;
+pub mod localization;
 pub mod models;
+pub mod perceptual_hash;
+pub mod probability;
+pub mod proxy;
+pub mod rate_limit;
+pub mod simulation;
+pub mod tabletop;
+pub mod trades;
 
 /// A type alias for the database connection pool.
 pub type Pool = sqlx::SqlitePool;
@@ -18,13 +35,55 @@ pub type Pool = sqlx::SqlitePool;
 #[derive(Clone)]
 pub struct ApiState {
     pub pool: Pool,
-    pub rarity_cache: Arc<RwLock<HashMap<String, models::RarityType>>>,
+    pub rarity_cache: Arc<RwLock<HashMap<String, models::Rarity>>>,
     pub name_variant_cache: Arc<RwLock<HashMap<String, String>>>,
     pub group_variant_cache: Arc<RwLock<HashMap<String, String>>>,
     pub sets_cache: Arc<RwLock<Vec<models::SetResponse>>>,
     pub groups_cache: Arc<RwLock<Vec<String>>>,
     pub units_cache: Arc<RwLock<Vec<String>>>,
     pub names_cache: Arc<RwLock<Vec<String>>>,
+    /// The directory of the local image store.
+    pub image_dir: PathBuf,
+    /// A TrueType font used for text-only proxies, so that Japanese text can be printed.
+    pub proxy_font: Option<PathBuf>,
+    /// The URL the server is publicly reached at, e.g. `https://cards.example.com`, used
+    /// to turn the paths of stored images into absolute URLs in deck exports.
+    pub public_base_url: Option<String>,
+    /// The per-client request quotas and their current usage.
+    pub rate_limiter: Arc<rate_limit::RateLimiter>,
+}
+
+impl ApiState {
+    /// Reloads every cache from the database, e.g. after the data was replaced wholesale.
+    pub async fn reload_caches(&self) -> Result<(), sqlx::Error> {
+        *self.rarity_cache.write().await = rarity_map(db::fetch_all_rarities(&self.pool).await?);
+
+        let name_variants: Vec<(String, String)> =
+            sqlx::query_as("SELECT variant_name, canonical_name FROM name_variants")
+                .fetch_all(&self.pool)
+                .await?;
+        *self.name_variant_cache.write().await = name_variants.into_iter().collect();
+
+        let group_variants: Vec<(String, String)> =
+            sqlx::query_as("SELECT variant_name, canonical_name FROM group_variants")
+                .fetch_all(&self.pool)
+                .await?;
+        *self.group_variant_cache.write().await = group_variants.into_iter().collect();
+
+        *self.sets_cache.write().await = db::fetch_all_sets(&self.pool).await?;
+        *self.groups_cache.write().await = db::fetch_all_groups(&self.pool).await?;
+        *self.units_cache.write().await = db::fetch_all_units(&self.pool).await?;
+        *self.names_cache.write().await = db::fetch_all_card_names(&self.pool).await?;
+        Ok(())
+    }
+}
+
+/// Keys rarities by their code, for the rarity cache.
+fn rarity_map(rarities: Vec<Rarity>) -> HashMap<String, Rarity> {
+    rarities
+        .into_iter()
+        .map(|rarity| (rarity.rarity_code.clone(), rarity))
+        .collect()
 }
 
 /// The shared state for our application, including the database connection pool.
@@ -48,16 +107,10 @@ pub async fn create_app_state_with_pool(
 ) -> Result<ApiState, Box<dyn std::error::Error>> {
     // --- Populate the rarity cache at startup ---
     println!("Loading rarities into cache...");
-    let rarities: Vec<(String, RarityType)> =
-        sqlx::query_as("SELECT rarity_code, rarity_type FROM rarities")
-            .fetch_all(&pool)
-            .await?;
-    let rarity_cache: Arc<RwLock<HashMap<String, RarityType>>> =
-        Arc::new(RwLock::new(rarities.into_iter().collect()));
-    println!(
-        "-> Loaded {} rarity mappings.",
-        rarity_cache.read().await.len()
-    );
+    let rarity_cache: Arc<RwLock<HashMap<String, Rarity>>> = Arc::new(RwLock::new(rarity_map(
+        db::fetch_all_rarities(&pool).await?,
+    )));
+    println!("-> Loaded {} rarities.", rarity_cache.read().await.len());
 
     // --- Populate the name variant cache at startup ---
     println!("Loading name variants into cache...");
@@ -109,6 +162,19 @@ pub async fn create_app_state_with_pool(
     let names_cache = Arc::new(RwLock::new(names));
     println!("-> Loaded {} names.", names_cache.read().await.len());
 
+    // --- Read the optional file locations ---
+    let image_dir = std::env::var("IMAGE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("images"));
+    let proxy_font = std::env::var("PROXY_FONT_PATH").ok().map(PathBuf::from);
+    let public_base_url = std::env::var("PUBLIC_BASE_URL")
+        .ok()
+        .map(|url| url.trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty());
+    let rate_limiter = Arc::new(rate_limit::RateLimiter::new(
+        rate_limit::RateLimitConfig::from_env(),
+    ));
+
     Ok(ApiState {
         pool,
         rarity_cache,
@@ -118,6 +184,10 @@ pub async fn create_app_state_with_pool(
         groups_cache,
         units_cache,
         names_cache,
+        image_dir,
+        proxy_font,
+        public_base_url,
+        rate_limiter,
     })
 }
 
@@ -125,40 +195,119 @@ pub async fn create_app_state_with_pool(
 ///
 /// The router is configured with all the API endpoints and the shared application state.
 ///
+/// # Authorization
+///
+/// `GET` routes are public. Routes that change the catalog or collections take an API
+/// key as `Authorization: Bearer <token>`: creating, importing, renaming reference
+/// data, submitting translations and restoring card revisions requires the `editor`
+/// role, while deleting and restoring reference data, restoring dumps and managing keys
+/// requires `admin`.
+/// Reading the audit log and the trash, and creating collections, takes a key of any
+/// role, including `reader`. A collection and its want list can then only be changed
+/// with the key that created it, or by an admin.
+/// Requests without a valid key get `401 Unauthorized`, and keys with too low a role
+/// get `403 Forbidden`. The first admin key is issued with `llocg-admin keys issue`.
+///
+/// # Rate Limiting
+///
+/// Each client, identified by its API key or else its IP address, has separate token
+/// buckets for reads and writes, configured by [`rate_limit::RateLimitConfig::from_env`].
+/// Responses carry `RateLimit-*` headers, and requests over the limit get
+/// `429 Too Many Requests`.
+///
 /// # Endpoints
 ///
 /// ## Cards
 /// - `GET /cards`: [`handlers::cards::get_all`] - Get all cards. (Not Implemented)
 /// - `POST /cards`: [`handlers::cards::create`] - Create a new card. Body: [`models::CreateCard`].
-/// - `GET /cards/:id`: [`handlers::cards::get_by_id`] - Get a card by its ID. Returns: [`models::FullCard`].
+/// - `GET /cards/:id?lang`: [`handlers::cards::get_by_id`] - Get a card by its ID, localized into the language of `lang` or `Accept-Language`. Returns: [`models::FullCard`].
+/// - `GET /cards/:id/history`: [`handlers::history::get_history`] - List the revisions of a card, a snapshot after each change. Returns: `Vec<[`models::CardRevision`]>`.
+/// - `GET /cards/:id/history/diff?from&to`: [`handlers::history::diff`] - Compare two revisions of a card field by field. Query: [`models::RevisionDiffQuery`]. Returns: [`models::CardRevisionDiff`].
+/// - `POST /cards/:id/history/:revision/restore?lang`: [`handlers::history::restore`] - Reapply an earlier revision of a card, returning it localized. Returns: [`models::FullCard`].
 /// - `POST /cards/bulk`: [`handlers::cards::create_bulk`] - Create multiple cards in bulk. Body: `Vec<[`models::CreateCard`]>`.
+/// - `POST /cards/import/csv`: [`handlers::cards::import_csv`] - Import cards from a CSV sheet. Body: [`models::CsvImportRequest`].
+/// - `POST /cards/import/html`: [`handlers::cards::import_html`] - Import saved card detail pages of the official card list, with a diff against existing cards. Body: [`models::HtmlImportRequest`]. Returns: [`models::HtmlImportResponse`].
+/// - `GET /cards/export/csv`: [`handlers::cards::export_csv`] - Stream the catalog as CSV, one row per printing. Rows: [`models::CardExportRow`].
+/// - `GET /cards/export/ndjson`: [`handlers::cards::export_ndjson`] - Stream the catalog as JSON Lines, one object per printing. Rows: [`models::CardExportRow`].
 /// - `TODO`: `PUT /cards/:id` - Update a card.
 /// - `TODO`: `PATCH /cards/:id` - Partially update a card.
 /// - `TODO`: `DELETE /cards/:id` - Delete a card.
 /// - `TODO`: `GET /cards/search?query` - Advanced card search.
 ///
+/// ## Collections
+/// - `GET /collections`: [`handlers::collections::get_all`] - Get all collections. Returns: `Vec<[`models::Collection`]>`.
+/// - `POST /collections`: [`handlers::collections::add`] - Create a new collection. Body: [`models::CreateCollection`].
+/// - `GET /collections/:id?lang`: [`handlers::collections::get_by_id`] - Get a collection and its cards, localized. Returns: [`models::CollectionView`].
+/// - `DELETE /collections/:id`: [`handlers::collections::delete`] - Delete a collection.
+/// - `POST /collections/:id/add`: [`handlers::collections::add_cards`] - Add copies of a printing. Body: [`models::CollectionQuantity`].
+/// - `POST /collections/:id/remove`: [`handlers::collections::remove_cards`] - Remove copies of a printing. Body: [`models::CollectionQuantity`].
+/// - `PUT /collections/:id/items`: [`handlers::collections::set_quantities`] - Set owned quantities in bulk. Body: `Vec<[`models::CollectionQuantity`]>`.
+/// - `GET /collections/:id/completion`: [`handlers::collections::completion`] - Get set completion percentages. Returns: `Vec<[`models::SetCompletion`]>`.
+/// - `GET /collections/:id/wants`: [`handlers::collections::get_wants`] - Get a collection's want list. Returns: `Vec<[`models::Want`]>`.
+/// - `POST /collections/:id/wants`: [`handlers::collections::add_want`] - Add a printing or base card to the want list. Body: [`models::CreateWant`].
+/// - `DELETE /collections/:id/wants/:want_id`: [`handlers::collections::delete_want`] - Remove a want.
+/// - `GET /collections/:id/matches/:other_id?exclude_parallel`: [`handlers::collections::matches`] - Match surplus against wants between two collections. Returns: [`models::TradeMatch`].
+///
+/// ## Decks
+/// - `POST /decks/probability`: [`handlers::decks::probability`] - Compute draw probabilities for a deck. Body: [`models::ProbabilityRequest`]. Returns: [`models::ProbabilityResponse`].
+/// - `POST /decks/simulate`: [`handlers::decks::simulate`] - Simulate yells to estimate the chance of clearing a Live card. Body: [`models::SimulationRequest`]. Returns: [`models::SimulationResponse`].
+/// - `POST /decks/export/tts`: [`handlers::decks::export_tts`] - Export a deck as a Tabletop Simulator saved object. Body: [`models::TtsExportRequest`]. Returns: [`models::TtsSavedObject`].
+/// - `POST /decks/export/list`: [`handlers::decks::export_list`] - Export a deck as a plain card list. Body: [`models::DeckListRequest`]. Returns: `Vec<[`models::DeckListCard`]>`.
+/// - `POST /decks/export/pdf`: [`handlers::decks::export_pdf`] - Render a deck as a printable 3x3 proxy sheet PDF. Body: [`models::ProxySheetRequest`].
+///
+/// ## Lives
+/// - `POST /lives/check`: [`handlers::lives::check`] - Check whether members on stage can pay a Live card's hearts. Body: [`models::LiveCheckRequest`]. Returns: [`models::RequirementCheck`].
+///
+/// ## Images
+/// - `POST /printings/:id/image`: [`handlers::images::upload`] - Upload the image of a printing (raw PNG, JPEG or WebP body). Returns: [`models::Printing`].
+/// - `POST /printings/identify?limit&lang`: [`handlers::images::identify`] - Find the printings whose images look most like a photo (raw PNG, JPEG or WebP body), with their cards localized. Returns: `Vec<[`models::PrintingMatch`]>`.
+/// - `GET /images/:name`: [`handlers::images::serve`] - Serve a stored image by its content hash, or a variant as `:hash-thumb` or `:hash-medium`.
+///
+/// ## API Keys
+/// - `GET /api-keys`: [`handlers::api_keys::get_all`] - List all API keys, without their secrets. Returns: `Vec<[`models::ApiKey`]>`.
+/// - `POST /api-keys`: [`handlers::api_keys::issue`] - Issue a new API key. Body: [`models::CreateApiKey`]. Returns: [`models::IssuedApiKey`].
+/// - `DELETE /api-keys/:id`: [`handlers::api_keys::revoke`] - Revoke an API key.
+///
+/// ## Audit Log
+/// - `GET /audit-log?entity_kind&entity_key&actor&since&until&limit`: [`handlers::audit::get_log`] - Search recorded changes to cards and reference data. Returns: `Vec<[`models::AuditEntry`]>`.
+///
+/// ## Trash
+/// - `GET /trash`: [`handlers::trash::get_all`] - List deleted sets, groups and units. Returns: `Vec<[`models::TrashItem`]>`.
+/// - `POST /trash/:kind/:key/restore`: [`handlers::trash::restore`] - Restore a deleted `set`, `group` or `unit`.
+/// - `DELETE /trash/:kind/:key`: [`handlers::trash::purge`] - Permanently delete a `set`, `group` or `unit`.
+///
+/// ## Dump
+/// - `GET /dump`: [`handlers::dump::export`] - Export the whole database. Returns: [`models::DatabaseDump`].
+/// - `POST /dump`: [`handlers::dump::import`] - Restore a dump into an empty database. Body: [`models::DatabaseDump`].
+///
 /// ## Sets
-/// - `GET /sets`: [`handlers::sets::get_all`] - Get all card sets. Returns: `Vec<[`models::Set`]>`.
+/// - `GET /sets?sort&order`: [`handlers::sets::get_all`] - Get all card sets, sorted by their sort order or a field such as `release_date`. Query: [`models::SetListQuery`]. Returns: `Vec<[`models::SetResponse`]>`.
 /// - `POST /sets`: [`handlers::sets::add`] - Add a new card set. Body: [`models::CreateSet`].
-/// - `DELETE /sets/:set_code`: [`handlers::sets::delete`] - Delete a card set by its code.
+/// - `GET /sets/:set_code/checklist?lang`: [`handlers::sets::checklist`] - List every card and printing of a set in number order, with the numbers missing from its total and names localized. Returns: [`models::SetChecklist`].
+/// - `GET /sets/:set_code/rarities`: [`handlers::sets::rarities`] - Get the rarities a set declares, by rank. Returns: `Vec<[`models::Rarity`]>`.
+/// - `PUT /sets/:set_code/rarities`: [`handlers::sets::set_rarities`] - Replace the rarities a set declares; cards of it must then have one of them. Body: [`models::SetRarities`].
+/// - `PUT`/`PATCH /sets/:set_code`: [`handlers::sets::update`] - Change the code, name or other fields of a card set, moving its cards along. Body: [`models::UpdateSet`].
+/// - `DELETE /sets/:set_code`: [`handlers::sets::delete`] - Move a card set to the trash by its code.
 ///
 /// ## Groups
 /// - `GET /groups`: [`handlers::groups::get_all`] - Get all groups. Returns: `Vec<[`models::Group`]>`.
 /// - `POST /groups`: [`handlers::groups::add`] - Add a new group. Body: [`models::CreateGroup`].
-/// - `DELETE /groups/:name`: [`handlers::groups::delete`] - Delete a group by its name.
+/// - `PUT`/`PATCH /groups/:name`: [`handlers::groups::rename`] - Rename a group, optionally keeping the old name as a variant. Body: [`models::RenameGroup`].
+/// - `DELETE /groups/:name`: [`handlers::groups::delete`] - Move a group to the trash by its name.
 ///
 /// ## Units
 /// - `GET /units`: [`handlers::units::get_all`] - Get all units. Returns: `Vec<[`models::Unit`]>`.
 /// - `POST /units`: [`handlers::units::add`] - Add a new unit. Body: [`models::CreateUnit`].
-/// - `DELETE /units/:name`: [`handlers::units::delete`] - Delete a unit by its name.
+/// - `PUT`/`PATCH /units/:name`: [`handlers::units::rename`] - Rename a unit. Body: [`models::RenameUnit`].
+/// - `DELETE /units/:name`: [`handlers::units::delete`] - Move a unit to the trash by its name.
 ///
 /// ## Names
 /// - `GET /names`: [`handlers::names::get_all`] - Get all distinct canonical card names.
 ///
 /// ## Rarities
-/// - `GET /rarities`: [`handlers::rarities::get_all`] - Get all rarities.
+/// - `GET /rarities`: [`handlers::rarities::get_all`] - Get all rarities, by rank. Returns: `Vec<[`models::Rarity`]>`.
 /// - `POST /rarities`: [`handlers::rarities::add`] - Add a new rarity. Body: [`models::CreateRarity`].
-/// - `GET /rarities/:code`: [`handlers::rarities::get_by_code`] - Get a rarity by its code.
+/// - `GET /rarities/:code`: [`handlers::rarities::get_by_code`] - Get a rarity by its code. Returns: [`models::Rarity`].
 /// - `DELETE /rarities/:code`: [`handlers::rarities::delete`] - Delete a rarity by its code.
 ///
 /// ## Name Variants
@@ -170,6 +319,10 @@ pub async fn create_app_state_with_pool(
 /// - `GET /variants/groups`: [`handlers::variants::group_variants::get_all`] - Get all group variants.
 /// - `POST /variants/groups`: [`handlers::variants::group_variants::add`] - Add a new group variant. Body: [`models::CreateGroupVariant`].
 /// - `DELETE /variants/groups/:variant`: [`handlers::variants::group_variants::delete`] - Delete a group variant.
+///
+/// ## Translations
+/// - `GET /translations?kind&language`: [`handlers::translations::get_all`] - List translations of card names, groups, units, sets and skills. Query: [`models::TranslationQuery`]. Returns: `Vec<[`models::Translation`]>`.
+/// - `PUT /translations`: [`handlers::translations::submit`] - Submit the translation of a text into a language, replacing any earlier one. Body: [`models::Translation`].
 pub fn create_router(app_state: ApiState) -> Router {
     Router::new()
         // Card routes
@@ -178,7 +331,103 @@ pub fn create_router(app_state: ApiState) -> Router {
             get(handlers::cards::get_all).post(handlers::cards::create),
         )
         .route("/cards/bulk", post(handlers::cards::create_bulk))
+        .route("/cards/import/csv", post(handlers::cards::import_csv))
+        .route(
+            "/cards/import/html",
+            post(handlers::cards::import_html)
+                .layer(DefaultBodyLimit::max(handlers::cards::MAX_HTML_IMPORT_SIZE)),
+        )
+        .route("/cards/export/csv", get(handlers::cards::export_csv))
+        .route("/cards/export/ndjson", get(handlers::cards::export_ndjson))
         .route("/cards/:id", get(handlers::cards::get_by_id))
+        .route("/cards/:id/history", get(handlers::history::get_history))
+        .route("/cards/:id/history/diff", get(handlers::history::diff))
+        .route(
+            "/cards/:id/history/:revision/restore",
+            post(handlers::history::restore),
+        )
+        // Collection routes
+        .route(
+            "/collections",
+            get(handlers::collections::get_all).post(handlers::collections::add),
+        )
+        .route(
+            "/collections/:id",
+            get(handlers::collections::get_by_id).delete(handlers::collections::delete),
+        )
+        .route(
+            "/collections/:id/add",
+            post(handlers::collections::add_cards),
+        )
+        .route(
+            "/collections/:id/remove",
+            post(handlers::collections::remove_cards),
+        )
+        .route(
+            "/collections/:id/items",
+            axum::routing::put(handlers::collections::set_quantities),
+        )
+        .route(
+            "/collections/:id/completion",
+            get(handlers::collections::completion),
+        )
+        .route(
+            "/collections/:id/wants",
+            get(handlers::collections::get_wants).post(handlers::collections::add_want),
+        )
+        .route(
+            "/collections/:id/wants/:want_id",
+            axum::routing::delete(handlers::collections::delete_want),
+        )
+        .route(
+            "/collections/:id/matches/:other_id",
+            get(handlers::collections::matches),
+        )
+        // Deck analysis routes
+        .route("/decks/probability", post(handlers::decks::probability))
+        .route("/decks/simulate", post(handlers::decks::simulate))
+        .route("/decks/export/tts", post(handlers::decks::export_tts))
+        .route("/decks/export/list", post(handlers::decks::export_list))
+        .route("/decks/export/pdf", post(handlers::decks::export_pdf))
+        // Live routes
+        .route("/lives/check", post(handlers::lives::check))
+        // Image routes
+        .route(
+            "/printings/:id/image",
+            post(handlers::images::upload)
+                .layer(DefaultBodyLimit::max(handlers::images::MAX_IMAGE_SIZE)),
+        )
+        .route(
+            "/printings/identify",
+            post(handlers::images::identify)
+                .layer(DefaultBodyLimit::max(handlers::images::MAX_IMAGE_SIZE)),
+        )
+        .route("/images/:name", get(handlers::images::serve))
+        // API key routes
+        .route(
+            "/api-keys",
+            get(handlers::api_keys::get_all).post(handlers::api_keys::issue),
+        )
+        .route(
+            "/api-keys/:id",
+            axum::routing::delete(handlers::api_keys::revoke),
+        )
+        // Audit log routes
+        .route("/audit-log", get(handlers::audit::get_log))
+        // Trash routes
+        .route("/trash", get(handlers::trash::get_all))
+        .route("/trash/:kind/:key/restore", post(handlers::trash::restore))
+        .route(
+            "/trash/:kind/:key",
+            axum::routing::delete(handlers::trash::purge),
+        )
+        // Dump routes
+        .route(
+            "/dump",
+            get(handlers::dump::export)
+                .post(handlers::dump::import)
+                .layer(DefaultBodyLimit::max(handlers::dump::MAX_DUMP_SIZE)),
+        )
         // Set, Group, and Unit routes
         .route(
             "/sets",
@@ -186,7 +435,14 @@ pub fn create_router(app_state: ApiState) -> Router {
         )
         .route(
             "/sets/:set_code",
-            axum::routing::delete(handlers::sets::delete),
+            axum::routing::put(handlers::sets::update)
+                .patch(handlers::sets::update)
+                .delete(handlers::sets::delete),
+        )
+        .route("/sets/:set_code/checklist", get(handlers::sets::checklist))
+        .route(
+            "/sets/:set_code/rarities",
+            get(handlers::sets::rarities).put(handlers::sets::set_rarities),
         )
         .route(
             "/groups",
@@ -194,7 +450,9 @@ pub fn create_router(app_state: ApiState) -> Router {
         )
         .route(
             "/groups/:name",
-            axum::routing::delete(handlers::groups::delete),
+            axum::routing::put(handlers::groups::rename)
+                .patch(handlers::groups::rename)
+                .delete(handlers::groups::delete),
         )
         .route(
             "/units",
@@ -202,7 +460,9 @@ pub fn create_router(app_state: ApiState) -> Router {
         )
         .route(
             "/units/:name",
-            axum::routing::delete(handlers::units::delete),
+            axum::routing::put(handlers::units::rename)
+                .patch(handlers::units::rename)
+                .delete(handlers::units::delete),
         )
         // Name routes
         .route("/names", get(handlers::names::get_all))
@@ -235,5 +495,14 @@ pub fn create_router(app_state: ApiState) -> Router {
             "/variants/groups/:variant",
             axum::routing::delete(handlers::variants::group_variants::delete),
         )
+        // Translation routes
+        .route(
+            "/translations",
+            get(handlers::translations::get_all).put(handlers::translations::submit),
+        )
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            rate_limit::limit,
+        ))
         .with_state(app_state)
 }
@@ -0,0 +1,226 @@
+use crate::{
+    Pool, db,
+    models::{FullCard, LanguageQuery, SetChecklist, TranslationKind},
+};
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::{header, request::Parts},
+};
+use std::{collections::HashMap, convert::Infallible};
+
+/// The languages a response is localized into, most preferred first: the one given with
+/// `?lang=`, or else those of the `Accept-Language` header. Empty for the original text.
+pub struct Languages(pub Vec<String>);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Languages {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Infallible> {
+        let lang = Query::<LanguageQuery>::try_from_uri(&parts.uri)
+            .ok()
+            .and_then(|Query(query)| query.lang);
+        let (tags, refused) = match lang {
+            Some(lang) => (vec![lang], Vec::new()),
+            None => parts
+                .headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok())
+                .map(parse_accept_language)
+                .unwrap_or_default(),
+        };
+        Ok(Languages(with_fallbacks(&tags, &refused)))
+    }
+}
+
+/// Checks a language code, e.g. `en` or `zh-TW`, returning it in lowercase.
+pub fn normalize_language(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_ascii_lowercase();
+    let mut subtags = tag.split('-');
+    let primary = subtags.next()?;
+    let valid = (2..=8).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_lowercase())
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    valid.then_some(tag)
+}
+
+/// Lists the languages of an `Accept-Language` header by preference, leaving out the
+/// wildcard, followed by those refused with `q=0`.
+fn parse_accept_language(header: &str) -> (Vec<String>, Vec<String>) {
+    let (mut languages, refused): (Vec<(String, f32)>, Vec<_>) = header
+        .split(',')
+        .filter_map(|item| {
+            let mut params = item.split(';');
+            let tag = params.next()?.trim();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.parse().ok())
+                .unwrap_or(1.0);
+            Some((tag.to_string(), quality))
+        })
+        .filter(|(tag, _)| tag != "*")
+        .partition(|(_, quality)| *quality > 0.0);
+    // The sort is stable, so languages of equal quality keep their order.
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    (
+        languages.into_iter().map(|(tag, _)| tag).collect(),
+        refused.into_iter().map(|(tag, _)| tag).collect(),
+    )
+}
+
+/// Normalizes language codes, following each regional one with its base language, so
+/// that `en-US` falls back to `en` unless `en` is among the `refused` ones.
+fn with_fallbacks(tags: &[String], refused: &[String]) -> Vec<String> {
+    let refused: Vec<_> = refused
+        .iter()
+        .filter_map(|tag| normalize_language(tag))
+        .collect();
+    let mut languages = Vec::new();
+    for tag in tags.iter().filter_map(|tag| normalize_language(tag)) {
+        let base = tag.split('-').next().unwrap_or_default().to_string();
+        for language in [tag, base] {
+            if !languages.contains(&language) && !refused.contains(&language) {
+                languages.push(language);
+            }
+        }
+    }
+    languages
+}
+
+/// Replaces the name, set name, groups, units and skills of a card with their
+/// translations into `languages`, keeping the original text of any without one.
+pub async fn localize_card(
+    pool: &Pool,
+    card: &mut FullCard,
+    languages: &[String],
+) -> Result<(), sqlx::Error> {
+    localize_cards(pool, [card], languages).await
+}
+
+/// Localizes cards like [`localize_card`], looking the translations of all of them up
+/// with one query per kind of text, however many cards there are.
+pub async fn localize_cards<'a>(
+    pool: &Pool,
+    cards: impl IntoIterator<Item = &'a mut FullCard>,
+    languages: &[String],
+) -> Result<(), sqlx::Error> {
+    let mut cards: Vec<&mut FullCard> = cards.into_iter().collect();
+    if languages.is_empty() || cards.is_empty() {
+        return Ok(());
+    }
+
+    let names: Vec<&str> = cards.iter().map(|card| card.base.name.as_str()).collect();
+    let names = db::fetch_localized_texts(pool, TranslationKind::Name, &names, languages).await?;
+    let set_codes: Vec<&str> = cards
+        .iter()
+        .map(|card| card.base.set_code.as_str())
+        .collect();
+    let sets = db::fetch_localized_texts(pool, TranslationKind::Set, &set_codes, languages).await?;
+    for card in cards.iter_mut() {
+        translate(&names, &mut card.base.name);
+        if let Some(set_name) = sets.get(&card.base.set_code) {
+            card.set_name = set_name.clone();
+        }
+    }
+
+    for kind in [
+        TranslationKind::Group,
+        TranslationKind::Unit,
+        TranslationKind::Skill,
+    ] {
+        let keys: Vec<String> = cards
+            .iter_mut()
+            .flat_map(|card| texts_of(card, kind).clone())
+            .collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let translations = db::fetch_localized_texts(pool, kind, &keys, languages).await?;
+        for card in cards.iter_mut() {
+            for text in texts_of(card, kind).iter_mut() {
+                translate(&translations, text);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the set name and card names of a checklist with their translations into
+/// `languages`, keeping the original text of any without one.
+pub async fn localize_checklist(
+    pool: &Pool,
+    checklist: &mut SetChecklist,
+    languages: &[String],
+) -> Result<(), sqlx::Error> {
+    if languages.is_empty() {
+        return Ok(());
+    }
+
+    let sets = db::fetch_localized_texts(
+        pool,
+        TranslationKind::Set,
+        &[&checklist.set.set_code],
+        languages,
+    )
+    .await?;
+    if let Some(set_name) = sets.get(&checklist.set.set_code) {
+        checklist.set.name = set_name.clone();
+    }
+
+    let names: Vec<&str> = checklist
+        .cards
+        .iter()
+        .map(|card| card.name.as_str())
+        .collect();
+    let names = db::fetch_localized_texts(pool, TranslationKind::Name, &names, languages).await?;
+    for card in &mut checklist.cards {
+        translate(&names, &mut card.name);
+    }
+    Ok(())
+}
+
+/// Replaces a text with its translation, if it has one.
+fn translate(translations: &HashMap<String, String>, text: &mut String) {
+    if let Some(translation) = translations.get(text.as_str()) {
+        *text = translation.clone();
+    }
+}
+
+/// The groups, units or skills of a card.
+fn texts_of(card: &mut FullCard, kind: TranslationKind) -> &mut Vec<String> {
+    match kind {
+        TranslationKind::Group => &mut card.groups,
+        TranslationKind::Unit => &mut card.units,
+        _ => &mut card.skills,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_language_order() {
+        let (tags, refused) = parse_accept_language("fr;q=0.5, en-US,en;q=0.9, *;q=0.1, de;q=0");
+        assert_eq!(tags, ["en-US", "en", "fr"]);
+        assert_eq!(refused, ["de"]);
+        assert_eq!(with_fallbacks(&tags, &refused), ["en-us", "en", "fr"]);
+        assert_eq!(
+            with_fallbacks(&["zh-TW".to_string(), "ja".to_string()], &[]),
+            ["zh-tw", "zh", "ja"]
+        );
+
+        // Refused base languages are not fallen back to.
+        let (tags, refused) = parse_accept_language("en-GB, EN;q=0, ja;q=0.5");
+        assert_eq!(with_fallbacks(&tags, &refused), ["en-gb", "ja"]);
+    }
+
+    #[test]
+    fn test_normalize_language() {
+        assert_eq!(normalize_language(" EN-us "), Some("en-us".to_string()));
+        assert_eq!(normalize_language("e"), None);
+        assert_eq!(normalize_language("en_US"), None);
+        assert_eq!(normalize_language("en-"), None);
+    }
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 // Enums for type safety, mapping to database CHECK constraints.
 // The `sqlx::Type` derive allows sqlx to map these to TEXT columns.
@@ -21,6 +21,17 @@ pub enum RarityType {
     Parallel,
 }
 
+/// The kind of product a set was released as.
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash, Clone, Copy)]
+#[sqlx(type_name = "TEXT")]
+#[serde(rename_all = "PascalCase")]
+pub enum ProductType {
+    Booster,
+    PremiumBooster,
+    StartDeck,
+    Promo,
+}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash, Clone, Copy)]
 #[sqlx(type_name = "TEXT")]
 #[serde(rename_all = "PascalCase")]
@@ -47,6 +58,22 @@ pub enum BladeHeartColor {
     All,
 }
 
+impl BladeHeartColor {
+    /// Returns the heart color this blade heart provides, or `None` for `All`,
+    /// which can stand in for any color.
+    pub fn heart_color(self) -> Option<HeartColor> {
+        match self {
+            BladeHeartColor::Pink => Some(HeartColor::Pink),
+            BladeHeartColor::Red => Some(HeartColor::Red),
+            BladeHeartColor::Yellow => Some(HeartColor::Yellow),
+            BladeHeartColor::Green => Some(HeartColor::Green),
+            BladeHeartColor::Blue => Some(HeartColor::Blue),
+            BladeHeartColor::Purple => Some(HeartColor::Purple),
+            BladeHeartColor::All => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash, Clone, Copy)]
 #[sqlx(type_name = "TEXT")]
 #[serde(rename_all = "PascalCase")]
@@ -55,6 +82,22 @@ pub enum SpecialHeart {
     Score,
 }
 
+/// The role of an API key, ordered so that each role can do everything the roles
+/// before it can.
+#[derive(
+    Debug, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy,
+)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Reads the audit log, without granting any writes.
+    Reader,
+    /// Creates and imports catalog data.
+    Editor,
+    /// Also deletes reference data, restores dumps and manages API keys.
+    Admin,
+}
+
 // Structs mapping directly to database tables.
 
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
@@ -68,6 +111,15 @@ pub struct Set {
 pub struct SetResponse {
     pub set_code: String,
     pub name: String,
+    /// The release date, as `YYYY-MM-DD`.
+    pub release_date: Option<String>,
+    pub product_type: Option<ProductType>,
+    /// The official number of cards in the set.
+    pub total_cards: Option<i64>,
+    /// The language or region the set was released for, e.g. `ja`.
+    pub language: String,
+    /// The position of the set in listings, before sets without one.
+    pub sort_order: Option<i64>,
 }
 
 
@@ -99,23 +151,27 @@ pub struct Card {
     pub card_type: CardType,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct Printing {
     pub id: i64,
     pub card_id: i64,
     pub rarity_code: String,
     pub rarity_type: RarityType,
     pub image_url: Option<String>,
+    /// A small copy of a locally stored image, for card grids.
+    pub thumbnail_url: Option<String>,
+    /// A medium-sized copy of a locally stored image, for detail pages.
+    pub medium_url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct CardHeart {
     pub card_id: i64,
     pub color: HeartColor,
     pub count: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct CharacterCard {
     pub card_id: i64,
     pub cost: i64,
@@ -123,7 +179,7 @@ pub struct CharacterCard {
     pub blade_heart: Option<BladeHeartColor>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct LiveCard {
     pub card_id: i64,
     pub score: i64,
@@ -131,15 +187,63 @@ pub struct LiveCard {
     pub special_heart: Option<SpecialHeart>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct NameVariant {
     pub variant_name: String,
     pub canonical_name: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct GroupVariant {
+    pub variant_name: String,
+    pub canonical_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Rarity {
+    pub rarity_code: String,
+    pub rarity_type: RarityType,
+    /// The name the rarity is shown with, e.g. "Secret Rare".
+    #[serde(default)]
+    pub display_name: String,
+    /// Where the rarity sorts in lists, lowest first.
+    #[serde(default = "default_sort_rank")]
+    pub sort_rank: i64,
+    /// Whether cards of this rarity are hidden ones, beyond a set's regular lineup.
+    #[serde(default)]
+    pub is_secret: bool,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// The sort rank of rarities that were not given one, placing them after the known ones.
+pub const DEFAULT_SORT_RANK: i64 = 1000;
+
+fn default_sort_rank() -> i64 {
+    DEFAULT_SORT_RANK
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub owner: Option<String>,
+    /// The API key that created the collection and may change it.
+    pub api_key_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Want {
+    pub id: i64,
+    pub collection_id: i64,
+    pub printing_id: Option<i64>,
+    pub card_id: Option<i64>,
+    pub quantity: i64,
+}
+
 // This struct doesn't map to a table but will be used to return
 // a fully composed card object in our API responses.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FullCard {
     #[serde(flatten)]
     pub base: BaseCard,
@@ -154,7 +258,7 @@ pub struct FullCard {
 }
 
 /// A subset of the Card model used for composing the FullCard response.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BaseCard {
     pub id: i64,
     pub series_code: String,
@@ -164,7 +268,7 @@ pub struct BaseCard {
     pub card_type: CardType,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum CardTypeSpecifics {
     Character(CharacterCard),
@@ -333,11 +437,550 @@ impl<'de> Deserialize<'de> for CreateCard {
     }
 }
 
-/// Represents the payload for creating a new rarity mapping.
+// --- Structs for Card Imports ---
+
+/// Maps the columns of a CSV card sheet to the fields of [`CreateCard`].
+/// Each field holds the header of the column that provides it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CsvColumnMapping {
+    pub card_identifier: String,
+    pub name: String,
+    pub card_type: String,
+    pub cost: String,
+    pub blades: String,
+    /// One column per heart color holding its count.
+    pub hearts: HashMap<HeartColor, String>,
+    pub blade_heart: String,
+    pub score: String,
+    pub special_heart: String,
+    pub groups: String,
+    pub units: String,
+    pub skills: String,
+    pub image_url: String,
+    /// The separator used for list columns (groups, units and skills).
+    pub list_delimiter: char,
+}
+
+impl Default for CsvColumnMapping {
+    fn default() -> Self {
+        let hearts = [
+            (HeartColor::Pink, "heart_pink"),
+            (HeartColor::Red, "heart_red"),
+            (HeartColor::Yellow, "heart_yellow"),
+            (HeartColor::Green, "heart_green"),
+            (HeartColor::Blue, "heart_blue"),
+            (HeartColor::Purple, "heart_purple"),
+            (HeartColor::Gray, "heart_gray"),
+        ]
+        .into_iter()
+        .map(|(color, column)| (color, column.to_string()))
+        .collect();
+
+        CsvColumnMapping {
+            card_identifier: "card_identifier".to_string(),
+            name: "name".to_string(),
+            card_type: "card_type".to_string(),
+            cost: "cost".to_string(),
+            blades: "blades".to_string(),
+            hearts,
+            blade_heart: "blade_heart".to_string(),
+            score: "score".to_string(),
+            special_heart: "special_heart".to_string(),
+            groups: "groups".to_string(),
+            units: "units".to_string(),
+            skills: "skills".to_string(),
+            image_url: "image_url".to_string(),
+            list_delimiter: '|',
+        }
+    }
+}
+
+/// Represents the payload for importing cards from CSV.
+#[derive(Debug, Deserialize)]
+pub struct CsvImportRequest {
+    pub csv: String,
+    #[serde(default)]
+    pub mapping: CsvColumnMapping,
+}
+
+/// An error tied to a single row of an import. Rows are numbered from 1,
+/// not counting the header.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub message: String,
+}
+
+/// Represents the payload for importing saved card detail pages of the official card list.
+#[derive(Debug, Deserialize)]
+pub struct HtmlImportRequest {
+    /// The HTML of each page.
+    pub pages: Vec<String>,
+    /// Only report the differences against the database, without creating any cards.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// How an imported card compares to the card already stored under the same number.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportDiffStatus {
+    New,
+    Unchanged,
+    Changed,
+}
+
+/// A single field that differs between a stored card and an imported one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub existing: serde_json::Value,
+    pub incoming: serde_json::Value,
+}
+
+/// The diff of a single imported page. Pages are numbered from 1.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportDiffEntry {
+    pub row: usize,
+    pub card_identifier: String,
+    pub status: ImportDiffStatus,
+    /// The ID of the stored card, if there is one.
+    pub card_id: Option<i64>,
+    pub changes: Vec<FieldChange>,
+}
+
+/// The result of an HTML import: the diff of every page, and the cards that were created.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HtmlImportResponse {
+    pub diff: Vec<ImportDiffEntry>,
+    pub created: Vec<FullCard>,
+}
+
+// --- Structs for Catalog Exports ---
+
+/// A flattened row of the card catalog, with one row per printing.
+///
+/// Column names match the defaults of [`CsvColumnMapping`], so an exported CSV can be
+/// imported again as is. Groups, units and skills are joined with `|`.
+#[derive(Debug, Serialize, Default, sqlx::FromRow)]
+pub struct CardExportRow {
+    pub card_id: i64,
+    pub printing_id: i64,
+    pub card_identifier: String,
+    pub series_code: String,
+    pub set_code: String,
+    pub number_in_set: String,
+    pub name: String,
+    pub card_type: String,
+    pub rarity_code: String,
+    pub rarity_type: String,
+    pub image_url: Option<String>,
+    pub cost: Option<i64>,
+    pub blades: Option<i64>,
+    pub score: Option<i64>,
+    pub blade_heart: Option<String>,
+    pub special_heart: Option<String>,
+    pub heart_pink: Option<i64>,
+    pub heart_red: Option<i64>,
+    pub heart_yellow: Option<i64>,
+    pub heart_green: Option<i64>,
+    pub heart_blue: Option<i64>,
+    pub heart_purple: Option<i64>,
+    pub heart_gray: Option<i64>,
+    pub groups: String,
+    pub units: String,
+    pub skills: String,
+}
+
+// --- Structs for Card Identification ---
+
+/// Query parameters for identifying a card from a photo.
+#[derive(Debug, Deserialize, Default)]
+pub struct IdentifyQuery {
+    /// How many of the closest printings to return. Defaults to 5.
+    pub limit: Option<usize>,
+}
+
+/// A printing whose image resembles an identified photo.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrintingMatch {
+    pub printing_id: i64,
+    /// The Hamming distance between the perceptual hashes of the photo and the printing
+    /// image, from 0 (alike) to 64.
+    pub distance: u32,
+    pub card: FullCard,
+}
+
+// --- Structs for API Keys ---
+
+/// An issued API key. The secret itself is never stored or returned after issuing.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct ApiKey {
+    pub id: i64,
+    /// Who or what the key was issued to.
+    pub name: String,
+    pub role: Role,
+    /// The public part of the key, to tell keys apart.
+    pub key_prefix: String,
+    pub created_at: String,
+    pub revoked_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKey {
+    pub name: String,
+    pub role: Role,
+}
+
+/// A newly issued API key, with the only copy of its token.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssuedApiKey {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    /// The token to send as `Authorization: Bearer <token>`.
+    pub token: String,
+}
+
+// --- Structs for the Audit Log ---
+
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash, Clone, Copy)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// The kinds of data whose changes are recorded in the audit log.
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash, Clone, Copy)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    /// Keyed by card ID.
+    Card,
+    /// Keyed by set code.
+    Set,
+    Group,
+    Unit,
+    /// Keyed by rarity code.
+    Rarity,
+    /// Keyed by variant name.
+    NameVariant,
+    GroupVariant,
+    /// Keyed by kind, language and original text, e.g. `group:en:ラブライブ！`.
+    Translation,
+}
+
+/// Who made a change: the API key used, or a local tool working on the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor {
+    pub name: String,
+    pub api_key_id: Option<i64>,
+}
+
+impl From<&ApiKey> for Actor {
+    fn from(key: &ApiKey) -> Self {
+        Actor {
+            name: key.name.clone(),
+            api_key_id: Some(key.id),
+        }
+    }
+}
+
+/// A recorded change, with the state of the entity before and after it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub actor: String,
+    pub api_key_id: Option<i64>,
+    /// When the change was made, in UTC (`YYYY-MM-DD HH:MM:SS`).
+    pub created_at: String,
+    pub action: AuditAction,
+    pub entity_kind: EntityKind,
+    pub entity_key: String,
+    /// `None` for creations.
+    pub before: Option<serde_json::Value>,
+    /// `None` for deletions.
+    pub after: Option<serde_json::Value>,
+}
+
+/// Query parameters for searching the audit log. Every filter is optional.
+#[derive(Debug, Deserialize, Default)]
+pub struct AuditQuery {
+    pub entity_kind: Option<EntityKind>,
+    pub entity_key: Option<String>,
+    pub actor: Option<String>,
+    /// The time range to include, from `since` up to but excluding `until`, each as
+    /// `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SS` with an optional time zone.
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// How many entries to return, newest first. Defaults to 100, at most 1000.
+    pub limit: Option<i64>,
+}
+
+// --- Structs for the Trash ---
+
+/// The kinds of reference data that are moved to the trash when deleted.
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash, Clone, Copy)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Set,
+    Group,
+    Unit,
+}
+
+impl TrashKind {
+    /// The kind as written in trash paths.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TrashKind::Set => "set",
+            TrashKind::Group => "group",
+            TrashKind::Unit => "unit",
+        }
+    }
+}
+
+impl From<TrashKind> for EntityKind {
+    fn from(kind: TrashKind) -> Self {
+        match kind {
+            TrashKind::Set => EntityKind::Set,
+            TrashKind::Group => EntityKind::Group,
+            TrashKind::Unit => EntityKind::Unit,
+        }
+    }
+}
+
+/// A deleted set, group or unit, which can be restored or purged.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct TrashItem {
+    pub kind: TrashKind,
+    /// The set code, or the name of a group or unit.
+    pub key: String,
+    pub name: String,
+    /// When the item was deleted, in UTC (`YYYY-MM-DD HH:MM:SS`).
+    pub deleted_at: String,
+}
+
+// --- Structs for Translations ---
+
+/// The kinds of text that can be translated, each keyed by its original text: the
+/// canonical card name, group or unit name, set code or skill text.
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, Hash, Clone, Copy)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationKind {
+    Name,
+    Group,
+    Unit,
+    /// Translates the name of the set.
+    Set,
+    Skill,
+}
+
+impl TranslationKind {
+    pub const ALL: [TranslationKind; 5] = [
+        TranslationKind::Name,
+        TranslationKind::Group,
+        TranslationKind::Unit,
+        TranslationKind::Set,
+        TranslationKind::Skill,
+    ];
+
+    /// The kind as written in query parameters and the audit log.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TranslationKind::Name => "name",
+            TranslationKind::Group => "group",
+            TranslationKind::Unit => "unit",
+            TranslationKind::Set => "set",
+            TranslationKind::Skill => "skill",
+        }
+    }
+}
+
+/// The translation of a name, set or skill into one language. Also the payload for
+/// submitting one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Translation {
+    pub kind: TranslationKind,
+    /// The text translated, as stored in the database.
+    pub key: String,
+    /// A lowercase language code, e.g. `en` or `zh-tw`.
+    pub language: String,
+    pub text: String,
+}
+
+/// Query parameters for listing translations.
+#[derive(Debug, Deserialize, Default)]
+pub struct TranslationQuery {
+    pub kind: Option<TranslationKind>,
+    pub language: Option<String>,
+}
+
+/// Query parameters choosing the language of a response, over `Accept-Language`.
+#[derive(Debug, Deserialize, Default)]
+pub struct LanguageQuery {
+    pub lang: Option<String>,
+}
+
+// --- Structs for Deleting Reference Data ---
+
+/// Query parameters for deleting a set, group, unit or rarity that cards may depend on.
+#[derive(Debug, Deserialize, Default)]
+pub struct DeleteQuery {
+    /// Detach the dependent cards from a group or unit, or remove the cards of a set
+    /// and the printings of a rarity. Only rarities accept it, as sets, groups and
+    /// units go to the trash.
+    #[serde(default)]
+    pub cascade: bool,
+    /// Move the dependent cards to another set, group, unit or rarity.
+    pub reassign_to: Option<String>,
+}
+
+/// What to do with the cards that depend on deleted reference data.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DependentAction {
+    /// Refuse to delete anything that cards still depend on.
+    #[default]
+    Refuse,
+    Cascade,
+    ReassignTo(String),
+}
+
+impl DeleteQuery {
+    /// Returns `None` if both `cascade` and `reassign_to` are given.
+    pub fn action(self) -> Option<DependentAction> {
+        match (self.cascade, self.reassign_to) {
+            (true, Some(_)) => None,
+            (true, None) => Some(DependentAction::Cascade),
+            (false, Some(target)) => Some(DependentAction::ReassignTo(target)),
+            (false, None) => Some(DependentAction::Refuse),
+        }
+    }
+}
+
+// --- Structs for Card History ---
+
+/// A snapshot of a card, taken after one of its changes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CardRevision {
+    pub card_id: i64,
+    /// Numbered from 1, in the order the changes were made.
+    pub revision: i64,
+    /// When the change was made, in UTC (`YYYY-MM-DD HH:MM:SS`).
+    pub created_at: String,
+    pub actor: String,
+    /// The card as a [`FullCard`].
+    pub card: serde_json::Value,
+}
+
+/// Query parameters for comparing two revisions of a card.
+#[derive(Debug, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i64,
+    /// Defaults to the latest revision.
+    pub to: Option<i64>,
+}
+
+/// A field that differs between two revisions.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FieldDiff {
+    /// The path to the field, with nested fields and list indexes separated by dots,
+    /// e.g. `printings.0.image_url`.
+    pub field: String,
+    /// `null` if the field was added.
+    pub from: serde_json::Value,
+    /// `null` if the field was removed.
+    pub to: serde_json::Value,
+}
+
+/// The field-level changes between two revisions of a card.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CardRevisionDiff {
+    pub card_id: i64,
+    pub from: i64,
+    pub to: i64,
+    pub changes: Vec<FieldDiff>,
+}
+
+// --- Structs for Database Dumps ---
+
+/// A versioned snapshot of the card catalog and its reference data.
+///
+/// Tables are listed in dependency order, which is also the order they are restored in.
+/// Collections, want lists, API keys, the audit log and card revisions are not included.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseDump {
+    /// The version of the dump format itself.
+    pub format_version: u32,
+    /// The latest applied sqlx migration of the database the dump was taken from.
+    pub schema_version: i64,
+    pub sets: Vec<SetResponse>,
+    pub groups: Vec<String>,
+    pub units: Vec<String>,
+    pub names: Vec<String>,
+    pub rarities: Vec<Rarity>,
+    pub set_rarities: Vec<SetRarity>,
+    pub name_variants: Vec<NameVariant>,
+    pub group_variants: Vec<GroupVariant>,
+    pub skills: Vec<String>,
+    pub cards: Vec<FullCard>,
+    pub translations: Vec<Translation>,
+    pub perceptual_hashes: Vec<PrintingHash>,
+    /// The sets, groups and units above that are in the trash.
+    pub trash: Vec<TrashItem>,
+}
+
+/// The perceptual hash of a printing's image, used to identify cards from photos.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PrintingHash {
+    pub printing_id: i64,
+    pub perceptual_hash: u64,
+}
+
+/// Represents the payload for creating a new rarity.
 #[derive(Debug, Deserialize)]
 pub struct CreateRarity {
     pub rarity_code: String,
     pub rarity_type: RarityType,
+    /// Defaults to the rarity code.
+    pub display_name: Option<String>,
+    /// Defaults to [`DEFAULT_SORT_RANK`].
+    pub sort_rank: Option<i64>,
+    #[serde(default)]
+    pub is_secret: bool,
+    pub description: Option<String>,
+}
+
+impl From<CreateRarity> for Rarity {
+    fn from(value: CreateRarity) -> Self {
+        Rarity {
+            display_name: value
+                .display_name
+                .unwrap_or_else(|| value.rarity_code.clone()),
+            rarity_code: value.rarity_code,
+            rarity_type: value.rarity_type,
+            sort_rank: value.sort_rank.unwrap_or(DEFAULT_SORT_RANK),
+            is_secret: value.is_secret,
+            description: value.description,
+        }
+    }
+}
+
+/// Represents the payload for declaring the rarities a set contains.
+#[derive(Debug, Deserialize)]
+pub struct SetRarities {
+    pub rarities: Vec<String>,
+}
+
+/// A rarity declared for a set.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct SetRarity {
+    pub set_code: String,
+    pub rarity_code: String,
 }
 
 /// Represents the payload for creating a new name variant.
@@ -354,11 +997,17 @@ pub struct CreateGroupVariant {
     pub canonical_name: String,
 }
 
-/// Represents the payload for creating a new set.
-#[derive(Debug, Deserialize)]
+/// Represents the payload for creating a new set. The language defaults to `ja`.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CreateSet {
     pub set_code: String,
     pub name: String,
+    /// The release date, as `YYYY-MM-DD`.
+    pub release_date: Option<String>,
+    pub product_type: Option<ProductType>,
+    pub total_cards: Option<i64>,
+    pub language: Option<String>,
+    pub sort_order: Option<i64>,
 }
 
 /// Represents the payload for creating a new group.
@@ -373,6 +1022,505 @@ pub struct CreateUnit {
     pub name: String,
 }
 
+/// Deserializes an optional field that is cleared when given as `null`, so that it is
+/// `None` when left out and `Some(None)` when cleared.
+fn clearable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Represents the payload for updating a set. Fields left out are not changed, and the
+/// release date, product type, total and sort order are cleared when given as `null`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSet {
+    pub set_code: Option<String>,
+    pub name: Option<String>,
+    /// The release date, as `YYYY-MM-DD`.
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub release_date: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub product_type: Option<Option<ProductType>>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub total_cards: Option<Option<i64>>,
+    pub language: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "clearable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sort_order: Option<Option<i64>>,
+}
+
+impl UpdateSet {
+    /// Returns `true` if no field is given.
+    pub fn is_empty(&self) -> bool {
+        self.set_code.is_none()
+            && self.name.is_none()
+            && self.release_date.is_none()
+            && self.product_type.is_none()
+            && self.total_cards.is_none()
+            && self.language.is_none()
+            && self.sort_order.is_none()
+    }
+}
+
+/// How to sort the list of sets.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SetSort {
+    ReleaseDate,
+    SortOrder,
+    SetCode,
+    Name,
+}
+
+/// The direction to sort a list in.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Query parameters for listing sets. Without `sort`, sets are listed by their sort
+/// order. Sets missing the sorted field are listed last in either direction.
+#[derive(Debug, Deserialize, Default)]
+pub struct SetListQuery {
+    pub sort: Option<SetSort>,
+    #[serde(default)]
+    pub order: SortDirection,
+}
+
+/// A card on a set checklist, with all of its printings.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChecklistCard {
+    pub card_id: i64,
+    /// The card identifier without rarity, e.g. `PL!-BP01-001`.
+    pub card_identifier: String,
+    pub number_in_set: String,
+    pub name: String,
+    pub card_type: CardType,
+    pub printings: Vec<Printing>,
+}
+
+/// Every card of a set in number order, checked against its declared total.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetChecklist {
+    #[serde(flatten)]
+    pub set: SetResponse,
+    pub cards: Vec<ChecklistCard>,
+    /// The numbers from 1 up to `total_cards` that no card of the set has, across all
+    /// series. Empty if the total is not known. Cards numbered past the total, such as
+    /// secret rares, are still listed in `cards`.
+    pub missing_numbers: Vec<i64>,
+}
+
+/// Represents the payload for renaming a group.
+#[derive(Debug, Deserialize)]
+pub struct RenameGroup {
+    pub name: String,
+    /// Keep the old name as a group variant of the new one.
+    #[serde(default)]
+    pub keep_old_as_variant: bool,
+}
+
+/// Represents the payload for renaming a unit.
+#[derive(Debug, Deserialize)]
+pub struct RenameUnit {
+    pub name: String,
+}
+
+/// Represents the payload for creating a new collection.
+#[derive(Debug, Deserialize)]
+pub struct CreateCollection {
+    pub name: String,
+    pub owner: Option<String>,
+}
+
+/// An owned quantity of a single printing, used to add, remove or set quantities.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectionQuantity {
+    pub printing_id: i64,
+    pub quantity: i64,
+}
+
+/// A printing owned in a collection, together with its full card data.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollectionEntry {
+    pub printing_id: i64,
+    pub quantity: i64,
+    pub card: FullCard,
+}
+
+/// A collection and every card it contains.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollectionView {
+    #[serde(flatten)]
+    pub collection: Collection,
+    pub cards: Vec<CollectionEntry>,
+}
+
+/// How many of a set's printings a collection owns.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetCompletion {
+    pub set_code: String,
+    pub name: String,
+    pub owned_printings: i64,
+    pub total_printings: i64,
+    pub percentage: f64,
+}
+
+/// Represents the payload for adding a want to a collection's want list.
+/// Exactly one of `printing_id` or `card_id` must be set; a `card_id` accepts any printing.
+#[derive(Debug, Deserialize)]
+pub struct CreateWant {
+    pub printing_id: Option<i64>,
+    pub card_id: Option<i64>,
+    pub quantity: i64,
+}
+
+/// A printing owned in a collection, with the details needed for trade matching.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct OwnedPrinting {
+    pub printing_id: i64,
+    pub card_id: i64,
+    pub name: String,
+    pub rarity_code: String,
+    pub rarity_type: RarityType,
+    pub quantity: i64,
+}
+
+/// A number of copies of a printing one collection can give to another.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TradeOffer {
+    pub printing_id: i64,
+    pub card_id: i64,
+    pub name: String,
+    pub rarity_code: String,
+    pub rarity_type: RarityType,
+    pub quantity: i64,
+}
+
+/// The trades possible between two collections, in both directions.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TradeMatch {
+    pub collection_id: i64,
+    pub other_collection_id: i64,
+    /// Cards `collection_id` has in surplus that `other_collection_id` wants.
+    pub gives: Vec<TradeOffer>,
+    /// Cards `other_collection_id` has in surplus that `collection_id` wants.
+    pub receives: Vec<TradeOffer>,
+}
+
+/// Query parameters for trade matching.
+#[derive(Debug, Deserialize, Default)]
+pub struct TradeMatchQuery {
+    /// Excludes Parallel printings from matches.
+    #[serde(default)]
+    pub exclude_parallel: bool,
+}
+
+// --- Structs for Deck Analysis ---
+
+/// A single entry in a deck list: a card and how many copies of it are included.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeckEntry {
+    pub card_id: i64,
+    pub count: i64,
+}
+
+/// A predicate over cards, mirroring the filters offered by card search.
+/// Every field that is set must match; an empty filter matches every card.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CardFilter {
+    pub card_type: Option<CardType>,
+    pub heart_color: Option<HeartColor>,
+    pub cost: Option<i64>,
+    pub group: Option<String>,
+    pub name: Option<String>,
+}
+
+impl CardFilter {
+    /// Replaces variant names in the `name` and `group` criteria with their canonical names.
+    pub fn canonicalize(
+        &mut self,
+        name_variant_cache: &HashMap<String, String>,
+        group_variant_cache: &HashMap<String, String>,
+    ) {
+        if let Some(canonical) = self.name.as_ref().and_then(|n| name_variant_cache.get(n)) {
+            self.name = Some(canonical.clone());
+        }
+        if let Some(canonical) = self.group.as_ref().and_then(|g| group_variant_cache.get(g)) {
+            self.group = Some(canonical.clone());
+        }
+    }
+
+    /// Returns `true` if the given card satisfies every criterion of this filter.
+    ///
+    /// `group` and `name` are expected to already be canonicalized by the caller.
+    pub fn matches(&self, card: &FullCard) -> bool {
+        let type_matches = self
+            .card_type
+            .is_none_or(|card_type| card.base.card_type == card_type);
+        let heart_matches = self
+            .heart_color
+            .is_none_or(|color| card.hearts.get(&color).copied().unwrap_or(0) > 0);
+        let cost_matches = self.cost.is_none_or(|cost| {
+            matches!(&card.type_specifics, Some(CardTypeSpecifics::Character(c)) if c.cost == cost)
+        });
+        let group_matches = self
+            .group
+            .as_ref()
+            .is_none_or(|group| card.groups.contains(group));
+        let name_matches = self
+            .name
+            .as_ref()
+            .is_none_or(|name| &card.base.name == name);
+
+        type_matches && heart_matches && cost_matches && group_matches && name_matches
+    }
+}
+
+/// A single condition to evaluate in a draw probability request,
+/// e.g. "at least 2 Pink-heart members".
+#[derive(Debug, Deserialize)]
+pub struct ProbabilityCondition {
+    pub label: Option<String>,
+    #[serde(default)]
+    pub filter: CardFilter,
+    #[serde(default = "default_at_least")]
+    pub at_least: i64,
+}
+
+fn default_at_least() -> i64 {
+    1
+}
+
+fn default_hand_size() -> i64 {
+    6
+}
+
+fn default_draws_per_turn() -> i64 {
+    1
+}
+
+fn default_turns() -> i64 {
+    5
+}
+
+/// Represents the payload for computing draw probabilities for a deck.
+#[derive(Debug, Deserialize)]
+pub struct ProbabilityRequest {
+    pub deck: Vec<DeckEntry>,
+    pub conditions: Vec<ProbabilityCondition>,
+    #[serde(default = "default_hand_size")]
+    pub hand_size: i64,
+    #[serde(default = "default_draws_per_turn")]
+    pub draws_per_turn: i64,
+    #[serde(default = "default_turns")]
+    pub turns: i64,
+}
+
+/// The probability of a condition being met after a given turn.
+/// Turn 0 is the opening hand.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TurnProbability {
+    pub turn: i64,
+    pub cards_seen: i64,
+    pub probability: f64,
+}
+
+/// The per-turn probability table for a single condition.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConditionProbability {
+    pub label: Option<String>,
+    pub at_least: i64,
+    pub matching_cards: i64,
+    pub table: Vec<TurnProbability>,
+}
+
+/// The response body for a draw probability request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProbabilityResponse {
+    /// The number of cards in the main deck, without the Energy deck.
+    pub deck_size: i64,
+    pub conditions: Vec<ConditionProbability>,
+}
+
+/// The result of checking whether a set of hearts can pay a Live card's heart requirements.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RequirementCheck {
+    pub satisfied: bool,
+    /// Hearts that could not be paid, by required color. `Gray` means any color.
+    pub missing: HashMap<HeartColor, i64>,
+    /// Colored hearts that were not needed to pay the requirements.
+    pub leftover: HashMap<HeartColor, i64>,
+    /// Wildcard hearts (Gray hearts and `All` blade hearts) that were not needed.
+    pub leftover_wildcards: i64,
+}
+
+/// Represents the payload for checking whether a stage can pay for a Live card.
+#[derive(Debug, Deserialize)]
+pub struct LiveCheckRequest {
+    /// The IDs of the member cards on stage.
+    pub stage: Vec<i64>,
+    pub live_card_id: i64,
+    /// Blade hearts revealed by yells, in addition to the hearts on stage.
+    #[serde(default)]
+    pub blade_hearts: Vec<BladeHeartColor>,
+}
+
+fn default_trials() -> u32 {
+    10_000
+}
+
+/// Represents the payload for simulating whether a stage can clear a Live card.
+#[derive(Debug, Deserialize)]
+pub struct SimulationRequest {
+    /// The deck that yells are revealed from.
+    pub deck: Vec<DeckEntry>,
+    /// The IDs of the member cards on stage.
+    pub stage: Vec<i64>,
+    pub live_card_id: i64,
+    #[serde(default = "default_trials")]
+    pub trials: u32,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// The response body for a live-success simulation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationResponse {
+    pub seed: u64,
+    pub trials: u32,
+    pub successes: u32,
+    pub probability: f64,
+    /// The number of cards revealed by each yell, i.e. the total blades on stage.
+    pub yell_count: i64,
+}
+
+// --- Structs for Tabletop Exports ---
+
+/// Represents the payload for exporting a deck to Tabletop Simulator.
+#[derive(Debug, Deserialize)]
+pub struct TtsExportRequest {
+    pub deck: Vec<DeckEntry>,
+    /// The image shown on the back of every card.
+    pub card_back_url: String,
+}
+
+/// Represents the payload for exporting a deck as a plain card list.
+#[derive(Debug, Deserialize)]
+pub struct DeckListRequest {
+    pub deck: Vec<DeckEntry>,
+}
+
+/// The paper size of a printed proxy sheet.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageSize {
+    #[default]
+    A4,
+    Letter,
+}
+
+/// Represents the payload for printing a deck as a sheet of proxies.
+#[derive(Debug, Deserialize)]
+pub struct ProxySheetRequest {
+    pub deck: Vec<DeckEntry>,
+    #[serde(default)]
+    pub page_size: PageSize,
+}
+
+/// A single card of an exported deck list, resolved to the printing whose image is used.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeckListCard {
+    pub card_id: i64,
+    pub printing_id: Option<i64>,
+    pub card_identifier: String,
+    pub name: String,
+    pub card_type: CardType,
+    pub count: i64,
+    pub image_url: Option<String>,
+}
+
+/// A Tabletop Simulator saved object, as found in its `Saves/Saved Objects` folder.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TtsSavedObject {
+    pub object_states: Vec<TtsObject>,
+}
+
+/// A card or deck object within a Tabletop Simulator save.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TtsObject {
+    /// Either `"Card"` or `"Deck"`.
+    pub name: String,
+    pub nickname: String,
+    pub transform: TtsTransform,
+    #[serde(rename = "CardID", skip_serializing_if = "Option::is_none")]
+    pub card_id: Option<i64>,
+    #[serde(rename = "DeckIDs", skip_serializing_if = "Vec::is_empty", default)]
+    pub deck_ids: Vec<i64>,
+    /// The card sheets used by this object, keyed by sheet number.
+    pub custom_deck: BTreeMap<String, TtsCustomDeck>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub contained_objects: Vec<TtsObject>,
+}
+
+/// The position, rotation and scale of a Tabletop Simulator object.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct TtsTransform {
+    #[serde(rename = "posX")]
+    pub pos_x: f64,
+    #[serde(rename = "posY")]
+    pub pos_y: f64,
+    #[serde(rename = "posZ")]
+    pub pos_z: f64,
+    #[serde(rename = "rotX")]
+    pub rot_x: f64,
+    #[serde(rename = "rotY")]
+    pub rot_y: f64,
+    #[serde(rename = "rotZ")]
+    pub rot_z: f64,
+    #[serde(rename = "scaleX")]
+    pub scale_x: f64,
+    #[serde(rename = "scaleY")]
+    pub scale_y: f64,
+    #[serde(rename = "scaleZ")]
+    pub scale_z: f64,
+}
+
+/// A Tabletop Simulator card sheet. Every exported card uses a sheet of its own.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct TtsCustomDeck {
+    #[serde(rename = "FaceURL")]
+    pub face_url: String,
+    #[serde(rename = "BackURL")]
+    pub back_url: String,
+    pub num_width: u32,
+    pub num_height: u32,
+    pub back_is_hidden: bool,
+    pub unique_back: bool,
+}
+
 #[cfg(test)]
 mod test_character {
     use super::*;
@@ -0,0 +1,79 @@
+use image::{DynamicImage, imageops::FilterType};
+use std::f64::consts::PI;
+
+/// The side of the grayscale thumbnail the hash is computed from.
+const SAMPLE_SIZE: usize = 32;
+
+/// The side of the block of lowest frequencies that make up the hash, one bit each.
+const HASH_SIZE: usize = 8;
+
+/// Computes the perceptual hash (pHash) of an image.
+///
+/// The image is reduced to a 32x32 grayscale thumbnail and transformed with a discrete
+/// cosine transform. Each of the 64 lowest frequencies sets a bit if it lies above their
+/// median. Rescaling, recompression and small changes in color or lighting barely affect
+/// the result, so visually similar images have hashes a small Hamming distance apart.
+pub fn perceptual_hash(image: &DynamicImage) -> u64 {
+    let thumbnail = image
+        .resize_exact(SAMPLE_SIZE as u32, SAMPLE_SIZE as u32, FilterType::Triangle)
+        .to_luma8();
+    let pixels: Vec<f64> = thumbnail.pixels().map(|p| f64::from(p.0[0])).collect();
+
+    let mut cosines = [[0.0; SAMPLE_SIZE]; HASH_SIZE];
+    for (frequency, row) in cosines.iter_mut().enumerate() {
+        for (x, cosine) in row.iter_mut().enumerate() {
+            *cosine = ((2 * x + 1) as f64 * frequency as f64 * PI / (2 * SAMPLE_SIZE) as f64).cos();
+        }
+    }
+
+    // The transform is separable: first along the rows, then along the columns.
+    let mut rows = [[0.0; HASH_SIZE]; SAMPLE_SIZE];
+    for (y, row) in rows.iter_mut().enumerate() {
+        for (u, value) in row.iter_mut().enumerate() {
+            *value = (0..SAMPLE_SIZE)
+                .map(|x| pixels[y * SAMPLE_SIZE + x] * cosines[u][x])
+                .sum();
+        }
+    }
+    let mut coefficients = [0.0; HASH_SIZE * HASH_SIZE];
+    for v in 0..HASH_SIZE {
+        for u in 0..HASH_SIZE {
+            coefficients[v * HASH_SIZE + u] =
+                (0..SAMPLE_SIZE).map(|y| rows[y][u] * cosines[v][y]).sum();
+        }
+    }
+
+    // The first coefficient is the average brightness, which says nothing about the
+    // picture itself, so it is left out of the median.
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let median = sorted[sorted.len() / 2];
+
+    coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, coefficient)| **coefficient > median)
+        .fold(0, |hash, (bit, _)| hash | (1 << bit))
+}
+
+/// Decodes an image and computes its [`perceptual_hash`].
+pub fn hash_image_bytes(bytes: &[u8]) -> image::ImageResult<u64> {
+    Ok(perceptual_hash(&image::load_from_memory(bytes)?))
+}
+
+/// The number of bits in which two hashes differ. The lower, the more alike the images.
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(hamming_distance(0b1011, 0b1011), 0);
+        assert_eq!(hamming_distance(0b1011, 0b0110), 3);
+        assert_eq!(hamming_distance(0, u64::MAX), 64);
+    }
+}
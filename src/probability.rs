@@ -0,0 +1,128 @@
+use crate::models::{
+    CardFilter, ConditionProbability, FullCard, ProbabilityCondition, TurnProbability,
+};
+
+/// Natural logarithm of the binomial coefficient `C(n, k)`.
+///
+/// Working in log space keeps the computation stable for large decks,
+/// where the raw coefficients would overflow an `f64`.
+fn ln_choose(n: i64, k: i64) -> f64 {
+    if k < 0 || k > n {
+        return f64::NEG_INFINITY;
+    }
+    let k = k.min(n - k);
+    (0..k)
+        .map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln())
+        .sum()
+}
+
+/// Probability of drawing exactly `k` successes in `draws` cards from a
+/// population of `population` cards containing `successes` successes.
+pub fn hypergeometric_pmf(population: i64, successes: i64, draws: i64, k: i64) -> f64 {
+    if k < 0 || k > successes || k > draws || draws - k > population - successes {
+        return 0.0;
+    }
+    (ln_choose(successes, k) + ln_choose(population - successes, draws - k)
+        - ln_choose(population, draws))
+    .exp()
+}
+
+/// Probability of drawing at least `k` successes in `draws` cards from a
+/// population of `population` cards containing `successes` successes.
+pub fn hypergeometric_at_least(population: i64, successes: i64, draws: i64, k: i64) -> f64 {
+    if k <= 0 {
+        return 1.0;
+    }
+    let upper = successes.min(draws);
+    let probability: f64 = (k..=upper)
+        .map(|i| hypergeometric_pmf(population, successes, draws, i))
+        .sum();
+    probability.clamp(0.0, 1.0)
+}
+
+/// Counts how many cards in a deck match the given filter.
+///
+/// # Arguments
+/// * `deck` - The deck as pairs of cards and their copy counts.
+/// * `filter` - The filter to match cards against.
+pub fn count_matching(deck: &[(FullCard, i64)], filter: &CardFilter) -> i64 {
+    deck.iter()
+        .filter(|(card, _)| filter.matches(card))
+        .map(|(_, count)| count)
+        .sum()
+}
+
+/// Builds the per-turn probability table for each condition.
+///
+/// Turn 0 is the opening hand of `hand_size` cards, and every following turn
+/// draws `draws_per_turn` more cards, capped at the size of the deck.
+pub fn probability_tables(
+    deck: &[(FullCard, i64)],
+    conditions: &[ProbabilityCondition],
+    hand_size: i64,
+    draws_per_turn: i64,
+    turns: i64,
+) -> Vec<ConditionProbability> {
+    let deck_size: i64 = deck.iter().map(|(_, count)| count).sum();
+
+    conditions
+        .iter()
+        .map(|condition| {
+            let matching_cards = count_matching(deck, &condition.filter);
+            let table = (0..=turns)
+                .map(|turn| {
+                    let cards_seen = (hand_size + turn * draws_per_turn).min(deck_size);
+                    TurnProbability {
+                        turn,
+                        cards_seen,
+                        probability: hypergeometric_at_least(
+                            deck_size,
+                            matching_cards,
+                            cards_seen,
+                            condition.at_least,
+                        ),
+                    }
+                })
+                .collect();
+
+            ConditionProbability {
+                label: condition.label.clone(),
+                at_least: condition.at_least,
+                matching_cards,
+                table,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_hypergeometric_pmf_small_population() {
+        // 2 successes in a population of 5, drawing 2: P(X = 1) = C(2,1)C(3,1)/C(5,2) = 6/10.
+        assert_close(hypergeometric_pmf(5, 2, 2, 1), 0.6);
+        assert_close(hypergeometric_pmf(5, 2, 2, 2), 0.1);
+        assert_close(hypergeometric_pmf(5, 2, 2, 0), 0.3);
+        assert_close(hypergeometric_pmf(5, 2, 2, 3), 0.0);
+    }
+
+    #[test]
+    fn test_hypergeometric_at_least() {
+        // 4 copies in a 60-card deck, opening hand of 6: 1 - C(56,6)/C(60,6).
+        let expected = 1.0 - (32_468_436.0 / 50_063_860.0);
+        assert_close(hypergeometric_at_least(60, 4, 6, 1), expected);
+        assert_close(hypergeometric_at_least(60, 4, 6, 0), 1.0);
+        assert_close(hypergeometric_at_least(60, 0, 6, 1), 0.0);
+        // Seeing the whole deck guarantees every copy.
+        assert_close(hypergeometric_at_least(60, 4, 60, 4), 1.0);
+    }
+}
@@ -0,0 +1,547 @@
+use crate::hearts::COLORS;
+use crate::models::{CardTypeSpecifics, FullCard, HeartColor, PageSize};
+use miniz_oxide::deflate::{CompressionLevel, compress_to_vec_zlib};
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+/// The size of a card, in millimetres.
+const CARD_WIDTH_MM: f32 = 63.0;
+const CARD_HEIGHT_MM: f32 = 88.0;
+
+/// The number of cards across and down a page.
+const COLUMNS: usize = 3;
+const ROWS: usize = 3;
+
+/// Cut marks are drawn in the margin, starting this far from the grid.
+const CUT_MARK_GAP_MM: f32 = 1.0;
+const CUT_MARK_LENGTH_MM: f32 = 5.0;
+
+/// The inner padding of a text-only proxy.
+const TEXT_PADDING_MM: f32 = 3.0;
+
+/// The average advance of a Helvetica glyph, relative to the font size.
+const HELVETICA_AVERAGE_ADVANCE: f32 = 0.55;
+
+const FONT_NAME: Name<'static> = Name(b"F1");
+
+/// Converts millimetres to PDF points.
+fn mm(value: f32) -> f32 {
+    value * 72.0 / 25.4
+}
+
+/// Returns the width and height of a page, in points.
+fn page_dimensions(page_size: PageSize) -> (f32, f32) {
+    match page_size {
+        PageSize::A4 => (mm(210.0), mm(297.0)),
+        PageSize::Letter => (mm(215.9), mm(279.4)),
+    }
+}
+
+/// The font used by text-only proxies.
+enum TextFont<'a> {
+    /// The built-in Helvetica, which only covers Latin text. Other characters print as `?`.
+    Helvetica,
+    /// An embedded TrueType font, which can cover Japanese text.
+    TrueType {
+        face: Box<ttf_parser::Face<'a>>,
+        data: &'a [u8],
+        /// The glyphs shown so far, with the character each one stands for.
+        used: RefCell<BTreeMap<u16, char>>,
+    },
+}
+
+impl TextFont<'_> {
+    /// The number of references reserved for the font objects.
+    const REFS: i32 = 5;
+
+    /// Encodes text as the bytes of a PDF string in this font.
+    fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            TextFont::Helvetica => text
+                .chars()
+                .map(|c| match c as u32 {
+                    code @ (0x20..=0x7E | 0xA0..=0xFF) => code as u8,
+                    _ => b'?',
+                })
+                .collect(),
+            TextFont::TrueType { face, used, .. } => {
+                let mut used = used.borrow_mut();
+                text.chars()
+                    .flat_map(|c| {
+                        let glyph = face.glyph_index(c).map_or(0, |glyph| glyph.0);
+                        used.entry(glyph).or_insert(c);
+                        glyph.to_be_bytes()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Measures the width of text at the given font size, in points.
+    fn width(&self, text: &str, size: f32) -> f32 {
+        match self {
+            TextFont::Helvetica => text.chars().count() as f32 * HELVETICA_AVERAGE_ADVANCE * size,
+            TextFont::TrueType { face, .. } => {
+                let units: f32 = text
+                    .chars()
+                    .filter_map(|c| face.glyph_index(c))
+                    .filter_map(|glyph| face.glyph_hor_advance(glyph))
+                    .map(f32::from)
+                    .sum();
+                units / f32::from(face.units_per_em()) * size
+            }
+        }
+    }
+
+    /// Writes the font objects into the references reserved from `font_id` on.
+    ///
+    /// This must be called after all text is encoded, so that the widths and the
+    /// Unicode mapping cover every glyph shown.
+    fn write(&self, pdf: &mut Pdf, font_id: Ref) {
+        let TextFont::TrueType { face, data, used } = self else {
+            pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
+            return;
+        };
+        let used = used.borrow();
+
+        let cid_font_id = Ref::new(font_id.get() + 1);
+        let descriptor_id = Ref::new(font_id.get() + 2);
+        let file_id = Ref::new(font_id.get() + 3);
+        let cmap_id = Ref::new(font_id.get() + 4);
+        let base_font = Name(b"ProxyFont");
+        let system_info = SystemInfo {
+            registry: Str(b"Adobe"),
+            ordering: Str(b"Identity"),
+            supplement: 0,
+        };
+        // PDF font metrics are expressed in thousandths of the font size.
+        let scale = 1000.0 / f32::from(face.units_per_em());
+
+        pdf.type0_font(font_id)
+            .base_font(base_font)
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_font_id)
+            .to_unicode(cmap_id);
+
+        let mut cid_font = pdf.cid_font(cid_font_id);
+        cid_font
+            .subtype(CidFontType::Type2)
+            .base_font(base_font)
+            .system_info(system_info)
+            .font_descriptor(descriptor_id)
+            .cid_to_gid_map_predefined(Name(b"Identity"));
+        let mut widths = cid_font.widths();
+        for &glyph in used.keys() {
+            let advance = face
+                .glyph_hor_advance(ttf_parser::GlyphId(glyph))
+                .unwrap_or(0);
+            widths.consecutive(glyph, [f32::from(advance) * scale]);
+        }
+        widths.finish();
+        cid_font.finish();
+
+        let bbox = face.global_bounding_box();
+        pdf.font_descriptor(descriptor_id)
+            .name(base_font)
+            .flags(FontFlags::SYMBOLIC)
+            .bbox(Rect::new(
+                f32::from(bbox.x_min) * scale,
+                f32::from(bbox.y_min) * scale,
+                f32::from(bbox.x_max) * scale,
+                f32::from(bbox.y_max) * scale,
+            ))
+            .italic_angle(0.0)
+            .ascent(f32::from(face.ascender()) * scale)
+            .descent(f32::from(face.descender()) * scale)
+            .cap_height(f32::from(face.capital_height().unwrap_or(face.ascender())) * scale)
+            .stem_v(80.0)
+            .font_file2(file_id);
+
+        let compressed = compress_to_vec_zlib(data, CompressionLevel::DefaultLevel as u8);
+        pdf.stream(file_id, &compressed)
+            .filter(Filter::FlateDecode)
+            .pair(Name(b"Length1"), data.len() as i32);
+
+        // Map glyphs back to text, so that the proxies can be searched and copied.
+        let mut cmap = UnicodeCmap::new(Name(b"Custom"), system_info);
+        // Glyph 0 stands for every character missing from the font, so it is left unmapped.
+        for (&glyph, &c) in used.iter().filter(|(glyph, _)| **glyph != 0) {
+            cmap.pair(glyph, c);
+        }
+        pdf.cmap(cmap_id, &cmap.finish());
+    }
+}
+
+/// Breaks text into lines that fit the given width.
+///
+/// Lines are broken at the last space where possible, and between any two characters
+/// otherwise, since Japanese text has no spaces.
+fn wrap(font: &TextFont, text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for c in paragraph.chars() {
+            line.push(c);
+            if font.width(&line, size) <= max_width {
+                continue;
+            }
+            line.pop();
+            let rest = match line.rfind(' ') {
+                Some(index) if index > 0 => {
+                    let rest = line[index + 1..].to_string();
+                    line.truncate(index);
+                    rest
+                }
+                _ => String::new(),
+            };
+            lines.push(std::mem::take(&mut line));
+            line = rest;
+            line.push(c);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Describes the type-specific stats of a card on a single line.
+fn stats_line(card: &FullCard) -> String {
+    let mut parts = vec![format!("{:?}", card.base.card_type)];
+    match &card.type_specifics {
+        Some(CardTypeSpecifics::Character(c)) => {
+            parts.push(format!("Cost {}", c.cost));
+            parts.push(format!("Blades {}", c.blades));
+            if let Some(blade_heart) = c.blade_heart {
+                parts.push(format!("Blade heart {:?}", blade_heart));
+            }
+        }
+        Some(CardTypeSpecifics::Live(l)) => {
+            parts.push(format!("Score {}", l.score));
+            if let Some(blade_heart) = l.blade_heart {
+                parts.push(format!("Blade heart {:?}", blade_heart));
+            }
+            if let Some(special_heart) = l.special_heart {
+                parts.push(format!("Special heart {:?}", special_heart));
+            }
+        }
+        None => {}
+    }
+    parts.join(" | ")
+}
+
+/// Lists a card's hearts in color order, e.g. `Hearts: Pink 2, Gray 1`.
+fn hearts_line(card: &FullCard) -> Option<String> {
+    let hearts: Vec<String> = COLORS
+        .iter()
+        .chain([HeartColor::Gray].iter())
+        .filter_map(|color| {
+            card.hearts
+                .get(color)
+                .map(|count| format!("{:?} {}", color, count))
+        })
+        .collect();
+    (!hearts.is_empty()).then(|| format!("Hearts: {}", hearts.join(", ")))
+}
+
+/// Draws a text-only proxy for a card into the given rectangle.
+fn draw_text_proxy(
+    content: &mut Content,
+    font: &TextFont,
+    card: &FullCard,
+    (x, y, width, height): (f32, f32, f32, f32),
+) {
+    content.set_line_width(0.5);
+    content.rect(x, y, width, height).stroke();
+
+    let padding = mm(TEXT_PADDING_MM);
+    let text_width = width - 2.0 * padding;
+    let bottom = y + padding;
+    let mut cursor = y + height - padding;
+
+    let mut lines: Vec<(String, f32)> = Vec::new();
+    let mut push = |text: &str, size: f32| {
+        for line in wrap(font, text, size, text_width) {
+            lines.push((line, size));
+        }
+    };
+    push(&card.base.name, 11.0);
+    push(&stats_line(card), 7.0);
+    if let Some(hearts) = hearts_line(card) {
+        push(&hearts, 7.0);
+    }
+    let affiliations: Vec<&str> = card
+        .groups
+        .iter()
+        .chain(card.units.iter())
+        .map(String::as_str)
+        .collect();
+    if !affiliations.is_empty() {
+        push(&affiliations.join(" / "), 7.0);
+    }
+    for skill in &card.skills {
+        push(skill, 6.5);
+    }
+
+    let identifier = card.printings.first().map_or_else(
+        || {
+            format!(
+                "{}-{}-{}",
+                card.base.series_code, card.base.set_code, card.base.number_in_set
+            )
+        },
+        |printing| {
+            format!(
+                "{}-{}-{}-{}",
+                card.base.series_code,
+                card.base.set_code,
+                card.base.number_in_set,
+                printing.rarity_code
+            )
+        },
+    );
+    let footer_size = 6.5;
+
+    content.begin_text();
+    for (line, size) in &lines {
+        cursor -= size * 1.25;
+        // Leave room for the footer; anything that does not fit is cut off.
+        if cursor < bottom + footer_size * 1.5 {
+            break;
+        }
+        content.set_font(FONT_NAME, *size);
+        content.set_text_matrix([1.0, 0.0, 0.0, 1.0, x + padding, cursor]);
+        content.show(Str(&font.encode(line)));
+    }
+    content.set_font(FONT_NAME, footer_size);
+    content.set_text_matrix([1.0, 0.0, 0.0, 1.0, x + padding, bottom]);
+    content.show(Str(&font.encode(&identifier)));
+    content.end_text();
+}
+
+/// Draws cut marks in the margins around a grid, along every card edge.
+fn draw_cut_marks(content: &mut Content, (left, bottom, right, top): (f32, f32, f32, f32)) {
+    let gap = mm(CUT_MARK_GAP_MM);
+    let length = mm(CUT_MARK_LENGTH_MM);
+    let card_width = mm(CARD_WIDTH_MM);
+    let card_height = mm(CARD_HEIGHT_MM);
+
+    content.set_line_width(0.25);
+    for column in 0..=COLUMNS {
+        let x = left + column as f32 * card_width;
+        content.move_to(x, top + gap).line_to(x, top + gap + length);
+        content
+            .move_to(x, bottom - gap)
+            .line_to(x, bottom - gap - length);
+    }
+    for row in 0..=ROWS {
+        let y = bottom + row as f32 * card_height;
+        content
+            .move_to(left - gap, y)
+            .line_to(left - gap - length, y);
+        content
+            .move_to(right + gap, y)
+            .line_to(right + gap + length, y);
+    }
+    content.stroke();
+}
+
+/// Renders a deck as a PDF of printable proxies, laid out 3x3 per page at real card size.
+///
+/// `images` maps card IDs to the encoded image file shown for that card. Cards without
+/// an image, or whose image cannot be decoded, are drawn as text-only proxies instead,
+/// using `font` if given or the built-in Helvetica otherwise.
+///
+/// # Errors
+/// Returns an error if `font` is not a valid font file.
+pub fn render_proxy_sheet(
+    deck: &[(FullCard, i64)],
+    images: &HashMap<i64, Vec<u8>>,
+    page_size: PageSize,
+    font: Option<&[u8]>,
+) -> Result<Vec<u8>, ttf_parser::FaceParsingError> {
+    let font = match font {
+        Some(data) => TextFont::TrueType {
+            face: Box::new(ttf_parser::Face::parse(data, 0)?),
+            data,
+            used: RefCell::new(BTreeMap::new()),
+        },
+        None => TextFont::Helvetica,
+    };
+
+    let mut pdf = Pdf::new();
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+    let mut next_id = Ref::new(font_id.get() + TextFont::REFS);
+
+    // Embed each decodable image once, however many copies of the card are printed.
+    let mut image_names: HashMap<i64, String> = HashMap::new();
+    let mut image_ids: Vec<(String, Ref)> = Vec::new();
+    for (card, _) in deck {
+        let Some(decoded) = images
+            .get(&card.base.id)
+            .and_then(|data| image::load_from_memory(data).ok())
+        else {
+            continue;
+        };
+        let rgb = decoded.to_rgb8();
+        let encoded = compress_to_vec_zlib(rgb.as_raw(), CompressionLevel::DefaultLevel as u8);
+        let image_id = next_id;
+        next_id = Ref::new(next_id.get() + 1);
+
+        let mut image = pdf.image_xobject(image_id, &encoded);
+        image.filter(Filter::FlateDecode);
+        image.width(rgb.width() as i32);
+        image.height(rgb.height() as i32);
+        image.color_space().device_rgb();
+        image.bits_per_component(8);
+        image.finish();
+
+        let name = format!("Im{}", card.base.id);
+        image_names.insert(card.base.id, name.clone());
+        image_ids.push((name, image_id));
+    }
+
+    let (page_width, page_height) = page_dimensions(page_size);
+    let card_width = mm(CARD_WIDTH_MM);
+    let card_height = mm(CARD_HEIGHT_MM);
+    let grid_left = (page_width - COLUMNS as f32 * card_width) / 2.0;
+    let grid_top = (page_height + ROWS as f32 * card_height) / 2.0;
+
+    let copies: Vec<&FullCard> = deck
+        .iter()
+        .flat_map(|(card, count)| std::iter::repeat_n(card, (*count).max(0) as usize))
+        .collect();
+
+    let mut page_ids = Vec::new();
+    for page_cards in copies.chunks(COLUMNS * ROWS) {
+        let page_id = next_id;
+        let content_id = Ref::new(next_id.get() + 1);
+        next_id = Ref::new(next_id.get() + 2);
+        page_ids.push(page_id);
+
+        let mut content = Content::new();
+        let rows_used = page_cards.len().div_ceil(COLUMNS);
+        draw_cut_marks(
+            &mut content,
+            (
+                grid_left,
+                grid_top - rows_used as f32 * card_height,
+                grid_left + COLUMNS as f32 * card_width,
+                grid_top,
+            ),
+        );
+        for (index, card) in page_cards.iter().enumerate() {
+            let x = grid_left + (index % COLUMNS) as f32 * card_width;
+            let y = grid_top - (index / COLUMNS + 1) as f32 * card_height;
+            match image_names.get(&card.base.id) {
+                Some(name) => {
+                    content.save_state();
+                    content.transform([card_width, 0.0, 0.0, card_height, x, y]);
+                    content.x_object(Name(name.as_bytes()));
+                    content.restore_state();
+                }
+                None => draw_text_proxy(&mut content, &font, card, (x, y, card_width, card_height)),
+            }
+        }
+        pdf.stream(content_id, &content.finish());
+
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, page_width, page_height));
+        page.parent(page_tree_id);
+        page.contents(content_id);
+        let mut resources = page.resources();
+        resources.fonts().pair(FONT_NAME, font_id);
+        let mut x_objects = resources.x_objects();
+        for (name, image_id) in &image_ids {
+            x_objects.pair(Name(name.as_bytes()), *image_id);
+        }
+        x_objects.finish();
+        resources.finish();
+        page.finish();
+    }
+
+    font.write(&mut pdf, font_id);
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(page_ids.len() as i32);
+
+    Ok(pdf.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BaseCard, CardType, CharacterCard};
+
+    fn member(id: i64, name: &str) -> FullCard {
+        FullCard {
+            base: BaseCard {
+                id,
+                series_code: "PL!".to_string(),
+                set_code: "BP01".to_string(),
+                number_in_set: format!("{id:03}"),
+                name: name.to_string(),
+                card_type: CardType::Character,
+            },
+            set_name: "Booster Pack Vol. 1".to_string(),
+            groups: vec!["Love Live!".to_string()],
+            units: Vec::new(),
+            skills: vec!["Draw a card, then discard a card from your hand.".to_string()],
+            hearts: HashMap::from([(HeartColor::Pink, 2), (HeartColor::Gray, 1)]),
+            printings: Vec::new(),
+            type_specifics: Some(CardTypeSpecifics::Character(CharacterCard {
+                card_id: id,
+                cost: 4,
+                blades: 2,
+                blade_heart: None,
+            })),
+        }
+    }
+
+    #[test]
+    fn test_wrap_breaks_at_spaces_and_between_characters() {
+        let font = TextFont::Helvetica;
+        // Each character is 5.5pt wide at size 10, so 60pt fits 10 characters.
+        let lines = wrap(&font, "Draw a card then discard", 10.0, 60.0);
+        assert_eq!(lines, vec!["Draw a", "card then", "discard"]);
+
+        let lines = wrap(&font, "自分のステージにほかのメンバーがいない", 10.0, 60.0);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].chars().count(), 10);
+    }
+
+    #[test]
+    fn test_hearts_line_uses_color_order() {
+        let card = member(1, "Kousaka Honoka");
+        assert_eq!(
+            hearts_line(&card).as_deref(),
+            Some("Hearts: Pink 2, Gray 1")
+        );
+        assert_eq!(stats_line(&card), "Character | Cost 4 | Blades 2");
+    }
+
+    #[test]
+    fn test_render_proxy_sheet_paginates() {
+        let deck = vec![
+            (member(1, "Kousaka Honoka"), 4),
+            (member(2, "Ayase Eli"), 6),
+        ];
+        let pdf = render_proxy_sheet(&deck, &HashMap::new(), PageSize::Letter, None).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(pdf.starts_with(b"%PDF-"));
+        // 10 copies need two pages of nine.
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/Helvetica"));
+    }
+
+    #[test]
+    fn test_render_proxy_sheet_rejects_invalid_font() {
+        let deck = vec![(member(1, "Kousaka Honoka"), 1)];
+        let result = render_proxy_sheet(&deck, &HashMap::new(), PageSize::A4, Some(b"not a font"));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,533 @@
+use crate::{ApiState, auth, models::ApiKey};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// At most this many buckets are tracked; past it, the least recently used is dropped
+/// to bound memory use.
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+/// How long a key found by its token picks the client's bucket without looking it up
+/// again, and how many such keys are kept. Requests are authorized by looking their key
+/// up anew, so this only decides whose requests they are counted as.
+const KEY_CACHE_TTL: Duration = Duration::from_secs(60);
+const MAX_CACHED_KEYS: usize = 1_000;
+
+/// Whether a request reads or changes data. The two are limited separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    Read,
+    Write,
+}
+
+impl RequestKind {
+    /// `GET`, `HEAD` and `OPTIONS` requests are reads; every other method is a write.
+    pub fn of(method: &Method) -> Self {
+        if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+            RequestKind::Read
+        } else {
+            RequestKind::Write
+        }
+    }
+}
+
+/// A token bucket quota: a client may send `burst` requests at once, and earns
+/// `per_minute` requests back every minute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    per_minute: u32,
+    burst: u32,
+}
+
+impl Quota {
+    /// Creates a quota, raising a burst of `0` to one request. Returns `None` for a rate
+    /// of `0`, which would never earn requests back.
+    pub fn new(per_minute: u32, burst: u32) -> Option<Self> {
+        (per_minute > 0).then(|| Quota {
+            per_minute,
+            burst: burst.max(1),
+        })
+    }
+
+    fn refill_interval(&self) -> Duration {
+        Duration::from_secs(60) / self.per_minute
+    }
+}
+
+/// The limits applied to each client. `None` leaves that kind of request unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    pub read: Option<Quota>,
+    pub write: Option<Quota>,
+    /// Whether to identify anonymous clients by the last `X-Forwarded-For` address,
+    /// as set by a reverse proxy in front of the server, instead of the peer address.
+    pub trust_proxy: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            read: Some(Quota {
+                per_minute: 600,
+                burst: 600,
+            }),
+            write: Some(Quota {
+                per_minute: 60,
+                burst: 60,
+            }),
+            trust_proxy: false,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Reads the limits from `RATE_LIMIT_READS_PER_MINUTE`, `RATE_LIMIT_READ_BURST`,
+    /// `RATE_LIMIT_WRITES_PER_MINUTE`, `RATE_LIMIT_WRITE_BURST` and
+    /// `RATE_LIMIT_TRUST_PROXY`. A rate of `0` disables that limit, and bursts default
+    /// to one minute's worth of requests.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u32>().ok());
+        let quota = |rate: &str, burst: &str, default: Option<Quota>| match var(rate) {
+            Some(per_minute) => Quota::new(per_minute, var(burst).unwrap_or(per_minute)),
+            None => default
+                .and_then(|quota| Quota::new(quota.per_minute, var(burst).unwrap_or(quota.burst))),
+        };
+
+        let default = RateLimitConfig::default();
+        RateLimitConfig {
+            read: quota(
+                "RATE_LIMIT_READS_PER_MINUTE",
+                "RATE_LIMIT_READ_BURST",
+                default.read,
+            ),
+            write: quota(
+                "RATE_LIMIT_WRITES_PER_MINUTE",
+                "RATE_LIMIT_WRITE_BURST",
+                default.write,
+            ),
+            trust_proxy: std::env::var("RATE_LIMIT_TRUST_PROXY").is_ok_and(|v| v == "true"),
+        }
+    }
+
+    fn quota(&self, kind: RequestKind) -> Option<Quota> {
+        match kind {
+            RequestKind::Read => self.read,
+            RequestKind::Write => self.write,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// When the bucket was last used, as a key into [`Buckets::by_use`].
+    last_use: u64,
+}
+
+type BucketKey = (String, RequestKind);
+
+/// The buckets of every client, along with the order they were last used in.
+#[derive(Debug, Default)]
+struct Buckets {
+    by_client: HashMap<BucketKey, Bucket>,
+    by_use: BTreeMap<u64, BucketKey>,
+    uses: u64,
+}
+
+/// The outcome of taking a token from a client's bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub allowed: bool,
+    pub limit: u32,
+    /// The requests left right now.
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset: u64,
+    /// Seconds until the next request is allowed, if this one was not.
+    pub retry_after: Option<u64>,
+}
+
+/// In-memory token buckets, one per client and kind of request.
+///
+/// State is lost on restart and not shared between server processes.
+#[derive(Debug)]
+pub struct RateLimiter {
+    pub config: RateLimitConfig,
+    buckets: Mutex<Buckets>,
+    max_buckets: usize,
+    /// Keys recently found by the hash of their token, with when they were found.
+    keys: Mutex<HashMap<String, (ApiKey, Instant)>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            buckets: Mutex::new(Buckets::default()),
+            max_buckets: MAX_TRACKED_BUCKETS,
+            keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether a client's bucket has a request left, without taking it.
+    fn has_token(&self, client: &str, kind: RequestKind, now: Instant) -> bool {
+        let Some(quota) = self.config.quota(kind) else {
+            return true;
+        };
+        let buckets = self.buckets.lock().unwrap();
+        buckets
+            .by_client
+            .get(&(client.to_string(), kind))
+            .is_none_or(|bucket| {
+                let earned = now.saturating_duration_since(bucket.updated).as_secs_f64()
+                    / quota.refill_interval().as_secs_f64();
+                bucket.tokens + earned >= 1.0
+            })
+    }
+
+    /// Returns the key a token was recently found to belong to.
+    fn cached_key(&self, token: &str, now: Instant) -> Option<ApiKey> {
+        let keys = self.keys.lock().unwrap();
+        keys.get(&auth::hash_secret(token))
+            .filter(|(_, found)| now.saturating_duration_since(*found) < KEY_CACHE_TTL)
+            .map(|(key, _)| key.clone())
+    }
+
+    fn remember_key(&self, token: &str, key: ApiKey, now: Instant) {
+        let mut keys = self.keys.lock().unwrap();
+        if keys.len() >= MAX_CACHED_KEYS {
+            keys.retain(|_, (_, found)| now.saturating_duration_since(*found) < KEY_CACHE_TTL);
+            if keys.len() >= MAX_CACHED_KEYS {
+                keys.clear();
+            }
+        }
+        keys.insert(auth::hash_secret(token), (key, now));
+    }
+
+    /// Forgets the keys found so far, e.g. after one was revoked.
+    pub fn forget_keys(&self) {
+        self.keys.lock().unwrap().clear();
+    }
+
+    /// Takes a token from a client's bucket, if one is left.
+    ///
+    /// Returns `None` if this kind of request is unlimited.
+    pub fn acquire(
+        &self,
+        client: &str,
+        kind: RequestKind,
+        now: Instant,
+    ) -> Option<RateLimitStatus> {
+        let quota = self.config.quota(kind)?;
+        let refill_interval = quota.refill_interval().as_secs_f64();
+        let capacity = f64::from(quota.burst);
+        let refill = |bucket: &Bucket| {
+            let earned =
+                now.saturating_duration_since(bucket.updated).as_secs_f64() / refill_interval;
+            (bucket.tokens + earned).min(capacity)
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let Buckets {
+            by_client,
+            by_use,
+            uses,
+        } = &mut *buckets;
+        *uses += 1;
+        let key = (client.to_string(), kind);
+        if !by_client.contains_key(&key) {
+            while by_client.len() >= self.max_buckets
+                && let Some((_, oldest)) = by_use.pop_first()
+            {
+                by_client.remove(&oldest);
+            }
+        }
+        let bucket = by_client.entry(key.clone()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+            last_use: *uses,
+        });
+        by_use.remove(&bucket.last_use);
+        by_use.insert(*uses, key);
+        bucket.last_use = *uses;
+        bucket.tokens = refill(bucket);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        Some(RateLimitStatus {
+            allowed,
+            limit: quota.burst,
+            remaining: bucket.tokens as u32,
+            reset: ((capacity - bucket.tokens) * refill_interval).ceil() as u64,
+            retry_after: (!allowed)
+                .then(|| ((1.0 - bucket.tokens) * refill_interval).ceil() as u64),
+        })
+    }
+}
+
+/// Identifies the client of a request: by its API key if it sends a valid one, and
+/// by its IP address otherwise.
+///
+/// Keys are remembered by their token for a while, and unknown tokens are only looked
+/// up while the address has requests left, so that rate limited clients and bogus
+/// tokens cost no query.
+async fn client_id(
+    state: &ApiState,
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    kind: RequestKind,
+    now: Instant,
+) -> String {
+    let address = address_id(state, headers, peer);
+    let Some(token) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+    else {
+        return address;
+    };
+
+    let limiter = &state.rate_limiter;
+    let key = match limiter.cached_key(&token, now) {
+        Some(key) => Some(key),
+        None if limiter.has_token(&address, kind, now) => {
+            match auth::authenticate(&state.pool, &token).await {
+                Ok(key) => {
+                    if let Some(key) = &key {
+                        limiter.remember_key(&token, key.clone(), now);
+                    }
+                    key
+                }
+                Err(_) => return address,
+            }
+        }
+        None => return address,
+    };
+    match key {
+        Some(key) => format!("key:{}", key.id),
+        None => address,
+    }
+}
+
+/// Identifies a client by its IP address.
+fn address_id(state: &ApiState, headers: &HeaderMap, peer: Option<SocketAddr>) -> String {
+    let forwarded = state
+        .rate_limiter
+        .config
+        .trust_proxy
+        .then(|| {
+            headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit(',').next())
+                .map(|address| address.trim().to_string())
+        })
+        .flatten();
+    format!(
+        "ip:{}",
+        forwarded
+            .or_else(|| peer.map(|address| address.ip().to_string()))
+            .unwrap_or_else(|| "unknown".to_string())
+    )
+}
+
+fn insert_headers(headers: &mut HeaderMap, status: &RateLimitStatus) {
+    for (name, value) in [
+        ("ratelimit-limit", Some(u64::from(status.limit))),
+        ("ratelimit-remaining", Some(u64::from(status.remaining))),
+        ("ratelimit-reset", Some(status.reset)),
+        (header::RETRY_AFTER.as_str(), status.retry_after),
+    ] {
+        if let Some(value) = value {
+            headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+        }
+    }
+}
+
+/// Middleware limiting the rate of requests of each client, with separate buckets for
+/// reads and writes.
+///
+/// Responses carry `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset`
+/// headers. Requests over the limit get `429 Too Many Requests` with `Retry-After`.
+pub async fn limit(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let kind = RequestKind::of(request.method());
+    if state.rate_limiter.config.quota(kind).is_none() {
+        return next.run(request).await;
+    }
+
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| *address);
+    let now = Instant::now();
+    let client = client_id(&state, request.headers(), peer, kind, now).await;
+    let Some(status) = state.rate_limiter.acquire(&client, kind, now) else {
+        return next.run(request).await;
+    };
+
+    let mut response = if status.allowed {
+        next.run(request).await
+    } else {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            "Rate limit exceeded. Retry later.".to_string(),
+        )
+            .into_response()
+    };
+    insert_headers(response.headers_mut(), &status);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> RateLimiter {
+        limiter_with(MAX_TRACKED_BUCKETS)
+    }
+
+    fn limiter_with(max_buckets: usize) -> RateLimiter {
+        let mut limiter = RateLimiter::new(RateLimitConfig {
+            read: Quota::new(60, 2),
+            write: None,
+            trust_proxy: false,
+        });
+        limiter.max_buckets = max_buckets;
+        limiter
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = limiter();
+        let start = Instant::now();
+
+        // The burst is spent, then one request is earned back every second.
+        let first = limiter.acquire("a", RequestKind::Read, start).unwrap();
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 1);
+        assert!(
+            limiter
+                .acquire("a", RequestKind::Read, start)
+                .unwrap()
+                .allowed
+        );
+        let limited = limiter.acquire("a", RequestKind::Read, start).unwrap();
+        assert!(!limited.allowed);
+        assert_eq!(limited.retry_after, Some(1));
+        assert_eq!(limited.reset, 2);
+
+        let later = start + Duration::from_secs(1);
+        assert!(
+            limiter
+                .acquire("a", RequestKind::Read, later)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            !limiter
+                .acquire("a", RequestKind::Read, later)
+                .unwrap()
+                .allowed
+        );
+
+        // Other clients have their own buckets, and writes are unlimited here.
+        assert!(
+            limiter
+                .acquire("b", RequestKind::Read, later)
+                .unwrap()
+                .allowed
+        );
+        assert_eq!(limiter.acquire("a", RequestKind::Write, later), None);
+    }
+
+    #[test]
+    fn test_bucket_never_exceeds_burst() {
+        let limiter = limiter();
+        let start = Instant::now();
+        limiter.acquire("a", RequestKind::Read, start);
+
+        let status = limiter
+            .acquire("a", RequestKind::Read, start + Duration::from_secs(3600))
+            .unwrap();
+        assert_eq!(status.remaining, 1);
+    }
+
+    #[test]
+    fn test_quota_needs_a_rate() {
+        assert_eq!(Quota::new(0, 1), None);
+        assert_eq!(
+            Quota::new(1, 0),
+            Some(Quota {
+                per_minute: 1,
+                burst: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_has_token_takes_none() {
+        let limiter = limiter();
+        let start = Instant::now();
+        assert!(limiter.has_token("a", RequestKind::Read, start));
+        limiter.acquire("a", RequestKind::Read, start);
+        assert!(limiter.has_token("a", RequestKind::Read, start));
+        limiter.acquire("a", RequestKind::Read, start);
+        assert!(!limiter.has_token("a", RequestKind::Read, start));
+        assert!(limiter.has_token("a", RequestKind::Read, start + Duration::from_secs(1)));
+        assert!(limiter.has_token("a", RequestKind::Write, start));
+    }
+
+    #[test]
+    fn test_least_recently_used_bucket_is_dropped() {
+        let limiter = limiter_with(2);
+        let start = Instant::now();
+        limiter.acquire("a", RequestKind::Read, start);
+        limiter.acquire("a", RequestKind::Read, start);
+        limiter.acquire("b", RequestKind::Read, start);
+        limiter.acquire("b", RequestKind::Read, start);
+
+        // `a` was used last, so `b` makes room for `c`; `a` keeps its spent bucket.
+        assert!(
+            !limiter
+                .acquire("a", RequestKind::Read, start)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            limiter
+                .acquire("c", RequestKind::Read, start)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            !limiter
+                .acquire("a", RequestKind::Read, start)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            limiter
+                .acquire("b", RequestKind::Read, start)
+                .unwrap()
+                .allowed
+        );
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.by_client.len(), 2);
+        assert_eq!(buckets.by_use.len(), 2);
+    }
+}
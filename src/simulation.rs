@@ -0,0 +1,87 @@
+use crate::hearts::{self, HeartPool};
+use crate::models::{BladeHeartColor, CardType, CardTypeSpecifics, FullCard, SimulationResponse};
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+/// Returns the blade heart printed on a card, if any.
+fn blade_heart(card: &FullCard) -> Option<BladeHeartColor> {
+    match &card.type_specifics {
+        Some(CardTypeSpecifics::Character(c)) => c.blade_heart,
+        Some(CardTypeSpecifics::Live(l)) => l.blade_heart,
+        None => None,
+    }
+}
+
+/// Returns the number of blades on a member card.
+fn blades(card: &FullCard) -> i64 {
+    match &card.type_specifics {
+        Some(CardTypeSpecifics::Character(c)) => c.blades,
+        _ => 0,
+    }
+}
+
+/// Estimates the probability that a stage lineup clears a Live card.
+///
+/// Each trial shuffles the deck, reveals one yell card per blade on stage, and
+/// adds the revealed blade hearts to the hearts of the members on stage.
+/// The same seed always produces the same result.
+///
+/// # Arguments
+/// * `stage` - The member cards on stage, paired with their copy counts.
+/// * `deck` - The deck that yells are revealed from, paired with copy counts. Energy
+///   cards in it are left out.
+/// * `live` - The Live card whose heart requirements must be met.
+/// * `trials` - The number of yells to simulate.
+/// * `seed` - The seed for the random number generator.
+pub fn simulate_live(
+    stage: &[(FullCard, i64)],
+    deck: &[(FullCard, i64)],
+    live: &FullCard,
+    trials: u32,
+    seed: u64,
+) -> SimulationResponse {
+    // The hearts of the members on stage are the same in every trial.
+    let mut stage_hearts = HeartPool::default();
+    let mut yell_count = 0;
+    for (card, count) in stage {
+        for _ in 0..*count {
+            stage_hearts.add_all(&card.hearts);
+        }
+        yell_count += blades(card) * count;
+    }
+
+    // Yells are revealed from the main deck, where only the blade heart of each card
+    // matters; the Energy deck is kept apart.
+    let mut yell_deck: Vec<Option<BladeHeartColor>> = deck
+        .iter()
+        .filter(|(card, _)| card.base.card_type != CardType::Energy)
+        .flat_map(|(card, count)| std::iter::repeat_n(blade_heart(card), *count as usize))
+        .collect();
+    let reveal = (yell_count.max(0) as usize).min(yell_deck.len());
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut successes = 0;
+    for _ in 0..trials {
+        let (revealed, _) = yell_deck.partial_shuffle(&mut rng, reveal);
+        let mut pool = stage_hearts.clone();
+        for blade_heart in revealed.iter().flatten() {
+            pool.add_blade_heart(*blade_heart);
+        }
+        if hearts::can_satisfy(&pool, &live.hearts) {
+            successes += 1;
+        }
+    }
+
+    SimulationResponse {
+        seed,
+        trials,
+        successes,
+        probability: if trials == 0 {
+            0.0
+        } else {
+            f64::from(successes) / f64::from(trials)
+        },
+        yell_count,
+    }
+}
@@ -0,0 +1,224 @@
+use crate::models::{
+    CardType, DeckListCard, FullCard, Printing, TtsCustomDeck, TtsObject, TtsSavedObject,
+    TtsTransform,
+};
+use std::collections::BTreeMap;
+
+/// The stacks of an exported deck, in the order they are laid out on the table.
+const STACKS: [(CardType, &str); 3] = [
+    (CardType::Character, "Members"),
+    (CardType::Live, "Lives"),
+    (CardType::Energy, "Energy"),
+];
+
+/// The distance between two stacks on the table.
+const STACK_SPACING: f64 = 3.0;
+
+/// Picks the printing whose image represents a card: the first one that has an image.
+pub fn preferred_printing(card: &FullCard) -> Option<&Printing> {
+    card.printings
+        .iter()
+        .find(|printing| printing.image_url.is_some())
+}
+
+/// A face-down transform at the given position on the table.
+fn face_down_at(pos_x: f64) -> TtsTransform {
+    TtsTransform {
+        pos_x,
+        pos_y: 1.0,
+        pos_z: 0.0,
+        rot_x: 0.0,
+        rot_y: 180.0,
+        rot_z: 180.0,
+        scale_x: 1.0,
+        scale_y: 1.0,
+        scale_z: 1.0,
+    }
+}
+
+/// Builds a Tabletop Simulator saved object for a deck.
+///
+/// Member, Live and Energy cards are placed in separate stacks side by side. Every card
+/// uses the image of its [`preferred_printing`] as the face, on a card sheet of its own.
+/// A stack holding a single card is exported as a lone card, as Tabletop Simulator expects.
+pub fn build_saved_object(deck: &[(FullCard, i64)], card_back_url: &str) -> TtsSavedObject {
+    let mut object_states = Vec::new();
+    // Sheet numbers must be unique across the whole save.
+    let mut sheet = 1;
+
+    for (index, (card_type, nickname)) in STACKS.iter().enumerate() {
+        let transform = face_down_at(index as f64 * STACK_SPACING);
+        let mut cards = Vec::new();
+
+        for (card, count) in deck
+            .iter()
+            .filter(|(card, _)| card.base.card_type == *card_type)
+        {
+            let face_url = preferred_printing(card)
+                .and_then(|printing| printing.image_url.clone())
+                .unwrap_or_default();
+            let custom_deck = BTreeMap::from([(
+                sheet.to_string(),
+                TtsCustomDeck {
+                    face_url,
+                    back_url: card_back_url.to_string(),
+                    num_width: 1,
+                    num_height: 1,
+                    back_is_hidden: true,
+                    unique_back: false,
+                },
+            )]);
+            for _ in 0..*count {
+                cards.push(TtsObject {
+                    name: "Card".to_string(),
+                    nickname: card.base.name.clone(),
+                    transform,
+                    card_id: Some(sheet * 100),
+                    deck_ids: Vec::new(),
+                    custom_deck: custom_deck.clone(),
+                    contained_objects: Vec::new(),
+                });
+            }
+            sheet += 1;
+        }
+
+        match cards.len() {
+            0 => {}
+            1 => object_states.extend(cards),
+            _ => object_states.push(TtsObject {
+                name: "Deck".to_string(),
+                nickname: nickname.to_string(),
+                transform,
+                card_id: None,
+                deck_ids: cards.iter().filter_map(|card| card.card_id).collect(),
+                custom_deck: cards
+                    .iter()
+                    .flat_map(|card| card.custom_deck.clone())
+                    .collect(),
+                contained_objects: cards,
+            }),
+        }
+    }
+
+    TtsSavedObject { object_states }
+}
+
+/// Builds a plain card list for a deck, for virtual tabletops other than Tabletop Simulator.
+pub fn build_card_list(deck: &[(FullCard, i64)]) -> Vec<DeckListCard> {
+    deck.iter()
+        .map(|(card, count)| {
+            let printing = preferred_printing(card).or(card.printings.first());
+            DeckListCard {
+                card_id: card.base.id,
+                printing_id: printing.map(|printing| printing.id),
+                card_identifier: format!(
+                    "{}-{}-{}-{}",
+                    card.base.series_code,
+                    card.base.set_code,
+                    card.base.number_in_set,
+                    printing.map_or("", |printing| printing.rarity_code.as_str())
+                ),
+                name: card.base.name.clone(),
+                card_type: card.base.card_type,
+                count: *count,
+                image_url: printing.and_then(|printing| printing.image_url.clone()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BaseCard, RarityType};
+    use std::collections::HashMap;
+
+    fn card(id: i64, card_type: CardType, image_url: Option<&str>) -> FullCard {
+        FullCard {
+            base: BaseCard {
+                id,
+                series_code: "PL!".to_string(),
+                set_code: "BP01".to_string(),
+                number_in_set: format!("{id:03}"),
+                name: format!("Card {id}"),
+                card_type,
+            },
+            set_name: "Booster Pack Vol. 1".to_string(),
+            groups: Vec::new(),
+            units: Vec::new(),
+            skills: Vec::new(),
+            hearts: HashMap::new(),
+            printings: vec![Printing {
+                id: id * 10,
+                card_id: id,
+                rarity_code: "R".to_string(),
+                rarity_type: RarityType::Regular,
+                image_url: image_url.map(str::to_string),
+                thumbnail_url: None,
+                medium_url: None,
+            }],
+            type_specifics: None,
+        }
+    }
+
+    #[test]
+    fn test_build_saved_object_separates_stacks() {
+        let deck = vec![
+            (
+                card(1, CardType::Character, Some("https://example.com/1.png")),
+                2,
+            ),
+            (
+                card(2, CardType::Live, Some("https://example.com/2.png")),
+                1,
+            ),
+            (
+                card(3, CardType::Character, Some("https://example.com/3.png")),
+                1,
+            ),
+        ];
+        let saved = build_saved_object(&deck, "https://example.com/back.png");
+
+        // No Energy cards, so only the Member deck and a lone Live card are exported.
+        assert_eq!(saved.object_states.len(), 2);
+        let members = &saved.object_states[0];
+        assert_eq!(members.name, "Deck");
+        assert_eq!(members.deck_ids, vec![100, 100, 200]);
+        assert_eq!(members.custom_deck.len(), 2);
+        assert_eq!(
+            members.custom_deck["2"].face_url,
+            "https://example.com/3.png"
+        );
+        assert_eq!(members.contained_objects.len(), 3);
+
+        let live = &saved.object_states[1];
+        assert_eq!(live.name, "Card");
+        assert_eq!(live.card_id, Some(300));
+        assert_eq!(
+            live.custom_deck["3"].back_url,
+            "https://example.com/back.png"
+        );
+        assert!(live.transform.pos_x > members.transform.pos_x);
+    }
+
+    #[test]
+    fn test_build_card_list_prefers_printing_with_image() {
+        let mut member = card(1, CardType::Character, None);
+        member.printings.push(Printing {
+            id: 11,
+            card_id: 1,
+            rarity_code: "P".to_string(),
+            rarity_type: RarityType::Parallel,
+            image_url: Some("https://example.com/1-p.png".to_string()),
+            thumbnail_url: None,
+            medium_url: None,
+        });
+        let list = build_card_list(&[(member, 4), (card(2, CardType::Energy, None), 12)]);
+
+        assert_eq!(list[0].printing_id, Some(11));
+        assert_eq!(list[0].card_identifier, "PL!-BP01-001-P");
+        assert_eq!(list[0].count, 4);
+        assert_eq!(list[1].printing_id, Some(20));
+        assert_eq!(list[1].image_url, None);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::models::{OwnedPrinting, RarityType, TradeOffer, Want};
+use std::collections::HashMap;
+
+/// Computes how many copies of each printing a collection can spare.
+///
+/// Quantities are only bounded one at a time, so they are added up saturating.
+///
+/// Copies covered by the collection's own wants for that exact printing are kept.
+/// A want for a base card keeps that many copies of any of its printings, counting
+/// those already kept for printing wants.
+fn surplus(owned: &[OwnedPrinting], wants: &[Want]) -> Vec<(OwnedPrinting, i64)> {
+    let mut available: Vec<(OwnedPrinting, i64)> = owned
+        .iter()
+        .map(|printing| {
+            let keep: i64 = wants
+                .iter()
+                .filter(|want| want.printing_id == Some(printing.printing_id))
+                .map(|want| want.quantity)
+                .fold(0, i64::saturating_add);
+            (printing.clone(), (printing.quantity - keep).max(0))
+        })
+        .collect();
+
+    let mut card_wants: HashMap<i64, i64> = HashMap::new();
+    for want in wants {
+        if let (None, Some(card_id)) = (want.printing_id, want.card_id) {
+            let wanted = card_wants.entry(card_id).or_default();
+            *wanted = wanted.saturating_add(want.quantity);
+        }
+    }
+    for (card_id, quantity) in card_wants {
+        let kept: i64 = available
+            .iter()
+            .filter(|(printing, _)| printing.card_id == card_id)
+            .map(|(printing, spare)| printing.quantity - spare)
+            .fold(0, i64::saturating_add);
+        let mut outstanding = quantity - kept;
+        for (_, spare) in available
+            .iter_mut()
+            .filter(|(printing, _)| printing.card_id == card_id)
+        {
+            if outstanding <= 0 {
+                break;
+            }
+            let keep = outstanding.min(*spare);
+            *spare -= keep;
+            outstanding -= keep;
+        }
+    }
+
+    available.retain(|(_, spare)| *spare > 0);
+    available
+}
+
+/// Computes which cards a giving collection can trade to a receiving collection.
+///
+/// Each of the receiver's wants is reduced by the copies it already owns, and the
+/// remainder is matched against the giver's surplus. A want for a specific printing
+/// only matches that printing, while a want for a base card matches any printing of it.
+///
+/// # Arguments
+/// * `giver_owned` / `giver_wants` - The collection and want list of the giving side.
+/// * `receiver_owned` / `receiver_wants` - The collection and want list of the receiving side.
+/// * `exclude_parallel` - Whether Parallel printings may be matched at all.
+pub fn match_trades(
+    giver_owned: &[OwnedPrinting],
+    giver_wants: &[Want],
+    receiver_owned: &[OwnedPrinting],
+    receiver_wants: &[Want],
+    exclude_parallel: bool,
+) -> Vec<TradeOffer> {
+    let mut available = surplus(giver_owned, giver_wants);
+    if exclude_parallel {
+        available.retain(|(printing, _)| printing.rarity_type != RarityType::Parallel);
+    }
+
+    let mut offers: HashMap<i64, TradeOffer> = HashMap::new();
+    for want in receiver_wants {
+        let already_owned: i64 = receiver_owned
+            .iter()
+            .filter(|owned| match (want.printing_id, want.card_id) {
+                (Some(printing_id), _) => owned.printing_id == printing_id,
+                (None, Some(card_id)) => {
+                    owned.card_id == card_id
+                        && !(exclude_parallel && owned.rarity_type == RarityType::Parallel)
+                }
+                (None, None) => false,
+            })
+            .map(|owned| owned.quantity)
+            .fold(0, i64::saturating_add);
+        let mut outstanding = want.quantity - already_owned;
+
+        for (printing, spare) in available.iter_mut() {
+            if outstanding <= 0 {
+                break;
+            }
+            let matches = match (want.printing_id, want.card_id) {
+                (Some(printing_id), _) => printing.printing_id == printing_id,
+                (None, Some(card_id)) => printing.card_id == card_id,
+                (None, None) => false,
+            };
+            if !matches || *spare == 0 {
+                continue;
+            }
+
+            let quantity = outstanding.min(*spare);
+            *spare -= quantity;
+            outstanding -= quantity;
+            offers
+                .entry(printing.printing_id)
+                .or_insert_with(|| TradeOffer {
+                    printing_id: printing.printing_id,
+                    card_id: printing.card_id,
+                    name: printing.name.clone(),
+                    rarity_code: printing.rarity_code.clone(),
+                    rarity_type: printing.rarity_type,
+                    quantity: 0,
+                })
+                .quantity += quantity;
+        }
+    }
+
+    let mut offers: Vec<TradeOffer> = offers.into_values().collect();
+    offers.sort_by_key(|offer| offer.printing_id);
+    offers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owned(
+        printing_id: i64,
+        card_id: i64,
+        rarity_type: RarityType,
+        quantity: i64,
+    ) -> OwnedPrinting {
+        OwnedPrinting {
+            printing_id,
+            card_id,
+            name: format!("Card {card_id}"),
+            rarity_code: match rarity_type {
+                RarityType::Regular => "R".to_string(),
+                RarityType::Parallel => "P".to_string(),
+            },
+            rarity_type,
+            quantity,
+        }
+    }
+
+    fn want(printing_id: Option<i64>, card_id: Option<i64>, quantity: i64) -> Want {
+        Want {
+            id: 0,
+            collection_id: 0,
+            printing_id,
+            card_id,
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_match_trades_printing_want_respects_own_wants() {
+        // The giver owns 3 copies of printing 1 but wants to keep 2 of them.
+        let giver_owned = vec![owned(1, 10, RarityType::Regular, 3)];
+        let giver_wants = vec![want(Some(1), None, 2)];
+        let receiver_wants = vec![want(Some(1), None, 4)];
+
+        let offers = match_trades(&giver_owned, &giver_wants, &[], &receiver_wants, false);
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].printing_id, 1);
+        assert_eq!(offers[0].quantity, 1);
+    }
+
+    #[test]
+    fn test_match_trades_card_want_respects_own_wants() {
+        // The giver owns 4 copies of card 10 over two printings, wants to keep 1 copy of
+        // printing 2, and 3 copies of the card in any printing.
+        let giver_owned = vec![
+            owned(1, 10, RarityType::Regular, 2),
+            owned(2, 10, RarityType::Parallel, 2),
+        ];
+        let giver_wants = vec![want(Some(2), None, 1), want(None, Some(10), 3)];
+        let receiver_wants = vec![want(None, Some(10), 4)];
+
+        let offers = match_trades(&giver_owned, &giver_wants, &[], &receiver_wants, false);
+        let total: i64 = offers.iter().map(|offer| offer.quantity).sum();
+        assert_eq!(total, 1);
+
+        // Without the printing want, the card want alone still keeps 3 copies.
+        let giver_wants = vec![want(None, Some(10), 3)];
+        let offers = match_trades(&giver_owned, &giver_wants, &[], &receiver_wants, false);
+        let total: i64 = offers.iter().map(|offer| offer.quantity).sum();
+        assert_eq!(total, 1);
+
+        // Wanting more copies than are owned leaves nothing to trade.
+        let giver_wants = vec![want(None, Some(10), 5)];
+        let offers = match_trades(&giver_owned, &giver_wants, &[], &receiver_wants, false);
+        assert!(offers.is_empty());
+    }
+
+    #[test]
+    fn test_match_trades_huge_quantities_saturate() {
+        let giver_owned = vec![
+            owned(1, 10, RarityType::Regular, i64::MAX),
+            owned(2, 10, RarityType::Regular, i64::MAX),
+        ];
+        let giver_wants = vec![
+            want(Some(1), None, i64::MAX),
+            want(Some(1), None, i64::MAX),
+            want(None, Some(10), i64::MAX),
+        ];
+        let receiver_owned = giver_owned.clone();
+        let receiver_wants = vec![want(None, Some(10), i64::MAX)];
+
+        let offers = match_trades(&giver_owned, &[], &receiver_owned, &receiver_wants, false);
+        assert!(offers.is_empty());
+        // The copies of printing 1 kept for its own wants cover the card want.
+        let offers = match_trades(&giver_owned, &giver_wants, &[], &receiver_wants, false);
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].printing_id, 2);
+        assert_eq!(offers[0].quantity, i64::MAX);
+    }
+
+    #[test]
+    fn test_match_trades_card_want_accepts_any_printing() {
+        let giver_owned = vec![
+            owned(1, 10, RarityType::Regular, 1),
+            owned(2, 10, RarityType::Parallel, 2),
+        ];
+        // The receiver wants 3 copies of card 10 and already owns one.
+        let receiver_owned = vec![owned(1, 10, RarityType::Regular, 1)];
+        let receiver_wants = vec![want(None, Some(10), 3)];
+
+        let offers = match_trades(&giver_owned, &[], &receiver_owned, &receiver_wants, false);
+        let total: i64 = offers.iter().map(|offer| offer.quantity).sum();
+        assert_eq!(total, 2);
+
+        // Excluding Parallel printings leaves only the Regular copy.
+        let offers = match_trades(&giver_owned, &[], &receiver_owned, &receiver_wants, true);
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].printing_id, 1);
+        assert_eq!(offers[0].quantity, 1);
+    }
+}
@@ -1,10 +1,25 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, StatusCode, header},
+    response::Response,
+};
 use llocg_backend_api::ApiState;
 use sqlx::sqlite::SqlitePoolOptions;
+use tower::ServiceExt; // for `oneshot`
+
+/// The `Authorization` header of the admin key every test environment starts with.
+#[allow(dead_code)]
+pub const ADMIN_AUTH: &str = "Bearer llocg_test0admin_secret";
 
 /// Helper function to set up a test environment with an in-memory DB.
+#[allow(dead_code)]
 pub async fn setup_test_env() -> ApiState {
     // 1. Create an in-memory SQLite database pool.
+    // Every connection to `sqlite::memory:` opens a separate database, so the pool
+    // is limited to a single connection to keep all queries on the migrated one.
     let pool = SqlitePoolOptions::new()
+        .max_connections(1)
         .connect("sqlite::memory:")
         .await
         .expect("Failed to create in-memory database pool.");
@@ -15,8 +30,115 @@ pub async fn setup_test_env() -> ApiState {
         .await
         .expect("Failed to run migrations on in-memory database.");
 
-    // 3. Create the app state with the migrated database.
+    // 3. Issue the admin key used to authorize writes.
+    llocg_backend_api::db::insert_api_key(
+        &pool,
+        "tests",
+        llocg_backend_api::models::Role::Admin,
+        "test0admin",
+        &llocg_backend_api::auth::hash_secret("secret"),
+    )
+    .await
+    .expect("Failed to issue the test API key.");
+
+    // 4. Create the app state with the migrated database.
     llocg_backend_api::create_app_state_with_pool(pool)
         .await
         .expect("Failed to create test app state.")
 }
+
+/// Sends a request with the given `Authorization` header, if any, and a JSON content type.
+#[allow(dead_code)]
+pub async fn send_as(
+    app: &Router,
+    auth: Option<&str>,
+    method: Method,
+    uri: &str,
+    body: impl Into<Body>,
+) -> Response {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(auth) = auth {
+        request = request.header(header::AUTHORIZATION, auth);
+    }
+    app.clone()
+        .oneshot(request.body(body.into()).unwrap())
+        .await
+        .unwrap()
+}
+
+/// Sends a JSON request with the admin key.
+#[allow(dead_code)]
+pub async fn send(app: &Router, method: Method, uri: &str, body: &str) -> Response {
+    send_as(app, Some(ADMIN_AUTH), method, uri, body.to_string()).await
+}
+
+/// Reads the body of a response as JSON, or `null` if it is not JSON.
+#[allow(dead_code)]
+pub async fn read_json(response: Response) -> serde_json::Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap_or_default()
+}
+
+/// Sends a JSON request with the admin key and returns the status code and JSON body.
+#[allow(dead_code)]
+pub async fn send_json(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    body: &str,
+) -> (StatusCode, serde_json::Value) {
+    let response = send(app, method, uri, body).await;
+    let status = response.status();
+    (status, read_json(response).await)
+}
+
+/// Gets a resource with the admin key, expecting it to be found, and returns its JSON.
+#[allow(dead_code)]
+pub async fn get_json(app: &Router, uri: &str) -> serde_json::Value {
+    let (status, json) = send_json(app, Method::GET, uri, "").await;
+    assert_eq!(status, StatusCode::OK, "GET {uri}");
+    json
+}
+
+/// Helper function to create a card through the API and return its ID.
+#[allow(dead_code)]
+pub async fn create_card(app: &Router, payload: &str) -> i64 {
+    let (status, card) = send_json(app, Method::POST, "/cards", payload).await;
+    assert_eq!(status, StatusCode::CREATED);
+    card["id"].as_i64().unwrap()
+}
+
+/// The payload of a Kousaka Honoka member card with the given identifier, with `fields`
+/// such as `groups` or `image_url` added to it or replacing its own.
+#[allow(dead_code)]
+pub fn honoka(identifier: &str, fields: serde_json::Value) -> String {
+    let mut card = serde_json::json!({
+        "card_identifier": identifier,
+        "name": "Kousaka Honoka",
+        "card_type": "Character",
+        "cost": 4,
+        "blades": 2,
+        "hearts": { "Pink": 2 },
+    });
+    if let serde_json::Value::Object(fields) = fields {
+        card.as_object_mut().unwrap().extend(fields);
+    }
+    card.to_string()
+}
+
+/// Creates a Kousaka Honoka member card with the given identifier and returns its ID.
+#[allow(dead_code)]
+pub async fn create_honoka(app: &Router, identifier: &str) -> i64 {
+    create_card(app, &honoka(identifier, serde_json::json!({}))).await
+}
+
+/// Like [`create_honoka`], with `fields` added to the card as by [`honoka`].
+#[allow(dead_code)]
+pub async fn create_honoka_with(app: &Router, identifier: &str, fields: serde_json::Value) -> i64 {
+    create_card(app, &honoka(identifier, fields)).await
+}
@@ -0,0 +1,88 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+mod common;
+
+fn admin(database: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_llocg-admin"))
+        .arg("--database-url")
+        .arg(format!("sqlite:{}", database.display()))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_admin_cli() {
+    let dir = tempfile::tempdir().unwrap();
+    let database = dir.path().join("llocg.db");
+
+    // 1. Commands other than migrate and import do not create a missing database.
+    let output = admin(&database, &["validate"]);
+    assert!(!output.status.success());
+    assert!(!database.exists());
+
+    // 2. Migrate creates the database, which the other commands can then use.
+    let output = admin(&database, &["migrate"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(database.exists());
+
+    let output = admin(&database, &["sets", "add", "BP05", "Booster Pack Vol.5"]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = admin(&database, &["sets", "list"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("BP05"));
+
+    let output = admin(&database, &["validate"]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = admin(&database, &["check-caches"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("All caches loaded.\n"));
+}
+
+#[test]
+fn test_dump_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let database = dir.path().join("llocg.db");
+    let cards = dir.path().join("cards.json");
+    let dump = dir.path().join("dump.json");
+
+    let output = admin(&database, &["migrate"]);
+    assert!(output.status.success(), "{:?}", output);
+    let card = common::honoka(
+        "PL!-BP01-001-R",
+        serde_json::json!({ "groups": ["Love Live!"] }),
+    );
+    std::fs::write(&cards, format!("[{card}]")).unwrap();
+    let output = admin(&database, &["import", "json", cards.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = admin(&database, &["units", "delete", "Printemps"]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = admin(&database, &["export", "dump", "-o", dump.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+
+    // Importing into a new database migrates it first, and restores the dump in full.
+    let restored = dir.path().join("restored.db");
+    let output = admin(&restored, &["import", "dump", dump.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("Restored 1 cards.\n"));
+    let output = admin(&restored, &["validate"]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = admin(&restored, &["trash", "list"]);
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("unit\tPrintemps"));
+
+    let restored_dump = dir.path().join("restored.json");
+    let output = admin(
+        &restored,
+        &["export", "dump", "-o", restored_dump.to_str().unwrap()],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        std::fs::read_to_string(&dump).unwrap(),
+        std::fs::read_to_string(&restored_dump).unwrap()
+    );
+
+    // Dumps are only restored into databases without cards.
+    let output = admin(&restored, &["import", "dump", dump.to_str().unwrap()]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,108 @@
+use axum::http::{self, StatusCode};
+use llocg_backend_api::create_router;
+
+mod common;
+
+#[tokio::test]
+async fn test_api_key_authorization() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let new_set = r#"{ "set_code": "test-set", "name": "Test Set" }"#;
+
+    // 1. Reads are public, writes need a key.
+    let response = common::send_as(&app, None, http::Method::GET, "/sets", "").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = common::send_as(&app, None, http::Method::POST, "/sets", new_set).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()[http::header::WWW_AUTHENTICATE], "Bearer");
+    let response = common::send_as(
+        &app,
+        Some("Bearer llocg_test0admin_wrong"),
+        http::Method::POST,
+        "/sets",
+        new_set,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // 2. An admin issues an editor key, whose token is only shown now.
+    let (status, issued) = common::send_json(
+        &app,
+        http::Method::POST,
+        "/api-keys",
+        r#"{ "name": "importer", "role": "editor" }"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(issued["role"], "editor");
+    let editor_id = issued["id"].as_i64().unwrap();
+    let editor_auth = format!("Bearer {}", issued["token"].as_str().unwrap());
+    let editor = Some(editor_auth.as_str());
+
+    // 3. Editors can add reference data, but not delete it or manage keys.
+    let response = common::send_as(&app, editor, http::Method::POST, "/sets", new_set).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = common::send_as(&app, editor, http::Method::DELETE, "/sets/test-set", "").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = common::send_as(&app, editor, http::Method::GET, "/api-keys", "").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // 4. Listed keys never include their secrets.
+    let keys = common::get_json(&app, "/api-keys").await;
+    assert_eq!(keys.as_array().unwrap().len(), 2);
+    assert!(keys[1].get("token").is_none());
+    assert!(keys[1].get("secret_hash").is_none());
+
+    // 5. Revoked keys stop working.
+    let uri = format!("/api-keys/{editor_id}");
+    let response = common::send(&app, http::Method::DELETE, &uri, "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = common::send_as(
+        &app,
+        editor,
+        http::Method::POST,
+        "/groups",
+        r#"{ "name": "Test Group" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = common::send(&app, http::Method::DELETE, &uri, "").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_keys_revoked_from_another_process_stop_working_at_once() {
+    let state = common::setup_test_env().await;
+    let pool = state.pool.clone();
+    let app = create_router(state);
+
+    let (_, issued) = common::send_json(
+        &app,
+        http::Method::POST,
+        "/api-keys",
+        r#"{ "name": "importer", "role": "editor" }"#,
+    )
+    .await;
+    let editor_auth = format!("Bearer {}", issued["token"].as_str().unwrap());
+    let editor = Some(editor_auth.as_str());
+    let add_group = |name: &'static str| {
+        common::send_as(
+            &app,
+            editor,
+            http::Method::POST,
+            "/groups",
+            format!(r#"{{ "name": "{name}" }}"#),
+        )
+    };
+    assert_eq!(add_group("Test Group").await.status(), StatusCode::CREATED);
+
+    // As `llocg-admin keys revoke` does, bypassing the server.
+    llocg_backend_api::db::revoke_api_key(&pool, issued["id"].as_i64().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        add_group("Other Group").await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
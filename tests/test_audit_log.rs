@@ -0,0 +1,112 @@
+use axum::{
+    Router,
+    http::{self, StatusCode},
+};
+use llocg_backend_api::create_router;
+use serde_json::Value;
+
+mod common;
+
+async fn get_log(app: &Router, query: &str) -> (StatusCode, Value) {
+    common::send_json(app, http::Method::GET, &format!("/audit-log{query}"), "").await
+}
+
+/// Creates the set `test-set` and deletes it again.
+async fn create_and_delete_set(app: &Router) {
+    let response = common::send(
+        app,
+        http::Method::POST,
+        "/sets",
+        r#"{ "set_code": "test-set", "name": "Test Set" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = common::send(app, http::Method::DELETE, "/sets/test-set", "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_set_changes_are_recorded_with_snapshots() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_and_delete_set(&app).await;
+
+    let (status, entries) = get_log(&app, "?entity_kind=set&entity_key=test-set").await;
+    assert_eq!(status, StatusCode::OK);
+    let entries = entries.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["action"], "delete");
+    assert_eq!(entries[0]["actor"], "tests");
+    assert_eq!(entries[0]["api_key_id"], 1);
+    assert_eq!(entries[0]["before"]["name"], "Test Set");
+    assert!(entries[0]["after"].is_null());
+    assert_eq!(entries[1]["action"], "create");
+    assert!(entries[1]["before"].is_null());
+    assert_eq!(entries[1]["after"]["set_code"], "test-set");
+}
+
+#[tokio::test]
+async fn test_created_cards_are_recorded_by_id() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let card_id = common::create_honoka(&app, "PL!-BP01-001-R").await;
+    let (_, entries) = get_log(&app, &format!("?entity_kind=card&entity_key={card_id}")).await;
+    assert_eq!(entries[0]["action"], "create");
+    assert_eq!(entries[0]["after"]["number_in_set"], "001");
+}
+
+#[tokio::test]
+async fn test_entries_can_be_limited_to_a_time_range() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_and_delete_set(&app).await;
+
+    let (_, entries) = get_log(&app, "?since=2000-01-01").await;
+    assert_eq!(entries.as_array().unwrap().len(), 2);
+    let (_, entries) = get_log(&app, "?until=2000-01-01T00:00:00Z").await;
+    assert!(entries.as_array().unwrap().is_empty());
+    let (_, entries) = get_log(&app, "?since=2000-01-01&until=2999-01-01").await;
+    assert_eq!(entries.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_since_is_inclusive_and_until_is_exclusive() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_and_delete_set(&app).await;
+
+    let (_, entries) = get_log(&app, "?entity_kind=set&entity_key=test-set").await;
+    let created_at = entries[1]["created_at"].as_str().unwrap().to_string();
+    let query = |bound: &str| {
+        format!("?entity_kind=set&entity_key=test-set&{bound}={created_at}").replace(' ', "%20")
+    };
+
+    // The creation is the earliest entry, so it alone bounds both ends.
+    let (status, entries) = get_log(&app, &query("since")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(entries.as_array().unwrap().len(), 2);
+    let (status, entries) = get_log(&app, &query("until")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(entries.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_invalid_timestamps_are_rejected() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let (status, _) = get_log(&app, "?since=yesterday").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_log(&app, "?until=2000-13-45").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_audit_log_is_not_public() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let response = common::send_as(&app, None, http::Method::GET, "/audit-log", "").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
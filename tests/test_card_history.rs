@@ -0,0 +1,209 @@
+use axum::{
+    Router,
+    http::{self, StatusCode},
+};
+use llocg_backend_api::{Pool, create_router};
+use std::path::PathBuf;
+
+mod common;
+
+/// Encodes a small solid-color PNG.
+fn png(color: [u8; 3]) -> Vec<u8> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image::RgbImage::from_pixel(4, 4, image::Rgb(color))
+        .write_to(&mut bytes, image::ImageFormat::Png)
+        .unwrap();
+    bytes.into_inner()
+}
+
+const ORIGINAL_URL: &str = "https://example.com/cardlist/PL!-BP01-001-R.png";
+
+/// A card whose image was swapped once, so it has two revisions.
+struct Fixture {
+    app: Router,
+    pool: Pool,
+    card_id: i64,
+    image_dir: PathBuf,
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.image_dir);
+    }
+}
+
+async fn setup(name: &str) -> Fixture {
+    let mut state = common::setup_test_env().await;
+    let image_dir =
+        std::env::temp_dir().join(format!("llocg-history-{name}-{}", std::process::id()));
+    state.image_dir = image_dir.clone();
+    let pool = state.pool.clone();
+    let app = create_router(state);
+
+    let card_id = common::create_honoka_with(
+        &app,
+        "PL!-BP01-001-R",
+        serde_json::json!({ "image_url": ORIGINAL_URL }),
+    )
+    .await;
+    let card = common::get_json(&app, &format!("/cards/{card_id}")).await;
+    let printing_id = card["printings"][0]["id"].as_i64().unwrap();
+    let response = common::send_as(
+        &app,
+        Some(common::ADMIN_AUTH),
+        http::Method::POST,
+        &format!("/printings/{printing_id}/image"),
+        png([255, 0, 0]),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    Fixture {
+        app,
+        pool,
+        card_id,
+        image_dir,
+    }
+}
+
+#[tokio::test]
+async fn test_creating_and_swapping_images_store_revisions() {
+    let Fixture { app, card_id, .. } = &setup("revisions").await;
+
+    let (status, revisions) = common::send_json(
+        app,
+        http::Method::GET,
+        &format!("/cards/{card_id}/history"),
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let revisions = revisions.as_array().unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0]["revision"], 1);
+    assert_eq!(revisions[1]["actor"], "tests");
+    assert_eq!(
+        revisions[0]["card"]["printings"][0]["image_url"],
+        ORIGINAL_URL
+    );
+}
+
+#[tokio::test]
+async fn test_diffs_list_changed_fields_against_the_latest_revision() {
+    let Fixture { app, card_id, .. } = &setup("diff").await;
+
+    let (status, diff) = common::send_json(
+        app,
+        http::Method::GET,
+        &format!("/cards/{card_id}/history/diff?from=1"),
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(diff["to"], 2);
+    let fields: Vec<_> = diff["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| change["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "printings.0.image_url",
+            "printings.0.medium_url",
+            "printings.0.thumbnail_url"
+        ]
+    );
+    assert_eq!(diff["changes"][0]["from"], ORIGINAL_URL);
+}
+
+#[tokio::test]
+async fn test_diffs_need_known_revisions() {
+    let Fixture { app, card_id, .. } = &setup("diff-unknown").await;
+    let diff = |query: &str| {
+        let uri = format!("/cards/{card_id}/history/diff{query}");
+        async move { common::send_json(app, http::Method::GET, &uri, "").await.0 }
+    };
+
+    assert_eq!(diff("?from=9").await, StatusCode::NOT_FOUND);
+    assert_eq!(diff("?from=0&to=2").await, StatusCode::NOT_FOUND);
+    assert_eq!(diff("?from=1&to=9").await, StatusCode::NOT_FOUND);
+    assert_eq!(diff("").await, StatusCode::BAD_REQUEST);
+    let (status, _) = common::send_json(
+        app,
+        http::Method::GET,
+        "/cards/9999/history/diff?from=1",
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_restoring_reapplies_an_old_revision() {
+    let Fixture { app, card_id, .. } = &setup("restore").await;
+
+    let response = common::send(
+        app,
+        http::Method::POST,
+        &format!("/cards/{card_id}/history/1/restore"),
+        "",
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let card = common::get_json(app, &format!("/cards/{card_id}")).await;
+    assert_eq!(card["printings"][0]["image_url"], ORIGINAL_URL);
+    assert!(card["printings"][0]["thumbnail_url"].is_null());
+    let (_, diff) = common::send_json(
+        app,
+        http::Method::GET,
+        &format!("/cards/{card_id}/history/diff?from=1&to=3"),
+        "",
+    )
+    .await;
+    assert!(diff["changes"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_unknown_cards_and_revisions_are_not_found() {
+    let Fixture { app, card_id, .. } = &setup("unknown").await;
+
+    let (status, _) = common::send_json(app, http::Method::GET, "/cards/9999/history", "").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let response = common::send(
+        app,
+        http::Method::POST,
+        &format!("/cards/{card_id}/history/9/restore"),
+        "",
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_restores_keep_set_rarities_and_the_trash() {
+    let Fixture {
+        app, pool, card_id, ..
+    } = &setup("restore-conflict").await;
+    let restore_uri = format!("/cards/{card_id}/history/1/restore");
+    let restore = || common::send(app, http::Method::POST, &restore_uri, "");
+
+    // The set no longer declares the revision's rarity.
+    sqlx::query("INSERT INTO set_rarities (set_code, rarity_code) VALUES ('BP01', 'N')")
+        .execute(pool)
+        .await
+        .unwrap();
+    assert_eq!(restore().await.status(), StatusCode::CONFLICT);
+    sqlx::query("DELETE FROM set_rarities WHERE set_code = 'BP01'")
+        .execute(pool)
+        .await
+        .unwrap();
+
+    // The set is in the trash.
+    sqlx::query("UPDATE sets SET deleted_at = CURRENT_TIMESTAMP WHERE set_code = 'BP01'")
+        .execute(pool)
+        .await
+        .unwrap();
+    assert_eq!(restore().await.status(), StatusCode::CONFLICT);
+}
@@ -0,0 +1,344 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use llocg_backend_api::{create_router, models::ImportRowError};
+use tower::ServiceExt; // for `oneshot`
+
+mod common;
+
+#[tokio::test]
+async fn test_import_csv_endpoint() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    // 1. Import two cards with a custom heart column mapping.
+    let csv = "card_identifier,name,card_type,cost,blades,Pink,score,groups\n\
+               PL!-BP01-001-R,Kousaka Honoka,Character,4,2,2,,ラブライブ！\n\
+               PL!-BP01-019-L,Snow halation,Live,,,3,2,Love Live!\n";
+    let payload = serde_json::json!({
+        "csv": csv,
+        "mapping": { "hearts": { "Pink": "Pink" } }
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/cards/import/csv")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let cards: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(cards.len(), 2);
+    assert_eq!(cards[0]["groups"][0], "Love Live!");
+    assert_eq!(cards[1]["hearts"]["Pink"], 3);
+
+    // 2. Database errors are reported against the row that caused them.
+    let csv = "card_identifier,name,card_type,heart_pink,cost,blades,groups\n\
+               PL!-BP01-002-R,Ayase Eli,Character,1,4,2,Love Live!\n\
+               PL!-BP01-003-R,Minami Kotori,Character,1,4,2,Unknown Group\n";
+    let payload = serde_json::json!({ "csv": csv });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/cards/import/csv")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let errors: Vec<ImportRowError> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].row, 2);
+    assert!(errors[0].message.contains("Unknown Group"));
+
+    // 3. The failed import is rolled back, so the first row can still be imported.
+    let csv = "card_identifier,name,card_type,heart_pink,cost,blades\n\
+               PL!-BP01-002-R,Ayase Eli,Character,1,4,2\n";
+    let payload = serde_json::json!({ "csv": csv });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/cards/import/csv")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // 4. Card numbers that are listed twice or already exist are named in the conflict.
+    let csv = "card_identifier,name,card_type,heart_pink,cost,blades\n\
+               PL!-BP01-004-R,Sonoda Umi,Character,1,4,2\n\
+               PL!-BP01-002-R,Ayase Eli,Character,1,4,2\n\
+               PL!-BP01-004-P,Sonoda Umi,Character,1,4,2\n";
+    for (csv, row, message) in [
+        (
+            csv,
+            3,
+            "Card number PL!-BP01-004 is already listed at row 1.",
+        ),
+        (
+            &csv[..csv.rfind("PL!-BP01-004-P").unwrap()],
+            2,
+            "Card number PL!-BP01-002 already exists.",
+        ),
+    ] {
+        let payload = serde_json::json!({ "csv": csv });
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/cards/import/csv")
+                    .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let errors: Vec<ImportRowError> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            errors,
+            [ImportRowError {
+                row,
+                message: message.to_string()
+            }]
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_export_endpoints() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    common::create_honoka_with(
+        &app,
+        "PL!-BP01-001-R",
+        serde_json::json!({
+            "groups": ["Love Live!"],
+            "units": ["Printemps"],
+            "skills": ["Draw a card.", "Gain a blade."],
+            "hearts": { "Pink": 2, "Gray": 1 }
+        }),
+    )
+    .await;
+    common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-019-L",
+            "name": "Snow halation",
+            "card_type": "Live",
+            "groups": ["Love Live!"],
+            "hearts": { "Pink": 3 },
+            "score": 2
+        }"#,
+    )
+    .await;
+
+    let export = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+    };
+
+    // 1. NDJSON has one flattened object per printing.
+    let ndjson = export("/cards/export/ndjson").await;
+    let rows: Vec<serde_json::Value> = ndjson
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["card_identifier"], "PL!-BP01-001-R");
+    assert_eq!(rows[0]["heart_pink"], 2);
+    assert_eq!(rows[0]["heart_gray"], 1);
+    assert_eq!(rows[0]["heart_red"], serde_json::Value::Null);
+    assert_eq!(rows[0]["units"], "Printemps");
+    assert_eq!(rows[1]["score"], 2);
+
+    // 2. The CSV export uses the default import column names, so it imports as is.
+    let csv = export("/cards/export/csv").await;
+    assert!(csv.starts_with("card_id,printing_id,card_identifier,"));
+    assert_eq!(csv.lines().count(), 3);
+
+    let target = create_router(common::setup_test_env().await);
+    let payload = serde_json::json!({ "csv": csv });
+    let response = target
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/cards/import/csv")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let cards: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(cards[0]["skills"].as_array().unwrap().len(), 2);
+    assert_eq!(cards[0]["hearts"]["Gray"], 1);
+}
+
+#[tokio::test]
+async fn test_import_html_endpoint() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let pages = [
+        include_str!("fixtures/cardlist/member.html"),
+        include_str!("fixtures/cardlist/live.html"),
+        include_str!("fixtures/cardlist/energy.html"),
+    ];
+    let import = |pages: Vec<String>, dry_run: bool| {
+        let payload = serde_json::json!({ "pages": pages, "dry_run": dry_run });
+        app.clone().oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/cards/import/html")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+    };
+
+    // 1. A dry run reports every page as new without creating anything.
+    let response = import(pages.map(str::to_string).to_vec(), true)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(result["diff"].as_array().unwrap().len(), 3);
+    assert!(
+        result["diff"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|entry| entry["status"] == "new")
+    );
+    assert!(result["created"].as_array().unwrap().is_empty());
+
+    // 2. The real import creates the cards from the pages.
+    let response = import(pages.map(str::to_string).to_vec(), false)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let created = result["created"].as_array().unwrap();
+    assert_eq!(created.len(), 3);
+
+    let member = &created[0];
+    assert_eq!(member["name"], "高坂穂乃果");
+    assert_eq!(member["card_type"], "Character");
+    assert_eq!(member["groups"][0], "Love Live!");
+    assert_eq!(member["units"][0], "Printemps");
+    assert_eq!(member["cost"], 4);
+    assert_eq!(member["blades"], 2);
+    assert_eq!(member["hearts"]["Pink"], 2);
+    assert_eq!(member["hearts"]["Green"], 1);
+    assert_eq!(member["blade_heart"], "Pink");
+    assert_eq!(
+        member["skills"][0],
+        "[登場]自分のデッキの上からカードを3枚見る。"
+    );
+    assert_eq!(
+        member["printings"][0]["image_url"],
+        "https://llofficial-cardgame.com/wp-content/images/cardlist/BP01/PL!-BP01-001-R.png"
+    );
+
+    let live = &created[1];
+    assert_eq!(live["printings"][0]["rarity_code"], "L");
+    assert_eq!(live["score"], 3);
+    assert_eq!(live["hearts"]["Gray"], 4);
+    assert_eq!(live["blade_heart"], "All");
+    assert_eq!(live["special_heart"], "Draw");
+    assert_eq!(result["diff"][1]["card_id"], live["id"]);
+
+    let energy = &created[2];
+    assert_eq!(energy["card_type"], "Energy");
+    assert!(energy["groups"].as_array().unwrap().is_empty());
+    assert!(energy["skills"].as_array().unwrap().is_empty());
+
+    // 3. Importing again diffs the pages against the stored cards.
+    let updated_live = pages[1].replace("<th>スコア</th><td>3</td>", "<th>スコア</th><td>4</td>");
+    let response = import(
+        vec![pages[0].to_string(), updated_live, pages[2].to_string()],
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(result["diff"][0]["status"], "unchanged");
+    assert_eq!(result["diff"][1]["status"], "changed");
+    assert_eq!(result["diff"][1]["changes"][0]["field"], "score");
+    assert_eq!(result["diff"][1]["changes"][0]["existing"], 3);
+    assert_eq!(result["diff"][1]["changes"][0]["incoming"], 4);
+    assert_eq!(result["diff"][2]["status"], "unchanged");
+    assert!(result["created"].as_array().unwrap().is_empty());
+
+    // 4. Pages that cannot be parsed are reported by their position.
+    let response = import(vec!["<html></html>".to_string()], true)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let errors: Vec<ImportRowError> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(errors[0].row, 1);
+}
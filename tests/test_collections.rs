@@ -0,0 +1,411 @@
+use axum::{
+    Router,
+    http::{self, StatusCode},
+};
+use llocg_backend_api::{
+    create_router,
+    models::{Collection, CollectionQuantity, CollectionView, SetCompletion, TradeMatch, Want},
+};
+
+mod common;
+
+/// Sends a JSON request with the admin key and returns the status code and body.
+async fn send(app: &Router, method: http::Method, uri: &str, body: &str) -> (StatusCode, Vec<u8>) {
+    let response = common::send(app, method, uri, body).await;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_collections_endpoints() {
+    let state = common::setup_test_env().await;
+    let pool = state.pool.clone();
+    let app = create_router(state);
+
+    // Two printings in BP01, so owning one of them completes half the set.
+    let first_id = common::create_honoka(&app, "PL!-BP01-001-R").await;
+    common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-002-R",
+            "name": "Ayase Eli",
+            "card_type": "Character",
+            "hearts": { "Blue": 2 },
+            "cost": 4,
+            "blades": 2
+        }"#,
+    )
+    .await;
+    let (_, body) = send(&app, http::Method::GET, &format!("/cards/{first_id}"), "").await;
+    let card: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let printing_id = card["printings"][0]["id"].as_i64().unwrap();
+
+    // 1. Changing collections takes a key; then create a collection.
+    let response = common::send_as(
+        &app,
+        None,
+        http::Method::POST,
+        "/collections",
+        r#"{"name": "Binder"}"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let (status, body) = send(
+        &app,
+        http::Method::POST,
+        "/collections",
+        r#"{"name": "Binder", "owner": "user-1"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let collection: Collection = serde_json::from_slice(&body).unwrap();
+    assert_eq!(collection.name, "Binder");
+    assert_eq!(collection.owner.as_deref(), Some("user-1"));
+    let base = format!("/collections/{}", collection.id);
+
+    // 2. Add three copies, then remove one.
+    let (status, body) = send(
+        &app,
+        http::Method::POST,
+        &format!("{base}/add"),
+        &format!(r#"{{"printing_id": {printing_id}, "quantity": 3}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let owned: CollectionQuantity = serde_json::from_slice(&body).unwrap();
+    assert_eq!(owned.quantity, 3);
+
+    let (status, body) = send(
+        &app,
+        http::Method::POST,
+        &format!("{base}/remove"),
+        &format!(r#"{{"printing_id": {printing_id}, "quantity": 1}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let owned: CollectionQuantity = serde_json::from_slice(&body).unwrap();
+    assert_eq!(owned.quantity, 2);
+
+    // 3. The collection view joins to the full card.
+    let (status, body) = send(&app, http::Method::GET, &base, "").await;
+    assert_eq!(status, StatusCode::OK);
+    let view: CollectionView = serde_json::from_slice(&body).unwrap();
+    assert_eq!(view.cards.len(), 1);
+    assert_eq!(view.cards[0].quantity, 2);
+    assert_eq!(view.cards[0].card.base.name, "Kousaka Honoka");
+
+    // 4. Set completion counts owned printings against the set.
+    let (status, body) = send(&app, http::Method::GET, &format!("{base}/completion"), "").await;
+    assert_eq!(status, StatusCode::OK);
+    let completion: Vec<SetCompletion> = serde_json::from_slice(&body).unwrap();
+    let bp01 = completion.iter().find(|s| s.set_code == "BP01").unwrap();
+    assert_eq!(bp01.owned_printings, 1);
+    assert_eq!(bp01.total_printings, 2);
+    assert_eq!(bp01.percentage, 50.0);
+
+    // 5. Bulk-setting a quantity of zero removes the printing.
+    let (status, _) = send(
+        &app,
+        http::Method::PUT,
+        &format!("{base}/items"),
+        &format!(r#"[{{"printing_id": {printing_id}, "quantity": 0}}]"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, http::Method::GET, &base, "").await;
+    let view: CollectionView = serde_json::from_slice(&body).unwrap();
+    assert!(view.cards.is_empty());
+
+    // 6. Unknown printings and collections are rejected.
+    let (status, _) = send(
+        &app,
+        http::Method::POST,
+        &format!("{base}/add"),
+        r#"{"printing_id": 9999, "quantity": 1}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &app,
+        http::Method::POST,
+        "/collections/9999/add",
+        &format!(r#"{{"printing_id": {printing_id}, "quantity": 1}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // 7. Quantities that would overflow are rejected.
+    let (status, _) = send(
+        &app,
+        http::Method::PUT,
+        &format!("{base}/items"),
+        &format!(
+            r#"[{{"printing_id": {printing_id}, "quantity": {}}}]"#,
+            i64::MAX
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(
+        &app,
+        http::Method::POST,
+        &format!("{base}/add"),
+        &format!(r#"{{"printing_id": {printing_id}, "quantity": 1}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 8. Delete the collection along with its items, after which it cannot be deleted again.
+    let (status, _) = send(&app, http::Method::DELETE, &base, "").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let items: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM collection_items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(items, 0);
+    let (status, _) = send(&app, http::Method::GET, &base, "").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, http::Method::DELETE, &base, "").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_want_lists_and_trade_matching() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    // A Regular printing and a Parallel printing of two different cards.
+    let regular_card = common::create_honoka(&app, "PL!-BP01-001-R").await;
+    let parallel_card = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-002-P",
+            "name": "Ayase Eli",
+            "card_type": "Character",
+            "hearts": { "Blue": 2 },
+            "cost": 4,
+            "blades": 2
+        }"#,
+    )
+    .await;
+    let mut printing_ids = Vec::new();
+    for card_id in [regular_card, parallel_card] {
+        let (_, body) = send(&app, http::Method::GET, &format!("/cards/{card_id}"), "").await;
+        let card: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        printing_ids.push(card["printings"][0]["id"].as_i64().unwrap());
+    }
+    let (regular_printing, parallel_printing) = (printing_ids[0], printing_ids[1]);
+
+    let mut collection_ids = Vec::new();
+    for name in ["Alice", "Bob"] {
+        let (_, body) = send(
+            &app,
+            http::Method::POST,
+            "/collections",
+            &format!(r#"{{"name": "{name}"}}"#),
+        )
+        .await;
+        let collection: Collection = serde_json::from_slice(&body).unwrap();
+        collection_ids.push(collection.id);
+    }
+    let (alice, bob) = (collection_ids[0], collection_ids[1]);
+
+    // 1. Alice owns both printings; Bob wants any printing of the first card and the Parallel one.
+    let (status, _) = send(
+        &app,
+        http::Method::PUT,
+        &format!("/collections/{alice}/items"),
+        &format!(
+            r#"[{{"printing_id": {regular_printing}, "quantity": 2}}, {{"printing_id": {parallel_printing}, "quantity": 1}}]"#
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = send(
+        &app,
+        http::Method::POST,
+        &format!("/collections/{bob}/wants"),
+        &format!(r#"{{"card_id": {regular_card}, "quantity": 1}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let want: Want = serde_json::from_slice(&body).unwrap();
+    assert_eq!(want.card_id, Some(regular_card));
+
+    let (status, _) = send(
+        &app,
+        http::Method::POST,
+        &format!("/collections/{bob}/wants"),
+        &format!(r#"{{"printing_id": {parallel_printing}, "quantity": 1}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // 2. Wants must name exactly one of a printing or a card, and may not repeat.
+    let (status, _) = send(
+        &app,
+        http::Method::POST,
+        &format!("/collections/{bob}/wants"),
+        &format!(
+            r#"{{"card_id": {regular_card}, "printing_id": {regular_printing}, "quantity": 1}}"#
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &app,
+        http::Method::POST,
+        &format!("/collections/{bob}/wants"),
+        &format!(r#"{{"card_id": {regular_card}, "quantity": 2}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // 3. Alice can give Bob both cards.
+    let (status, body) = send(
+        &app,
+        http::Method::GET,
+        &format!("/collections/{alice}/matches/{bob}"),
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let matched: TradeMatch = serde_json::from_slice(&body).unwrap();
+    assert_eq!(matched.gives.len(), 2);
+    assert!(matched.receives.is_empty());
+
+    // 4. Excluding Parallel printings leaves only the Regular card.
+    let (status, body) = send(
+        &app,
+        http::Method::GET,
+        &format!("/collections/{alice}/matches/{bob}?exclude_parallel=true"),
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let matched: TradeMatch = serde_json::from_slice(&body).unwrap();
+    assert_eq!(matched.gives.len(), 1);
+    assert_eq!(matched.gives[0].printing_id, regular_printing);
+    assert_eq!(matched.gives[0].quantity, 1);
+
+    // 5. Removing the want list entries removes the matches.
+    let (_, body) = send(
+        &app,
+        http::Method::GET,
+        &format!("/collections/{bob}/wants"),
+        "",
+    )
+    .await;
+    let wants: Vec<Want> = serde_json::from_slice(&body).unwrap();
+    // Wants can only be removed through the collection they belong to.
+    let (status, _) = send(
+        &app,
+        http::Method::DELETE,
+        &format!("/collections/{alice}/wants/{}", wants[0].id),
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    for want in wants {
+        let (status, _) = send(
+            &app,
+            http::Method::DELETE,
+            &format!("/collections/{bob}/wants/{}", want.id),
+            "",
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = send(
+            &app,
+            http::Method::DELETE,
+            &format!("/collections/{bob}/wants/{}", want.id),
+            "",
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+    let (_, body) = send(
+        &app,
+        http::Method::GET,
+        &format!("/collections/{alice}/matches/{bob}"),
+        "",
+    )
+    .await;
+    let matched: TradeMatch = serde_json::from_slice(&body).unwrap();
+    assert!(matched.gives.is_empty());
+}
+
+#[tokio::test]
+async fn test_collections_belong_to_their_key() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let mut tokens = Vec::new();
+    for name in ["alice", "bob"] {
+        let (status, issued) = common::send_json(
+            &app,
+            http::Method::POST,
+            "/api-keys",
+            &format!(r#"{{ "name": "{name}", "role": "reader" }}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        tokens.push((
+            issued["id"].as_i64().unwrap(),
+            format!("Bearer {}", issued["token"].as_str().unwrap()),
+        ));
+    }
+    let (alice_id, alice) = (tokens[0].0, Some(tokens[0].1.as_str()));
+    let bob = Some(tokens[1].1.as_str());
+    let printing_id = common::get_json(
+        &app,
+        &format!(
+            "/cards/{}",
+            common::create_honoka(&app, "PL!-BP01-001-R").await
+        ),
+    )
+    .await["printings"][0]["id"]
+        .clone();
+    let add = format!(r#"{{"printing_id": {printing_id}, "quantity": 1}}"#);
+
+    // 1. Keys of any role can create collections, which are tied to them.
+    let response = common::send_as(
+        &app,
+        alice,
+        http::Method::POST,
+        "/collections",
+        r#"{"name": "Binder"}"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let collection: Collection = serde_json::from_value(common::read_json(response).await).unwrap();
+    assert_eq!(collection.api_key_id, Some(alice_id));
+    let base = format!("/collections/{}", collection.id);
+
+    // 2. Other keys cannot change the collection or its want list.
+    for (method, uri, body) in [
+        (http::Method::POST, format!("{base}/add"), add.clone()),
+        (
+            http::Method::PUT,
+            format!("{base}/items"),
+            format!("[{add}]"),
+        ),
+        (http::Method::POST, format!("{base}/wants"), add.clone()),
+        (http::Method::DELETE, base.clone(), String::new()),
+    ] {
+        let response = common::send_as(&app, bob, method, &uri, body).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    // 3. The key that created it can, and so can admins.
+    let response =
+        common::send_as(&app, alice, http::Method::POST, &format!("{base}/add"), add).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = common::send(&app, http::Method::DELETE, &base, "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
@@ -0,0 +1,522 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use llocg_backend_api::{
+    create_router, image_store,
+    models::{ProbabilityResponse, SimulationResponse},
+};
+use tower::ServiceExt; // for `oneshot`
+
+mod common;
+
+#[tokio::test]
+async fn test_deck_probability_endpoint() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    // 1. Create a Pink-heart member and a Live card to build a deck from.
+    let member_id = common::create_honoka_with(
+        &app,
+        "PL!-BP01-001-R",
+        serde_json::json!({ "groups": ["ラブライブ！"] }),
+    )
+    .await;
+    let other_member_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-003-R",
+            "name": "Minami Kotori",
+            "card_type": "Character",
+            "groups": ["ラブライブ！"],
+            "hearts": { "Pink": 1 },
+            "cost": 2,
+            "blades": 1
+        }"#,
+    )
+    .await;
+    let live_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-019-L",
+            "name": "Snow halation",
+            "card_type": "Live",
+            "groups": ["Love Live!"],
+            "hearts": { "Pink": 2, "Gray": 1 },
+            "score": 2
+        }"#,
+    )
+    .await;
+
+    let energy_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-031-PE",
+            "name": "Kousaka Honoka",
+            "card_type": "Energy"
+        }"#,
+    )
+    .await;
+
+    // 2. Ask for the odds of at least one Pink member and of the Live card. The Energy
+    // deck is not drawn from.
+    let payload = format!(
+        r#"{{
+            "deck": [
+                {{ "card_id": {energy_id}, "count": 12 }},
+                {{ "card_id": {member_id}, "count": 2 }},
+                {{ "card_id": {live_id}, "count": 4 }},
+                {{ "card_id": {other_member_id}, "count": 2 }},
+                {{ "card_id": {member_id}, "count": 2 }}
+            ],
+            "hand_size": 2,
+            "turns": 2,
+            "conditions": [
+                {{ "label": "pink", "filter": {{ "card_type": "Character", "heart_color": "Pink", "group": "ラブライブ！" }} }},
+                {{ "label": "live", "filter": {{ "name": "Snow halation" }}, "at_least": 4 }}
+            ]
+        }}"#
+    );
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/decks/probability")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let result: ProbabilityResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(result.deck_size, 10);
+    assert_eq!(result.conditions.len(), 2);
+
+    let pink = &result.conditions[0];
+    assert_eq!(pink.matching_cards, 6);
+    assert_eq!(pink.table.len(), 3);
+    assert_eq!(pink.table[0].cards_seen, 2);
+    // P(no Pink member in 2 cards) = C(4,2)/C(10,2) = 6/45.
+    assert!((pink.table[0].probability - (1.0 - 6.0 / 45.0)).abs() < 1e-9);
+
+    let live = &result.conditions[1];
+    assert_eq!(live.matching_cards, 4);
+    // Fewer cards seen than required copies can never succeed.
+    assert_eq!(live.table[0].probability, 0.0);
+    assert_eq!(live.table[2].cards_seen, 4);
+    assert!((live.table[2].probability - 1.0 / 210.0).abs() < 1e-9);
+
+    // 3. Unknown cards, too many copies, oversized decks and too many turns are rejected.
+    for payload in [
+        r#"{"deck": [{"card_id": 9999, "count": 1}], "conditions": []}"#.to_string(),
+        format!(
+            r#"{{"deck": [{{"card_id": {member_id}, "count": 4}}, {{"card_id": {member_id}, "count": 1}}], "conditions": []}}"#
+        ),
+        format!(
+            r#"{{"deck": [{{"card_id": {member_id}, "count": 9223372036854775807}}, {{"card_id": {member_id}, "count": 1}}], "conditions": []}}"#
+        ),
+        format!(
+            r#"{{"deck": [{{"card_id": {member_id}, "count": 4}}], "conditions": [], "turns": 1000000000}}"#
+        ),
+        format!(
+            r#"{{"deck": [{{"card_id": {member_id}, "count": 4}}], "conditions": [], "draws_per_turn": 5}}"#
+        ),
+        format!(
+            r#"{{"deck": [{{"card_id": {member_id}, "count": 4}}, {{"card_id": {energy_id}, "count": 2}}], "conditions": [], "hand_size": 5}}"#
+        ),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/decks/probability")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(payload))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_deck_simulate_endpoint() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    // 1. A member with two blades, a member that yells a Pink heart, and a Live card.
+    let member_id = common::create_honoka(&app, "PL!-BP01-001-R").await;
+    let yell_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-002-R",
+            "name": "Ayase Eli",
+            "card_type": "Character",
+            "hearts": { "Blue": 1 },
+            "blade_heart": "Pink",
+            "cost": 2,
+            "blades": 1
+        }"#,
+    )
+    .await;
+    let live_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-019-L",
+            "name": "Snow halation",
+            "card_type": "Live",
+            "hearts": { "Pink": 2, "Gray": 1 },
+            "score": 2
+        }"#,
+    )
+    .await;
+
+    // 2. Two yells from a deck of 4 Pink blade hearts and 4 blanks.
+    let payload = format!(
+        r#"{{
+            "deck": [
+                {{ "card_id": {member_id}, "count": 4 }},
+                {{ "card_id": {yell_id}, "count": 4 }}
+            ],
+            "stage": [{member_id}],
+            "live_card_id": {live_id},
+            "trials": 20000,
+            "seed": 42
+        }}"#
+    );
+
+    let mut results = Vec::new();
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/decks/simulate")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(payload.clone()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let result: SimulationResponse = serde_json::from_slice(&body).unwrap();
+        results.push(result);
+    }
+
+    // The same seed must produce the same result.
+    assert_eq!(results[0].successes, results[1].successes);
+    assert_eq!(results[0].yell_count, 2);
+    assert_eq!(results[0].trials, 20000);
+    // P(at least one Pink in 2 yells) = 1 - C(4,2)/C(8,2).
+    let expected = 1.0 - 6.0 / 28.0;
+    assert!((results[0].probability - expected).abs() < 0.02);
+
+    // 3. Energy cards are not yelled, so they do not change the result.
+    let energy_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-031-PE",
+            "name": "Kousaka Honoka",
+            "card_type": "Energy"
+        }"#,
+    )
+    .await;
+    let payload = format!(
+        r#"{{
+            "deck": [
+                {{ "card_id": {member_id}, "count": 4 }},
+                {{ "card_id": {energy_id}, "count": 12 }},
+                {{ "card_id": {yell_id}, "count": 4 }}
+            ],
+            "stage": [{member_id}],
+            "live_card_id": {live_id},
+            "trials": 20000,
+            "seed": 42
+        }}"#
+    );
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/decks/simulate")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let result: SimulationResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(result.successes, results[0].successes);
+
+    // 4. A Live card cannot stand on stage.
+    let payload = format!(
+        r#"{{
+            "deck": [{{ "card_id": {member_id}, "count": 4 }}],
+            "stage": [{live_id}],
+            "live_card_id": {live_id}
+        }}"#
+    );
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/decks/simulate")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_deck_export_endpoints() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let member_id = common::create_honoka_with(
+        &app,
+        "PL!-BP01-001-R",
+        serde_json::json!({
+            "groups": ["Love Live!"],
+            "image_url": "https://example.com/PL!-BP01-001-R.png"
+        }),
+    )
+    .await;
+    let live_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-019-L",
+            "name": "Snow halation",
+            "card_type": "Live",
+            "groups": ["Love Live!"],
+            "hearts": { "Pink": 2 },
+            "score": 2
+        }"#,
+    )
+    .await;
+
+    let post = |uri: &'static str, payload: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method(http::Method::POST)
+                        .uri(uri)
+                        .header(http::header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(payload))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, body)
+        }
+    };
+
+    // 1. A Member deck exports as a single TTS deck object.
+    let payload = format!(
+        r#"{{ "deck": [{{ "card_id": {member_id}, "count": 4 }}], "card_back_url": "https://example.com/back.png" }}"#
+    );
+    let (status, body) = post("/decks/export/tts", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    let saved: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let deck = &saved["ObjectStates"][0];
+    assert_eq!(deck["Name"], "Deck");
+    assert_eq!(deck["DeckIDs"].as_array().unwrap().len(), 4);
+    assert_eq!(
+        deck["CustomDeck"]["1"]["FaceURL"],
+        "https://example.com/PL!-BP01-001-R.png"
+    );
+    assert_eq!(
+        deck["CustomDeck"]["1"]["BackURL"],
+        "https://example.com/back.png"
+    );
+
+    // 2. Cards without an image cannot be exported to TTS.
+    let payload = format!(
+        r#"{{ "deck": [{{ "card_id": {live_id}, "count": 1 }}], "card_back_url": "https://example.com/back.png" }}"#
+    );
+    let (status, _) = post("/decks/export/tts", payload).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 3. The plain card list includes them regardless.
+    let payload = format!(
+        r#"{{ "deck": [{{ "card_id": {member_id}, "count": 4 }}, {{ "card_id": {live_id}, "count": 2 }}] }}"#
+    );
+    let (status, body) = post("/decks/export/list", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    let list: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[1]["card_identifier"], "PL!-BP01-019-L");
+    assert_eq!(list[1]["count"], 2);
+    assert_eq!(list[1]["image_url"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn test_deck_export_stored_image_urls() {
+    let mut state = common::setup_test_env().await;
+    let pool = state.pool.clone();
+    state.public_base_url = Some("https://cards.example.com".to_string());
+    let app = create_router(state.clone());
+
+    let member_id = common::create_honoka(&app, "PL!-BP01-001-R").await;
+    let stored_url = image_store::image_url(&image_store::content_hash(b"image"));
+    sqlx::query("UPDATE printings SET image_url = ? WHERE card_id = ?")
+        .bind(&stored_url)
+        .bind(member_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let export = |app: axum::Router, uri: &'static str| async move {
+        let payload = format!(
+            r#"{{ "deck": [{{ "card_id": {member_id}, "count": 1 }}], "card_back_url": "https://example.com/back.png" }}"#
+        );
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(uri)
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(payload))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    };
+
+    // 1. Stored images are exported under the public URL of the server.
+    let absolute_url = format!("https://cards.example.com{stored_url}");
+    let (status, saved): (_, serde_json::Value) = export(app.clone(), "/decks/export/tts").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        saved["ObjectStates"][0]["CustomDeck"]["1"]["FaceURL"],
+        absolute_url
+    );
+    let (status, list) = export(app.clone(), "/decks/export/list").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list[0]["image_url"], absolute_url);
+
+    // 2. Without a public URL, they cannot be exported to TTS.
+    state.public_base_url = None;
+    let (status, _) = export(create_router(state), "/decks/export/tts").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_deck_proxy_pdf_endpoint() {
+    let mut state = common::setup_test_env().await;
+
+    // 1. Set up a local image store holding the image of one card.
+    let image_dir = std::env::temp_dir().join(format!("llocg-proxy-test-{}", std::process::id()));
+    std::fs::create_dir_all(&image_dir).unwrap();
+    image::RgbImage::from_pixel(63, 88, image::Rgb([255, 128, 192]))
+        .save(image_dir.join("PL!-BP01-001-R.png"))
+        .unwrap();
+    state.image_dir = image_dir.clone();
+    let app = create_router(state);
+
+    let member_id = common::create_honoka_with(
+        &app,
+        "PL!-BP01-001-R",
+        serde_json::json!({
+            "groups": ["Love Live!"],
+            "image_url": "https://example.com/cardlist/PL!-BP01-001-R.png"
+        }),
+    )
+    .await;
+    let live_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-019-L",
+            "name": "Snow halation",
+            "card_type": "Live",
+            "groups": ["Love Live!"],
+            "hearts": { "Pink": 2 },
+            "score": 2
+        }"#,
+    )
+    .await;
+
+    let other_live_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-020-L",
+            "name": "No brand girls",
+            "card_type": "Live",
+            "groups": ["Love Live!"],
+            "hearts": { "Red": 2 },
+            "score": 2
+        }"#,
+    )
+    .await;
+
+    // 2. Ten copies fill two Letter pages; the Live cards fall back to text.
+    let payload = format!(
+        r#"{{
+            "deck": [
+                {{ "card_id": {member_id}, "count": 4 }},
+                {{ "card_id": {live_id}, "count": 4 }},
+                {{ "card_id": {other_live_id}, "count": 2 }}
+            ],
+            "page_size": "Letter"
+        }}"#
+    );
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/decks/export/pdf")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(payload))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[http::header::CONTENT_TYPE],
+        "application/pdf"
+    );
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8_lossy(&body);
+    assert!(body.starts_with(b"%PDF-"));
+    assert!(text.contains("/Count 2"));
+    assert_eq!(text.matches("/Subtype /Image").count(), 1);
+    assert!(text.contains("(Snow halation)"));
+
+    std::fs::remove_dir_all(image_dir).unwrap();
+}
@@ -0,0 +1,134 @@
+use axum::http::{self, StatusCode};
+use llocg_backend_api::create_router;
+
+mod common;
+
+#[tokio::test]
+async fn test_delete_with_dependents() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let delete = |uri: &str| {
+        let uri = uri.to_string();
+        let app = app.clone();
+        async move {
+            let response = common::send(&app, http::Method::DELETE, &uri, "").await;
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+    let get_card = |card_id: i64| {
+        let uri = format!("/cards/{card_id}");
+        let app = app.clone();
+        async move { common::send_json(&app, http::Method::GET, &uri, "").await }
+    };
+
+    let honoka = common::create_honoka_with(
+        &app,
+        "PL!-BP01-001-R",
+        serde_json::json!({ "groups": ["Love Live!"], "units": ["Printemps"] }),
+    )
+    .await;
+    let eli = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-002-P",
+            "name": "Ayase Eli",
+            "card_type": "Character",
+            "cost": 4,
+            "blades": 2,
+            "hearts": { "Blue": 2 },
+            "groups": ["Love Live!"]
+        }"#,
+    )
+    .await;
+
+    // 1. Deleting what cards depend on is refused, listing the cards.
+    let (status, message) = delete("/groups/Love%20Live!").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(message.contains("PL!-BP01-001"));
+    assert!(message.contains("PL!-BP01-002"));
+    let (status, _) = delete("/groups/Love%20Live!?cascade=true&reassign_to=BiBi").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = delete("/groups/Love%20Live!?reassign_to=Unknown").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 2. Cards can be moved to another group, which is recorded in their history.
+    let (status, _) = delete("/groups/Love%20Live!?reassign_to=Love%20Live!%20Sunshine!!").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, card) = get_card(honoka).await;
+    assert_eq!(card["groups"], serde_json::json!(["Love Live! Sunshine!!"]));
+    let history = common::get_json(&app, &format!("/cards/{honoka}/history")).await;
+    assert_eq!(history.as_array().unwrap().len(), 2);
+
+    // 3. Cards are not detached from what goes to the trash.
+    let (status, _) = delete("/units/Printemps?cascade=true").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = delete("/groups/Love%20Live!%20Sunshine!!?cascade=true").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (_, card) = get_card(honoka).await;
+    assert_eq!(card["units"], serde_json::json!(["Printemps"]));
+
+    // 4. Printings can be moved to another rarity, and missing rarities are not found.
+    let (status, message) = delete("/rarities/P").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(message.contains("PL!-BP01-002-P"));
+    let (status, _) = delete("/rarities/P?reassign_to=LLE").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, card) = get_card(eli).await;
+    assert_eq!(card["printings"][0]["rarity_code"], "LLE");
+    let (status, _) = delete("/rarities/P").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // 5. Cards can be moved to another set, but not removed along with it.
+    let (status, _) = delete("/sets/BP01").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = delete("/sets/BP01?reassign_to=BP02").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, card) = get_card(honoka).await;
+    assert_eq!(card["set_code"], "BP02");
+    let (status, _) = delete("/sets/BP02?cascade=true").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_card(eli).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 6. Deleting a group into another one points its variants at that group.
+    let response = common::send(
+        &app,
+        http::Method::POST,
+        "/variants/groups",
+        r#"{ "variant_name": "Sunshine", "canonical_name": "Love Live! Sunshine!!" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let (status, _) =
+        delete("/groups/Love%20Live!%20Sunshine!!?reassign_to=Love%20Live!%20Superstar!!").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, card) = get_card(honoka).await;
+    assert_eq!(
+        card["groups"],
+        serde_json::json!(["Love Live! Superstar!!"])
+    );
+
+    let card_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP03-001-R",
+            "name": "Takami Chika",
+            "card_type": "Character",
+            "cost": 4,
+            "blades": 2,
+            "hearts": { "Yellow": 2 },
+            "groups": ["Sunshine"]
+        }"#,
+    )
+    .await;
+    let (_, card) = get_card(card_id).await;
+    assert_eq!(
+        card["groups"],
+        serde_json::json!(["Love Live! Superstar!!"])
+    );
+}
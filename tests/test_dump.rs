@@ -0,0 +1,134 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use llocg_backend_api::create_router;
+use tower::ServiceExt; // for `oneshot`
+
+mod common;
+
+async fn export(app: &axum::Router) -> serde_json::Value {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/dump").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+async fn import(app: &axum::Router, dump: &serde_json::Value) -> StatusCode {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/dump")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(dump.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn test_dump_round_trip() {
+    let state = common::setup_test_env().await;
+    let pool = state.pool.clone();
+    let source = create_router(state);
+
+    // 1. Add some data beyond the seeded reference tables.
+    let card_id = common::create_honoka_with(
+        &source,
+        "PL!-BP01-001-R",
+        serde_json::json!({
+            "groups": ["ラブライブ！"],
+            "units": ["Printemps"],
+            "skills": ["Draw a card."],
+            "blade_heart": "Pink"
+        }),
+    )
+    .await;
+    // Deleted reference data is restored to the trash.
+    for (method, uri, body) in [
+        (http::Method::POST, "/groups", r#"{ "name": "Test Group" }"#),
+        (http::Method::DELETE, "/groups/Test%20Group", ""),
+    ] {
+        let response = source
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+    }
+    // Perceptual hashes use all 64 bits, so they must survive the signed SQLite column.
+    sqlx::query("UPDATE printings SET perceptual_hash = -1 WHERE card_id = ?")
+        .bind(card_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let dump = export(&source).await;
+    assert_eq!(dump["format_version"], 2);
+    assert_eq!(dump["cards"][0]["id"], card_id);
+    assert_eq!(
+        dump["perceptual_hashes"][0]["perceptual_hash"],
+        serde_json::json!(u64::MAX)
+    );
+    assert_eq!(dump["trash"][0]["key"], "Test Group");
+
+    // 2. Restore the dump into a fresh database and export it again.
+    let target = create_router(common::setup_test_env().await);
+    assert_eq!(import(&target, &dump).await, StatusCode::CREATED);
+    assert_eq!(export(&target).await, dump);
+
+    // 3. The restored card is served with its original ID.
+    let response = target
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/cards/{card_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // 4. A second import is rejected because the database is no longer empty.
+    assert_eq!(import(&target, &dump).await, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_dump_rejects_schema_mismatch() {
+    let app = create_router(common::setup_test_env().await);
+
+    let mut dump = export(&app).await;
+    dump["schema_version"] = serde_json::json!(1);
+    assert_eq!(import(&app, &dump).await, StatusCode::CONFLICT);
+
+    dump["format_version"] = serde_json::json!(99);
+    assert_eq!(import(&app, &dump).await, StatusCode::BAD_REQUEST);
+
+    // Dumps of the first format version lack the newer tables, and are reported as
+    // unsupported rather than as malformed.
+    let dump = serde_json::json!({ "format_version": 1, "schema_version": 1, "sets": [] });
+    let response = common::send(&app, http::Method::POST, "/dump", &dump.to_string()).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).starts_with("Unsupported dump format version: 1;"));
+}
@@ -0,0 +1,345 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use llocg_backend_api::{create_router, image_store, perceptual_hash};
+use tower::ServiceExt; // for `oneshot`
+
+mod common;
+
+/// Encodes a small solid-color PNG.
+fn png(color: [u8; 3]) -> Vec<u8> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image::RgbImage::from_pixel(4, 4, image::Rgb(color))
+        .write_to(&mut bytes, image::ImageFormat::Png)
+        .unwrap();
+    bytes.into_inner()
+}
+
+#[tokio::test]
+async fn test_image_upload_and_serving() {
+    let mut state = common::setup_test_env().await;
+    let image_dir = std::env::temp_dir().join(format!("llocg-image-test-{}", std::process::id()));
+    state.image_dir = image_dir.clone();
+    let app = create_router(state);
+
+    let mut printing_ids = Vec::new();
+    for number in ["001", "002"] {
+        let card_id = common::create_honoka_with(
+            &app,
+            &format!("PL!-BP01-{number}-R"),
+            serde_json::json!({
+                "image_url": format!("https://example.com/cardlist/PL!-BP01-{number}-R.png")
+            }),
+        )
+        .await;
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/cards/{card_id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let card: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        printing_ids.push((card_id, card["printings"][0]["id"].as_i64().unwrap()));
+    }
+
+    let upload = |printing_id: i64, image: Vec<u8>| {
+        app.clone().oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri(format!("/printings/{printing_id}/image"))
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                .header(http::header::CONTENT_TYPE, "image/png")
+                .body(Body::from(image))
+                .unwrap(),
+        )
+    };
+
+    // 1. Uploading an image points the printing at the stored copy.
+    let response = upload(printing_ids[0].1, png([255, 0, 0])).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let printing: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let image_url = printing["image_url"].as_str().unwrap().to_string();
+    assert!(image_url.starts_with("/images/"));
+    assert_eq!(printing["thumbnail_url"], format!("{image_url}-thumb"));
+    assert_eq!(printing["medium_url"], format!("{image_url}-medium"));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/cards/{}", printing_ids[0].0))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let card: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(card["printings"][0]["image_url"], image_url);
+
+    // 2. The same image uploaded for another printing is stored only once.
+    let response = upload(printing_ids[1].1, png([255, 0, 0])).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let printing: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(printing["image_url"], image_url);
+    // The original, its thumbnail and its medium-sized copy.
+    assert_eq!(std::fs::read_dir(&image_dir).unwrap().count(), 3);
+
+    // 3. Unsupported bodies and unknown printings are rejected.
+    let response = upload(printing_ids[0].1, b"not an image".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let response = upload(9999, png([0, 0, 255])).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // 4. Stored images are served with their content type and caching headers.
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(&image_url)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "image/png");
+    assert!(
+        response.headers()[http::header::CACHE_CONTROL]
+            .to_str()
+            .unwrap()
+            .contains("immutable")
+    );
+    let etag = response.headers()[http::header::ETAG].clone();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(body.to_vec(), png([255, 0, 0]));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(&image_url)
+                .header(http::header::IF_NONE_MATCH, etag)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("{image_url}-thumb"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "image/jpeg");
+
+    for uri in [
+        format!("/images/{}", "0".repeat(64)),
+        "/images/..".to_string(),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    std::fs::remove_dir_all(image_dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_regenerate_image_variants() {
+    let mut state = common::setup_test_env().await;
+    let image_dir =
+        std::env::temp_dir().join(format!("llocg-regenerate-test-{}", std::process::id()));
+    state.image_dir = image_dir.clone();
+    let pool = state.pool.clone();
+    let app = create_router(state);
+
+    // An image stored before variants existed, and one whose original is gone.
+    let image = png([0, 255, 0]);
+    let hash = image_store::store(&image_dir, &image).await.unwrap();
+    let missing = image_store::content_hash(b"missing");
+    for (number, hash) in [("001", &hash), ("002", &missing)] {
+        common::create_honoka_with(
+            &app,
+            &format!("PL!-BP01-{number}-R"),
+            serde_json::json!({ "image_url": format!("/images/{hash}") }),
+        )
+        .await;
+    }
+
+    let report = image_store::regenerate_variants(&pool, &image_dir)
+        .await
+        .unwrap();
+    assert_eq!(report.images, 1);
+    assert_eq!(report.printings, 1);
+    assert_eq!(report.failed, vec![missing]);
+
+    let thumbnail_url: Option<String> =
+        sqlx::query_scalar("SELECT thumbnail_url FROM printings WHERE image_url = ?")
+            .bind(image_store::image_url(&hash))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        thumbnail_url,
+        Some(image_store::variant_url(
+            &hash,
+            image_store::ImageVariant::Thumbnail
+        ))
+    );
+    assert!(
+        image_dir
+            .join(image_store::ImageVariant::Medium.file_name(&hash))
+            .exists()
+    );
+    let perceptual_hash: Option<i64> =
+        sqlx::query_scalar("SELECT perceptual_hash FROM printings WHERE image_url = ?")
+            .bind(image_store::image_url(&hash))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(perceptual_hash.is_some());
+
+    std::fs::remove_dir_all(image_dir).unwrap();
+}
+
+/// Encodes a PNG with large shapes, like card art, so that perceptual hashes differ.
+fn picture(width: u32, height: u32, inverted: bool) -> Vec<u8> {
+    let image = image::RgbImage::from_fn(width, height, |x, y| {
+        let (fx, fy) = (x as f64 / width as f64, y as f64 / height as f64);
+        let inside = (fx - 0.35).powi(2) + (fy - 0.4).powi(2) < 0.06 || fy > 0.8;
+        let value = if inside != inverted { 220 } else { 40 };
+        image::Rgb([value, (fx * 200.0) as u8, (fy * 200.0) as u8])
+    });
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut bytes, image::ImageFormat::Png).unwrap();
+    bytes.into_inner()
+}
+
+#[test]
+fn test_perceptual_hash_survives_resizing() {
+    let decode = |bytes: Vec<u8>| image::load_from_memory(&bytes).unwrap();
+    let original = perceptual_hash::perceptual_hash(&decode(picture(630, 880, false)));
+    let resized = perceptual_hash::perceptual_hash(&decode(picture(126, 176, false)).brighten(20));
+    assert!(perceptual_hash::hamming_distance(original, resized) <= 4);
+
+    let different = perceptual_hash::perceptual_hash(&decode(picture(630, 880, true)));
+    assert!(perceptual_hash::hamming_distance(original, different) > 16);
+}
+
+#[tokio::test]
+async fn test_identify_card_from_photo() {
+    let mut state = common::setup_test_env().await;
+    let image_dir =
+        std::env::temp_dir().join(format!("llocg-identify-test-{}", std::process::id()));
+    state.image_dir = image_dir.clone();
+    let app = create_router(state);
+
+    let mut printing_ids = Vec::new();
+    for (number, inverted) in [("001", false), ("002", true)] {
+        let card_id = common::create_honoka(&app, &format!("PL!-BP01-{number}-R")).await;
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/cards/{card_id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let card: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let printing_id = card["printings"][0]["id"].as_i64().unwrap();
+        printing_ids.push(printing_id);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!("/printings/{printing_id}/image"))
+                    .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
+                    .body(Body::from(picture(630, 880, inverted)))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let identify = |uri: &str, photo: Vec<u8>| {
+        app.clone().oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri(uri)
+                .body(Body::from(photo))
+                .unwrap(),
+        )
+    };
+
+    // 1. A smaller copy of the first image matches its printing first.
+    let response = identify("/printings/identify", picture(315, 440, false))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let matches: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let matches = matches.as_array().unwrap();
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0]["printing_id"], printing_ids[0]);
+    assert_eq!(matches[0]["card"]["number_in_set"], "001");
+    assert!(matches[0]["distance"].as_u64().unwrap() < matches[1]["distance"].as_u64().unwrap());
+
+    // 2. The number of matches can be limited.
+    let response = identify("/printings/identify?limit=1", picture(315, 440, true))
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let matches: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(matches.as_array().unwrap().len(), 1);
+    assert_eq!(matches[0]["printing_id"], printing_ids[1]);
+
+    // 3. Bodies that are not images are rejected.
+    let response = identify("/printings/identify", b"not an image".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    std::fs::remove_dir_all(image_dir).unwrap();
+}
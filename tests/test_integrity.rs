@@ -0,0 +1,54 @@
+use llocg_backend_api::{create_router, integrity};
+
+mod common;
+
+#[tokio::test]
+async fn test_check_database() {
+    let state = common::setup_test_env().await;
+    let pool = state.pool.clone();
+    let app = create_router(state);
+
+    common::create_honoka(&app, "PL!-BP01-001-R").await;
+
+    // 1. Data created through the API is consistent.
+    assert_eq!(integrity::check_database(&pool).await.unwrap(), vec![]);
+
+    // 2. Rows written around the API are reported.
+    sqlx::query(
+        "INSERT INTO cards (series_code, set_code, number_in_set, name_id, card_type)
+         SELECT 'PL!', 'XX99', '001', name_id, 'Character' FROM cards LIMIT 1",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Rarities added for existing printings by the migration keep a placeholder name and
+    // rank until described.
+    sqlx::query(
+        "INSERT INTO rarities (rarity_code, rarity_type, display_name, sort_rank)
+         VALUES ('XR', 'Regular', 'XR', 1000)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let problems = integrity::check_database(&pool).await.unwrap();
+    assert!(problems.contains(&integrity::IntegrityProblem {
+        check: "placeholder_rarity",
+        detail: "rarity XR has a placeholder display name and sort rank".to_string(),
+    }));
+    let problems: Vec<_> = problems
+        .into_iter()
+        .filter(|problem| problem.check != "placeholder_rarity")
+        .collect();
+    let checks: Vec<&str> = problems.iter().map(|problem| problem.check).collect();
+    assert!(checks.contains(&"card_set"));
+    assert!(checks.contains(&"card_type_data"));
+    assert!(checks.contains(&"card_hearts"));
+    assert!(checks.contains(&"card_printings"));
+    assert!(
+        problems
+            .iter()
+            .all(|problem| problem.detail.starts_with("PL!-XX99-001"))
+    );
+}
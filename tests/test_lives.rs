@@ -0,0 +1,102 @@
+use axum::{
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use llocg_backend_api::{
+    create_router,
+    models::{HeartColor, RequirementCheck},
+};
+use std::collections::HashMap;
+use tower::ServiceExt; // for `oneshot`
+
+mod common;
+
+#[tokio::test]
+async fn test_lives_check_endpoint() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let member_id = common::create_honoka_with(
+        &app,
+        "PL!-BP01-001-R",
+        serde_json::json!({ "hearts": { "Pink": 2, "Red": 1 } }),
+    )
+    .await;
+    let live_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-019-L",
+            "name": "Snow halation",
+            "card_type": "Live",
+            "hearts": { "Pink": 2, "Blue": 2, "Gray": 1 },
+            "score": 2
+        }"#,
+    )
+    .await;
+
+    // 1. The member alone is one Blue short after the wildcard blade heart.
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/lives/check")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(format!(
+                    r#"{{ "stage": [{member_id}], "live_card_id": {live_id}, "blade_hearts": ["All"] }}"#
+                )))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let check: RequirementCheck = serde_json::from_slice(&body).unwrap();
+    assert!(!check.satisfied);
+    assert_eq!(check.missing, HashMap::from([(HeartColor::Blue, 1)]));
+    assert!(check.leftover.is_empty());
+
+    // 2. A Blue blade heart on top makes the Live payable.
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/lives/check")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(format!(
+                    r#"{{ "stage": [{member_id}], "live_card_id": {live_id}, "blade_hearts": ["All", "Blue"] }}"#
+                )))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let check: RequirementCheck = serde_json::from_slice(&body).unwrap();
+    assert!(check.satisfied);
+    assert!(check.missing.is_empty());
+
+    // 3. A member card cannot be checked as a Live.
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/lives/check")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(format!(
+                    r#"{{ "stage": [{member_id}], "live_card_id": {member_id} }}"#
+                )))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
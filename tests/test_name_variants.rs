@@ -47,6 +47,7 @@ async fn test_name_variants_endpoints() {
             Request::builder()
                 .method(http::Method::POST)
                 .uri("/variants/names")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .header(http::header::CONTENT_TYPE, "application/json")
                 .body(Body::from(
                     r#"{"variant_name": "Test Variant", "canonical_name": "Test Canonical"}"#,
@@ -88,6 +89,7 @@ async fn test_name_variants_endpoints() {
             Request::builder()
                 .method(http::Method::DELETE)
                 .uri("/variants/names/Test%20Variant") // URL encode the space
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .body(Body::empty())
                 .unwrap(),
         )
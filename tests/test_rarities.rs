@@ -2,8 +2,10 @@ use axum::{
     body::Body,
     http::{self, Request, StatusCode},
 };
-use llocg_backend_api::{create_router, models::RarityType};
-use std::collections::HashMap;
+use llocg_backend_api::{
+    create_router,
+    models::{Rarity, RarityType},
+};
 use tower::ServiceExt; // for `oneshot`
 
 mod common;
@@ -13,7 +15,7 @@ async fn test_rarities_endpoints() {
     let state = common::setup_test_env().await;
     let app = create_router(state);
 
-    // 1. Initially, GET all rarities should return an empty map.
+    // 1. Initially, GET all rarities should return the seeded ones, by rank.
     let response = app
         .clone()
         .oneshot(
@@ -29,11 +31,20 @@ async fn test_rarities_endpoints() {
     let body = axum::body::to_bytes(response.into_body(), usize::MAX)
         .await
         .unwrap();
-    // Deserialize the response body into a HashMap for type-safe assertions.
-    let rarities: HashMap<String, RarityType> = serde_json::from_slice(&body).unwrap();
-    assert_eq!(rarities.len(), 2);
-    assert_eq!(rarities.get("P"), Some(&RarityType::Parallel));
-    assert_eq!(rarities.get("LLE"), Some(&RarityType::Parallel));
+    // Deserialize the response body into rarities for type-safe assertions.
+    let rarities: Vec<Rarity> = serde_json::from_slice(&body).unwrap();
+    let seeded = rarities.len();
+    assert_eq!(rarities[0].rarity_code, "N");
+    assert!(
+        rarities
+            .windows(2)
+            .all(|w| w[0].sort_rank <= w[1].sort_rank)
+    );
+    let sec = rarities.iter().find(|r| r.rarity_code == "SEC").unwrap();
+    assert_eq!(sec.display_name, "Secret Rare");
+    assert!(sec.is_secret);
+    let lle = rarities.iter().find(|r| r.rarity_code == "LLE").unwrap();
+    assert_eq!(lle.rarity_type, RarityType::Parallel);
 
     // 2. POST a new rarity.
     let response = app
@@ -42,9 +53,10 @@ async fn test_rarities_endpoints() {
             Request::builder()
                 .method(http::Method::POST)
                 .uri("/rarities")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .header(http::header::CONTENT_TYPE, "application/json")
                 .body(Body::from(
-                    r#"{"rarity_code": "TEST", "rarity_type": "Regular"}"#,
+                    r#"{"rarity_code": "TEST", "rarity_type": "Regular", "sort_rank": 5}"#,
                 ))
                 .unwrap(),
         )
@@ -69,11 +81,11 @@ async fn test_rarities_endpoints() {
     let body = axum::body::to_bytes(response.into_body(), usize::MAX)
         .await
         .unwrap();
-    let rarities: HashMap<String, RarityType> = serde_json::from_slice(&body).unwrap();
-    assert_eq!(rarities.len(), 3);
-    assert_eq!(rarities.get("P"), Some(&RarityType::Parallel));
-    assert_eq!(rarities.get("LLE"), Some(&RarityType::Parallel));
-    assert_eq!(rarities.get("TEST"), Some(&RarityType::Regular));
+    let rarities: Vec<Rarity> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(rarities.len(), seeded + 1);
+    assert_eq!(rarities[0].rarity_code, "TEST");
+    assert_eq!(rarities[0].display_name, "TEST");
+    assert_eq!(rarities[0].rarity_type, RarityType::Regular);
 
     // 4. DELETE the rarity.
     let response = app
@@ -82,6 +94,7 @@ async fn test_rarities_endpoints() {
             Request::builder()
                 .method(http::Method::DELETE)
                 .uri("/rarities/TEST")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .body(Body::empty())
                 .unwrap(),
         )
@@ -106,8 +119,162 @@ async fn test_rarities_endpoints() {
     let body = axum::body::to_bytes(response.into_body(), usize::MAX)
         .await
         .unwrap();
-    let rarities: HashMap<String, RarityType> = serde_json::from_slice(&body).unwrap();
-    assert_eq!(rarities.len(), 2);
-    assert_eq!(rarities.get("P"), Some(&RarityType::Parallel));
-    assert_eq!(rarities.get("LLE"), Some(&RarityType::Parallel));
+    let rarities: Vec<Rarity> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(rarities.len(), seeded);
+    assert!(rarities.iter().all(|r| r.rarity_code != "TEST"));
+
+    // 6. Unknown rarities are not found, rather than read as regular ones.
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/rarities/TEST")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_set_rarities() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let card = |identifier: &str| {
+        common::honoka(identifier, serde_json::json!({ "groups": ["Love Live!"] }))
+    };
+
+    // 1. Cards with unknown rarities are refused.
+    let response = common::send(
+        &app,
+        http::Method::POST,
+        "/cards",
+        &card("PL!-BP01-001-XYZ"),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // 2. Sets can declare their rarities, which must exist.
+    let response = common::send(
+        &app,
+        http::Method::PUT,
+        "/sets/BP01/rarities",
+        r#"{ "rarities": ["R", "XYZ"] }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = common::send(
+        &app,
+        http::Method::PUT,
+        "/sets/BP01/rarities",
+        r#"{ "rarities": ["SEC", "R", "N"] }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = common::send(
+        &app,
+        http::Method::PUT,
+        "/sets/BP99/rarities",
+        r#"{ "rarities": [] }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let rarities: Vec<Rarity> =
+        serde_json::from_value(common::get_json(&app, "/sets/BP01/rarities").await).unwrap();
+    let codes: Vec<_> = rarities.iter().map(|r| r.rarity_code.as_str()).collect();
+    assert_eq!(codes, ["N", "R", "SEC"]);
+
+    // 3. Cards of the set can then only have one of them; other sets accept any.
+    let response = common::send(&app, http::Method::POST, "/cards", &card("PL!-BP01-001-R")).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = common::send(&app, http::Method::POST, "/cards", &card("PL!-BP01-002-P")).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = common::send(&app, http::Method::POST, "/cards", &card("PL!-BP02-001-P")).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // 4. Clearing the list accepts any known rarity again.
+    let response = common::send(
+        &app,
+        http::Method::PUT,
+        "/sets/BP01/rarities",
+        r#"{ "rarities": [] }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = common::send(&app, http::Method::POST, "/cards", &card("PL!-BP01-002-P")).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_reassigning_keeps_set_rarities() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    let response = common::send(
+        &app,
+        http::Method::PUT,
+        "/sets/BP02/rarities",
+        r#"{ "rarities": ["R", "P"] }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let moved_id = common::create_honoka(&app, "PL!-BP01-001-SEC").await;
+    let parallel_id = common::create_honoka(&app, "PL!-BP02-001-P").await;
+
+    // 1. Cards are not moved to a set that does not declare their rarities.
+    let response = common::send(
+        &app,
+        http::Method::DELETE,
+        "/sets/BP01?reassign_to=BP02",
+        "",
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let card = common::get_json(&app, &format!("/cards/{moved_id}")).await;
+    assert_eq!(card["set_code"], "BP01");
+
+    // 2. Printings are not moved to a rarity their set does not declare.
+    let response = common::send(
+        &app,
+        http::Method::DELETE,
+        "/rarities/P?reassign_to=SEC",
+        "",
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let card = common::get_json(&app, &format!("/cards/{parallel_id}")).await;
+    assert_eq!(card["printings"][0]["rarity_code"], "P");
+    let response = common::send(&app, http::Method::DELETE, "/rarities/P?reassign_to=R", "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let card = common::get_json(&app, &format!("/cards/{parallel_id}")).await;
+    assert_eq!(card["printings"][0]["rarity_code"], "R");
+}
+
+#[tokio::test]
+async fn test_declared_rarities_keep_those_in_use() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    for identifier in ["PL!-BP01-001-R", "PL!-BP01-002-SEC", "PL!-BP01-003-P"] {
+        common::create_honoka(&app, identifier).await;
+    }
+    let set_rarities =
+        |body: &'static str| common::send(&app, http::Method::PUT, "/sets/BP01/rarities", body);
+
+    // 1. Leaving out rarities that printings of the set have is refused, listing them.
+    let response = set_rarities(r#"{ "rarities": ["N", "R"] }"#).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).ends_with("P, SEC"));
+    let rarities = common::get_json(&app, "/sets/BP01/rarities").await;
+    assert!(rarities.as_array().unwrap().is_empty());
+
+    // 2. Lists keeping every rarity in use are accepted.
+    let response = set_rarities(r#"{ "rarities": ["R", "P", "SEC"] }"#).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
 }
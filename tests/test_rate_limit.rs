@@ -0,0 +1,51 @@
+use axum::http::{self, StatusCode};
+use llocg_backend_api::{
+    create_router,
+    rate_limit::{Quota, RateLimitConfig, RateLimiter},
+};
+use std::sync::Arc;
+
+mod common;
+
+#[tokio::test]
+async fn test_rate_limiting() {
+    let mut state = common::setup_test_env().await;
+    state.rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+        read: Quota::new(1, 2),
+        write: Quota::new(1, 1),
+        trust_proxy: false,
+    }));
+    let app = create_router(state);
+
+    let send = |method: http::Method, auth: Option<&'static str>| {
+        common::send_as(&app, auth, method, "/groups", r#"{ "name": "Test" }"#)
+    };
+
+    // 1. Every response reports the remaining quota.
+    let response = send(http::Method::GET, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ratelimit-limit"], "2");
+    assert_eq!(response.headers()["ratelimit-remaining"], "1");
+    assert_eq!(response.headers()["ratelimit-reset"], "60");
+
+    // 2. Once the burst is spent, reads are refused until a token is earned back.
+    let response = send(http::Method::GET, None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = send(http::Method::GET, None).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["ratelimit-remaining"], "0");
+    assert_eq!(response.headers()[http::header::RETRY_AFTER], "60");
+
+    // 3. Writes have a bucket of their own.
+    let response = send(http::Method::POST, Some(common::ADMIN_AUTH)).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.headers()["ratelimit-limit"], "1");
+    let response = send(http::Method::POST, Some(common::ADMIN_AUTH)).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // 4. Clients with a valid API key are limited apart from anonymous ones.
+    let response = send(http::Method::GET, Some(common::ADMIN_AUTH)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = send(http::Method::GET, Some("Bearer llocg_test0admin_wrong")).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
@@ -0,0 +1,246 @@
+use axum::{
+    Router,
+    http::{self, StatusCode},
+};
+use llocg_backend_api::create_router;
+
+mod common;
+
+/// Creates a card in set BP01 with the group "Love Live!" and the unit "Printemps", and
+/// returns its URI.
+async fn create_honoka(app: &Router) -> String {
+    let card_id = common::create_honoka_with(
+        app,
+        "PL!-BP01-001-R",
+        serde_json::json!({ "groups": ["Love Live!"], "units": ["Printemps"] }),
+    )
+    .await;
+    format!("/cards/{card_id}")
+}
+
+#[tokio::test]
+async fn test_renaming_requires_a_key() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_honoka(&app).await;
+
+    let response = common::send_as(
+        &app,
+        None,
+        http::Method::PUT,
+        "/units/Printemps",
+        r#"{ "name": "Printemps!" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_renamed_groups_keep_their_cards_and_variants() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    let card_uri = create_honoka(&app).await;
+
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PATCH,
+            "/groups/Love%20Live!",
+            r#"{ "name": "μ's", "keep_old_as_variant": true }"#,
+        )
+        .await
+        .status(),
+        StatusCode::NO_CONTENT
+    );
+    let groups = common::get_json(&app, "/groups").await;
+    assert!(groups.as_array().unwrap().contains(&"μ's".into()));
+    assert!(!groups.as_array().unwrap().contains(&"Love Live!".into()));
+    assert_eq!(
+        common::get_json(&app, &card_uri).await["groups"],
+        serde_json::json!(["μ's"])
+    );
+    let variants = common::get_json(&app, "/variants/groups").await;
+    assert_eq!(variants["Love Live!"], "μ's");
+    assert_eq!(variants["ラブライブ！"], "μ's");
+
+    // The old name now resolves to the new one.
+    let card_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-002-R",
+            "name": "Ayase Eli",
+            "card_type": "Character",
+            "cost": 4,
+            "blades": 2,
+            "hearts": { "Blue": 2 },
+            "groups": ["Love Live!"]
+        }"#,
+    )
+    .await;
+    let card = common::get_json(&app, &format!("/cards/{card_id}")).await;
+    assert_eq!(card["groups"], serde_json::json!(["μ's"]));
+}
+
+#[tokio::test]
+async fn test_renamed_units_keep_their_cards() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    let card_uri = create_honoka(&app).await;
+
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PUT,
+            "/units/Printemps",
+            r#"{ "name": "Printemps!" }"#
+        )
+        .await
+        .status(),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        common::get_json(&app, &card_uri).await["units"],
+        serde_json::json!(["Printemps!"])
+    );
+    assert!(
+        common::get_json(&app, "/units")
+            .await
+            .as_array()
+            .unwrap()
+            .contains(&"Printemps!".into())
+    );
+}
+
+#[tokio::test]
+async fn test_taken_names_and_missing_units_are_refused() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_honoka(&app).await;
+
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PUT,
+            "/units/Printemps",
+            r#"{ "name": "BiBi" }"#
+        )
+        .await
+        .status(),
+        StatusCode::CONFLICT
+    );
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PUT,
+            "/units/Unknown",
+            r#"{ "name": "Other" }"#
+        )
+        .await
+        .status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn test_renaming_to_a_trashed_group_points_at_the_trash() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    let card_uri = create_honoka(&app).await;
+
+    let response = common::send(&app, http::Method::POST, "/groups", r#"{ "name": "μ's" }"#).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = common::send(&app, http::Method::DELETE, "/groups/μ's", "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // The trashed group still holds its name.
+    let response = common::send(
+        &app,
+        http::Method::PATCH,
+        "/groups/Love%20Live!",
+        r#"{ "name": "μ's" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("/trash/group/μ's/restore"));
+    assert_eq!(
+        common::get_json(&app, &card_uri).await["groups"],
+        serde_json::json!(["Love Live!"])
+    );
+
+    // Once purged, the name is free again.
+    let response = common::send(&app, http::Method::DELETE, "/trash/group/μ's", "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = common::send(
+        &app,
+        http::Method::PATCH,
+        "/groups/Love%20Live!",
+        r#"{ "name": "μ's" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        common::get_json(&app, &card_uri).await["groups"],
+        serde_json::json!(["μ's"])
+    );
+}
+
+#[tokio::test]
+async fn test_sets_move_their_cards_to_a_new_name_and_code() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    let card_uri = create_honoka(&app).await;
+
+    assert_eq!(
+        common::send(&app, http::Method::PATCH, "/sets/BP01", "{}")
+            .await
+            .status(),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PATCH,
+            "/sets/BP01",
+            r#"{ "name": "Booster Pack Vol.1" }"#
+        )
+        .await
+        .status(),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PATCH,
+            "/sets/BP01",
+            r#"{ "set_code": "BP02" }"#
+        )
+        .await
+        .status(),
+        StatusCode::CONFLICT
+    );
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PUT,
+            "/sets/BP01",
+            r#"{ "set_code": "BP01R", "name": "Booster Pack Vol.1 Reprint" }"#,
+        )
+        .await
+        .status(),
+        StatusCode::NO_CONTENT
+    );
+    let card = common::get_json(&app, &card_uri).await;
+    assert_eq!(card["set_code"], "BP01R");
+    assert_eq!(card["set_name"], "Booster Pack Vol.1 Reprint");
+    let sets = common::get_json(&app, "/sets").await;
+    let sets = sets.as_array().unwrap();
+    assert!(sets.iter().any(|set| set["set_code"] == "BP01R"));
+    assert!(!sets.iter().any(|set| set["set_code"] == "BP01"));
+
+    // Each change to the card is recorded in its history.
+    let history = common::get_json(&app, &format!("{card_uri}/history")).await;
+    assert_eq!(history.as_array().unwrap().len(), 3);
+}
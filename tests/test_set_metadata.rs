@@ -0,0 +1,216 @@
+use axum::{
+    Router,
+    http::{self, StatusCode},
+};
+use llocg_backend_api::create_router;
+use serde_json::Value;
+
+mod common;
+
+/// Finds a set in the list of all sets by its code.
+async fn get_set(app: &Router, set_code: &str) -> Value {
+    let sets = common::get_json(app, "/sets").await;
+    sets.as_array()
+        .unwrap()
+        .iter()
+        .find(|set| set["set_code"] == set_code)
+        .unwrap()
+        .clone()
+}
+
+/// Creates the set BP05 with `total_cards` and a card for each of `numbers` in it.
+async fn create_set_with_cards(app: &Router, total_cards: i64, numbers: &[&str]) {
+    assert_eq!(
+        common::send(
+            app,
+            http::Method::POST,
+            "/sets",
+            &format!(
+                r#"{{ "set_code": "BP05", "name": "Booster Pack Vol.5", "total_cards": {total_cards} }}"#
+            ),
+        )
+        .await
+        .status(),
+        StatusCode::CREATED
+    );
+    for number in numbers {
+        common::create_honoka_with(
+            app,
+            &format!("PL!-BP05-{number}-R"),
+            serde_json::json!({ "groups": ["Love Live!"] }),
+        )
+        .await;
+    }
+}
+
+fn checklist_numbers(checklist: &Value) -> Vec<Value> {
+    checklist["cards"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|card| card["number_in_set"].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sets_are_created_with_their_metadata() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::POST,
+            "/sets",
+            r#"{
+                "set_code": "BP05",
+                "name": "Booster Pack Vol.5",
+                "release_date": "2025-10-24",
+                "product_type": "Booster",
+                "total_cards": 3
+            }"#,
+        )
+        .await
+        .status(),
+        StatusCode::CREATED
+    );
+    let set = get_set(&app, "BP05").await;
+    assert_eq!(set["release_date"], "2025-10-24");
+    assert_eq!(set["product_type"], "Booster");
+    assert_eq!(set["total_cards"], 3);
+    assert_eq!(set["language"], "ja");
+}
+
+#[tokio::test]
+async fn test_invalid_dates_and_totals_are_refused() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    for body in [
+        r#"{ "set_code": "BP05", "name": "Booster Pack Vol.5", "release_date": "2025-13-40" }"#,
+        r#"{ "set_code": "BP05", "name": "Booster Pack Vol.5", "total_cards": -1 }"#,
+    ] {
+        assert_eq!(
+            common::send(&app, http::Method::POST, "/sets", body)
+                .await
+                .status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PATCH,
+            "/sets/BP04",
+            r#"{ "total_cards": 1000000000 }"#
+        )
+        .await
+        .status(),
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[tokio::test]
+async fn test_seeded_sets_are_typed_by_their_code() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    assert_eq!(get_set(&app, "PR").await["product_type"], "Promo");
+}
+
+#[tokio::test]
+async fn test_sets_sort_with_undated_sets_last() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_set_with_cards(&app, 3, &[]).await;
+    for (set_code, release_date) in [("BP05", "2025-10-24"), ("BP04", "2025-07-25")] {
+        assert_eq!(
+            common::send(
+                &app,
+                http::Method::PATCH,
+                &format!("/sets/{set_code}"),
+                &format!(r#"{{ "release_date": "{release_date}" }}"#),
+            )
+            .await
+            .status(),
+            StatusCode::NO_CONTENT
+        );
+    }
+
+    let sets = common::get_json(&app, "/sets?sort=release_date&order=desc").await;
+    let codes: Vec<_> = sets
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|set| set["set_code"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(codes[..2], ["BP05", "BP04"]);
+    assert!(sets[2]["release_date"].is_null());
+    let sets = common::get_json(&app, "/sets?sort=set_code").await;
+    assert_eq!(sets[0]["set_code"], "BP01");
+}
+
+#[tokio::test]
+async fn test_updates_keep_left_out_fields_and_clear_null_ones() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    for body in [
+        r#"{ "release_date": "2025-07-25" }"#,
+        r#"{ "release_date": null, "total_cards": 100 }"#,
+        r#"{ "name": "Vol.4" }"#,
+    ] {
+        assert_eq!(
+            common::send(&app, http::Method::PATCH, "/sets/BP04", body)
+                .await
+                .status(),
+            StatusCode::NO_CONTENT
+        );
+    }
+    let set = get_set(&app, "BP04").await;
+    assert_eq!(set["name"], "Vol.4");
+    assert!(set["release_date"].is_null());
+    assert_eq!(set["total_cards"], 100);
+    assert_eq!(set["product_type"], "Booster");
+}
+
+#[tokio::test]
+async fn test_checklists_list_cards_in_number_order_with_missing_numbers() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_set_with_cards(&app, 3, &["003", "001"]).await;
+
+    let checklist = common::get_json(&app, "/sets/BP05/checklist").await;
+    assert_eq!(checklist["set_code"], "BP05");
+    assert_eq!(checklist_numbers(&checklist), ["001", "003"]);
+    let card = &checklist["cards"][0];
+    assert_eq!(card["card_identifier"], "PL!-BP05-001");
+    assert_eq!(card["name"], "Kousaka Honoka");
+    assert_eq!(card["printings"].as_array().unwrap().len(), 1);
+    assert_eq!(card["printings"][0]["rarity_code"], "R");
+    assert_eq!(checklist["missing_numbers"], serde_json::json!([2]));
+}
+
+#[tokio::test]
+async fn test_checklists_list_cards_numbered_past_the_total() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_set_with_cards(&app, 2, &["001", "005"]).await;
+
+    let checklist = common::get_json(&app, "/sets/BP05/checklist").await;
+    assert_eq!(checklist_numbers(&checklist), ["001", "005"]);
+    assert_eq!(checklist["missing_numbers"], serde_json::json!([2]));
+}
+
+#[tokio::test]
+async fn test_checklists_of_unknown_sets_are_not_found() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+
+    assert_eq!(
+        common::send(&app, http::Method::GET, "/sets/BP99/checklist", "")
+            .await
+            .status(),
+        StatusCode::NOT_FOUND
+    );
+}
@@ -38,6 +38,7 @@ async fn test_sets_endpoints() {
             Request::builder()
                 .method(http::Method::POST)
                 .uri("/sets")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .header(http::header::CONTENT_TYPE, "application/json")
                 .body(Body::from(
                     r#"{"set_code": "test-set", "name": "Test Set"}"#,
@@ -77,6 +78,7 @@ async fn test_sets_endpoints() {
             Request::builder()
                 .method(http::Method::POST)
                 .uri("/sets")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .header(http::header::CONTENT_TYPE, "application/json")
                 .body(Body::from(
                     r#"{"set_code": "test-set", "name": "Another Test Set"}"#,
@@ -95,6 +97,7 @@ async fn test_sets_endpoints() {
             Request::builder()
                 .method(http::Method::DELETE)
                 .uri("/sets/test-set")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .body(Body::empty())
                 .unwrap(),
         )
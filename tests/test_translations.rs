@@ -0,0 +1,253 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{self, Request, StatusCode},
+};
+use llocg_backend_api::create_router;
+use tower::ServiceExt; // for `oneshot`
+
+mod common;
+
+/// Gets a resource in the languages of an `Accept-Language` header and returns its JSON.
+async fn get_localized(app: &Router, uri: &str, accept_language: &str) -> serde_json::Value {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header(http::header::ACCEPT_LANGUAGE, accept_language)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    common::read_json(response).await
+}
+
+/// Creates a card with translations of its name, set, group and first skill, and returns
+/// its URI.
+async fn create_translated_card(app: &Router) -> String {
+    let card_id = common::create_card(
+        app,
+        r#"{
+            "card_identifier": "PL!-BP01-001-R",
+            "name": "高坂穂乃果",
+            "card_type": "Character",
+            "cost": 4,
+            "blades": 2,
+            "hearts": { "Pink": 2 },
+            "groups": ["Love Live!"],
+            "units": ["Printemps"],
+            "skills": ["カードを1枚引く。", "ブレードを得る。"]
+        }"#,
+    )
+    .await;
+    for translation in [
+        r#"{ "kind": "name", "key": "高坂穂乃果", "language": "EN", "text": "Honoka" }"#,
+        r#"{ "kind": "name", "key": "高坂穂乃果", "language": "en", "text": "Honoka Kosaka" }"#,
+        r#"{ "kind": "name", "key": "高坂穂乃果", "language": "zh-tw", "text": "高坂穗乃果" }"#,
+        r#"{ "kind": "set", "key": "BP01", "language": "en", "text": "Booster Pack Volume 1" }"#,
+        r#"{ "kind": "group", "key": "Love Live!", "language": "zh", "text": "LoveLive!" }"#,
+        r#"{ "kind": "skill", "key": "カードを1枚引く。", "language": "en", "text": "Draw a card." }"#,
+    ] {
+        assert_eq!(
+            common::send(app, http::Method::PUT, "/translations", translation)
+                .await
+                .status(),
+            StatusCode::NO_CONTENT
+        );
+    }
+    format!("/cards/{card_id}")
+}
+
+/// Sends a request as admin, asserts it succeeded and returns its JSON.
+async fn json(app: &Router, method: http::Method, uri: &str, body: &str) -> serde_json::Value {
+    let (status, json) = common::send_json(app, method, uri, body).await;
+    assert!(status.is_success());
+    json
+}
+
+#[tokio::test]
+async fn test_submitting_translations_needs_a_key_a_valid_language_and_a_text() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_translated_card(&app).await;
+
+    let response = common::send_as(
+        &app,
+        None,
+        http::Method::PUT,
+        "/translations",
+        r#"{ "kind": "name", "key": "高坂穂乃果", "language": "en", "text": "Honoka Kosaka" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PUT,
+            "/translations",
+            r#"{ "kind": "name", "key": "高坂穂乃果", "language": "en_US", "text": "Honoka Kosaka" }"#,
+        )
+        .await
+        .status(),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        common::send(
+            &app,
+            http::Method::PUT,
+            "/translations",
+            r#"{ "kind": "name", "key": "Unknown", "language": "en", "text": "Unknown" }"#,
+        )
+        .await
+        .status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn test_translations_are_listed_with_the_latest_of_each_kept() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    create_translated_card(&app).await;
+
+    let translations = common::get_json(&app, "/translations?kind=name").await;
+    assert_eq!(
+        translations,
+        serde_json::json!([
+            { "kind": "name", "key": "高坂穂乃果", "language": "en", "text": "Honoka Kosaka" },
+            { "kind": "name", "key": "高坂穂乃果", "language": "zh-tw", "text": "高坂穗乃果" },
+        ])
+    );
+    let translations = common::get_json(&app, "/translations?language=en").await;
+    assert_eq!(translations.as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_cards_are_localized_by_lang_then_accept_language() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    let card_uri = create_translated_card(&app).await;
+
+    let card = common::get_json(&app, &card_uri).await;
+    assert_eq!(card["name"], "高坂穂乃果");
+    assert_eq!(card["set_name"], "Booster Pack vol.1");
+
+    let card = get_localized(&app, &card_uri, "en-US,en;q=0.9").await;
+    assert_eq!(card["name"], "Honoka Kosaka");
+    assert_eq!(card["set_name"], "Booster Pack Volume 1");
+    assert_eq!(card["groups"], serde_json::json!(["Love Live!"]));
+    assert_eq!(card["units"], serde_json::json!(["Printemps"]));
+    assert_eq!(
+        card["skills"],
+        serde_json::json!(["Draw a card.", "ブレードを得る。"])
+    );
+
+    let card = get_localized(&app, &format!("{card_uri}?lang=zh-TW"), "en").await;
+    assert_eq!(card["name"], "高坂穗乃果");
+    assert_eq!(card["groups"], serde_json::json!(["LoveLive!"]));
+    assert_eq!(card["skills"][0], "カードを1枚引く。");
+
+    let card = get_localized(&app, &card_uri, "fr, en;q=0.5").await;
+    assert_eq!(card["name"], "Honoka Kosaka");
+}
+
+#[tokio::test]
+async fn test_regional_languages_fall_back_to_their_base() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    let card_uri = create_translated_card(&app).await;
+
+    let card = get_localized(&app, &card_uri, "en-GB").await;
+    assert_eq!(card["name"], "Honoka Kosaka");
+    let card = get_localized(&app, &card_uri, "zh-HK").await;
+    assert_eq!(card["name"], "高坂穂乃果");
+    assert_eq!(card["groups"], serde_json::json!(["LoveLive!"]));
+}
+
+#[tokio::test]
+async fn test_languages_refused_with_q0_are_not_used() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    let card_uri = create_translated_card(&app).await;
+
+    let card = get_localized(&app, &card_uri, "en;q=0").await;
+    assert_eq!(card["name"], "高坂穂乃果");
+    let card = get_localized(&app, &card_uri, "en;q=0, zh-TW;q=0.5").await;
+    assert_eq!(card["name"], "高坂穗乃果");
+    let card = get_localized(&app, &card_uri, "en-US, en;q=0").await;
+    assert_eq!(card["name"], "高坂穂乃果");
+    assert_eq!(card["set_name"], "Booster Pack vol.1");
+}
+
+#[tokio::test]
+async fn test_collection_cards_are_localized_together() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    let card_uri = create_translated_card(&app).await;
+
+    // The second card has no translated name, but shares the set and first skill.
+    let other_id = common::create_card(
+        &app,
+        r#"{
+            "card_identifier": "PL!-BP01-002-R",
+            "name": "南ことり",
+            "card_type": "Character",
+            "cost": 2,
+            "blades": 1,
+            "hearts": { "Green": 1 },
+            "skills": ["カードを1枚引く。"]
+        }"#,
+    )
+    .await;
+    let collection = json(
+        &app,
+        http::Method::POST,
+        "/collections",
+        r#"{ "name": "Binder" }"#,
+    )
+    .await;
+    let collection_uri = format!("/collections/{}", collection["id"]);
+    for uri in [card_uri, format!("/cards/{other_id}")] {
+        let printing_id = &common::get_json(&app, &uri).await["printings"][0]["id"];
+        json(
+            &app,
+            http::Method::POST,
+            &format!("{collection_uri}/add"),
+            &format!(r#"{{ "printing_id": {printing_id}, "quantity": 1 }}"#),
+        )
+        .await;
+    }
+
+    let collection = common::get_json(&app, &format!("{collection_uri}?lang=en")).await;
+    let cards = &collection["cards"];
+    assert_eq!(cards[0]["card"]["name"], "Honoka Kosaka");
+    assert_eq!(cards[1]["card"]["name"], "南ことり");
+    assert_eq!(cards[1]["card"]["set_name"], "Booster Pack Volume 1");
+    assert_eq!(
+        cards[1]["card"]["skills"],
+        serde_json::json!(["Draw a card."])
+    );
+}
+
+#[tokio::test]
+async fn test_checklists_and_restored_cards_are_localized() {
+    let state = common::setup_test_env().await;
+    let app = create_router(state);
+    let card_uri = create_translated_card(&app).await;
+
+    let checklist = get_localized(&app, "/sets/BP01/checklist", "en").await;
+    assert_eq!(checklist["name"], "Booster Pack Volume 1");
+    assert_eq!(checklist["cards"][0]["name"], "Honoka Kosaka");
+
+    let card = json(
+        &app,
+        http::Method::POST,
+        &format!("{card_uri}/history/1/restore?lang=en"),
+        "",
+    )
+    .await;
+    assert_eq!(card["name"], "Honoka Kosaka");
+}
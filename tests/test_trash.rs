@@ -0,0 +1,120 @@
+use axum::http::{self, StatusCode};
+use llocg_backend_api::create_router;
+
+mod common;
+
+#[tokio::test]
+async fn test_trash() {
+    let state = common::setup_test_env().await;
+    let pool = state.pool.clone();
+    let app = create_router(state);
+
+    let has = |list: &serde_json::Value, name: &str| {
+        list.as_array()
+            .unwrap()
+            .iter()
+            .any(|item| item == name || item["set_code"] == name)
+    };
+
+    let response = common::send(
+        &app,
+        http::Method::POST,
+        "/sets",
+        r#"{ "set_code": "TS01", "name": "Test Set" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = common::send(
+        &app,
+        http::Method::POST,
+        "/groups",
+        r#"{ "name": "Test Group" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // 1. Deleted items leave the listings for the trash, and cannot be deleted twice.
+    let response = common::send(&app, http::Method::DELETE, "/sets/TS01", "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = common::send(&app, http::Method::DELETE, "/sets/TS01", "").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = common::send(&app, http::Method::DELETE, "/groups/Test%20Group", "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert!(!has(&common::get_json(&app, "/sets").await, "TS01"));
+    assert!(!has(&common::get_json(&app, "/groups").await, "Test Group"));
+    let trash = common::get_json(&app, "/trash").await;
+    assert_eq!(trash.as_array().unwrap().len(), 2);
+    let set = trash
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["kind"] == "set")
+        .unwrap();
+    assert_eq!(set["key"], "TS01");
+    assert_eq!(set["name"], "Test Set");
+
+    // 2. Cards cannot be created in deleted groups or sets, or in sets that do not exist.
+    for payload in [
+        common::honoka(
+            "PL!-BP01-001-R",
+            serde_json::json!({ "groups": ["Test Group"] }),
+        ),
+        common::honoka("PL!-TS01-001-R", serde_json::json!({})),
+        common::honoka("PL!-BP99-001-R", serde_json::json!({})),
+    ] {
+        let response = common::send(&app, http::Method::POST, "/cards", &payload).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    // 3. Deleted items still hold their keys, and re-adding one points at the trash.
+    let response = common::send(
+        &app,
+        http::Method::POST,
+        "/groups",
+        r#"{ "name": "Test Group" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("/trash/group/Test Group/restore"));
+
+    // 4. Restored items are listed again.
+    let response = common::send(&app, http::Method::POST, "/trash/set/TS01/restore", "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(has(&common::get_json(&app, "/sets").await, "TS01"));
+    let response = common::send(&app, http::Method::POST, "/trash/set/TS01/restore", "").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // 5. Purged items are gone for good, and their names can be reused.
+    let response = common::send(&app, http::Method::DELETE, "/trash/group/Test%20Group", "").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(
+        common::get_json(&app, "/trash")
+            .await
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+    let response = common::send(
+        &app,
+        http::Method::POST,
+        "/groups",
+        r#"{ "name": "Test Group" }"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // 6. Items that cards still belong to cannot be deleted, nor purged.
+    common::create_honoka(&app, "PL!-BP01-001-R").await;
+    let response = common::send(&app, http::Method::DELETE, "/sets/BP01", "").await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    sqlx::query("UPDATE sets SET deleted_at = CURRENT_TIMESTAMP WHERE set_code = 'BP01'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let response = common::send(&app, http::Method::DELETE, "/trash/set/BP01", "").await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
@@ -38,6 +38,7 @@ async fn test_units_endpoints() {
             Request::builder()
                 .method(http::Method::POST)
                 .uri("/units")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .header(http::header::CONTENT_TYPE, "application/json")
                 .body(Body::from(r#"{"name": "AiScream!"}"#))
                 .unwrap(),
@@ -74,6 +75,7 @@ async fn test_units_endpoints() {
             Request::builder()
                 .method(http::Method::POST)
                 .uri("/units")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .header(http::header::CONTENT_TYPE, "application/json")
                 .body(Body::from(r#"{"name": "AiScream!"}"#))
                 .unwrap(),
@@ -90,6 +92,7 @@ async fn test_units_endpoints() {
             Request::builder()
                 .method(http::Method::DELETE)
                 .uri("/units/AiScream!")
+                .header(http::header::AUTHORIZATION, common::ADMIN_AUTH)
                 .body(Body::empty())
                 .unwrap(),
         )